@@ -15,8 +15,8 @@
 
 //! Provide JS-Rust API bindings to create and encode Pact contract
 use trn_pact::types::{
-    opcode::{Comparator, Conjunction, OpCode, OpIndices},
-    Contract, DataTable, Numeric, PactType, StringLike,
+    opcode::{Comparator, Conjunction, Group, OpCode, OpIndices},
+    Contract, DataTable, Numeric, PactType, StringLike, U256,
 };
 use wasm_bindgen::prelude::*;
 
@@ -45,9 +45,11 @@ impl ContractJS {
         let mut data_table = Vec::<PactType>::new();
         // TODO: Find a better way to take input data table
         for item in input_data_table {
-            match item.parse::<u64>() {
-                Ok(number) => data_table.push(PactType::Numeric(Numeric(number))),
-                Err(_) => data_table.push(PactType::StringLike(StringLike(item.into_bytes()))),
+            match U256::from_dec_str(&item) {
+                Ok(number) if !item.is_empty() => {
+                    data_table.push(PactType::Numeric(Numeric::new(number)))
+                }
+                _ => data_table.push(PactType::StringLike(StringLike(item.into_bytes()))),
             }
         }
 
@@ -65,10 +67,13 @@ impl ContractJS {
     }
 
     /// encode the Pact contract
-    pub fn encode(&self) -> Vec<u8> {
+    /// Throws if a data table value can not be represented in the binary format
+    pub fn encode(&self) -> Result<Vec<u8>, JsError> {
         let mut payload = vec![];
-        self.0.encode(&mut payload);
-        payload
+        self.0
+            .try_encode(&mut payload)
+            .map_err(|err| JsError::new(&err.to_string()))?;
+        Ok(payload)
     }
 }
 
@@ -125,10 +130,10 @@ impl OpCodeComparator {
     }
 
     pub fn encode(&self) -> Vec<u8> {
-        let payload: u8 = OpCode::COMP(self.0).into();
-        // add indices
-        let indice: u8 = (self.0.indices.lhs << 4) | self.0.indices.rhs;
-        vec![payload, indice]
+        // opcode and indices, in the wide form if necessary
+        let mut payload = Vec::new();
+        OpCode::COMP(self.0).compile(&mut payload);
+        payload
     }
 }
 
@@ -156,3 +161,27 @@ impl OpCodeConjunction {
         vec![payload]
     }
 }
+
+#[wasm_bindgen(js_name = OpCodeGroup)]
+pub struct OpCodeGroup(Group);
+
+#[wasm_bindgen(js_class = OpCodeGroup)]
+impl OpCodeGroup {
+    #[wasm_bindgen(constructor)]
+    pub fn new(op: JsValue, invert: JsValue) -> Self {
+        console_error_panic_hook::set_once();
+        let op: u8 = serde_wasm_bindgen::from_value(op).expect("Deserialization of op failed");
+        let invert: bool =
+            serde_wasm_bindgen::from_value(invert).expect("Deserialization of invert failed");
+
+        OpCodeGroup(Group {
+            op: op.into(),
+            invert,
+        })
+    }
+
+    pub fn encode(self) -> Vec<u8> {
+        let payload: u8 = OpCode::GROUP(self.0).into();
+        vec![payload]
+    }
+}
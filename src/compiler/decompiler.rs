@@ -0,0 +1,543 @@
+// Copyright 2023-2024 Futureverse Corporation Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! The pact decompiler
+//!
+//! Prints a `Contract` as pact DSL source. Input parameters are named by the contract metadata
+//! of an `AnnotatedContract` or else by their index (`$input0`, `$input1`, ..) and data table
+//! values are written inline.
+//!
+use crate::interpreter::{InterpErr, MAX_GROUP_DEPTH};
+use crate::types::opcode::{
+    Comparator, Conjunction, Group, OpCode, OpComp, OpConj, OpGroup, OpLoad,
+};
+use crate::types::{AnnotatedContract, Contract, Metadata, PactType};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// A decompiler error
+#[derive(Debug, PartialEq)]
+pub enum DecompileErr {
+    /// The bytecode failed to parse
+    InvalidBytecode(InterpErr),
+    /// Encountered an OpCode which is not valid at this position
+    UnexpectedOpCode(u8),
+    /// The bytecode ended with a conjunction or an unclosed group
+    IncompleteClause,
+    /// A referenced index in the data table does not exist
+    MissingIndex(u8),
+    /// The OpCode has no equivalent in the DSL
+    Unrepresentable(u8),
+}
+
+impl From<InterpErr> for DecompileErr {
+    fn from(err: InterpErr) -> Self {
+        DecompileErr::InvalidBytecode(err)
+    }
+}
+
+/// Decompile `contract` into pact DSL source.
+///
+/// The source compiles back to the same bytes whenever the data table holds no duplicate
+/// or unused values and is ordered by first use, as it is for all compiler output.
+/// Otherwise it compiles to an equivalent contract.
+pub fn decompile(contract: &Contract) -> Result<String, DecompileErr> {
+    decompile_with(contract, None)
+}
+
+/// Decompile `contract` into pact DSL source, naming parameters from its metadata
+/// See `decompile`
+pub fn decompile_annotated(contract: &AnnotatedContract) -> Result<String, DecompileErr> {
+    decompile_with(&contract.contract, contract.metadata.as_ref())
+}
+
+/// Decompile `contract` into pact DSL source, naming parameters from `metadata` if any
+fn decompile_with(
+    contract: &Contract,
+    metadata: Option<&Metadata>,
+) -> Result<String, DecompileErr> {
+    let clauses = clauses(&contract.bytecode)?;
+
+    // Declare parameters up to the highest referenced input
+    let input_count = clauses
+        .iter()
+        .flatten()
+        .filter_map(|op| match op {
+            OpCode::COMP(comparator) => Some(comparator),
+            OpCode::CONJ(_)
+            | OpCode::GROUP(_)
+            | OpCode::REQUIRE(_)
+            | OpCode::ARITH(_)
+            | OpCode::MASK(_) => None,
+        })
+        .map(|comparator| match comparator.load {
+            OpLoad::INPUT_VS_USER => comparator.indices.lhs,
+            OpLoad::INPUT_VS_INPUT => comparator.indices.lhs.max(comparator.indices.rhs),
+        })
+        .max()
+        .map_or(1, |index| usize::from(index) + 1);
+    let parameters = parameter_names(metadata, input_count);
+
+    let mut source = format!("given parameters {}\n", parameters.join(", "));
+    for clause in clauses {
+        source.push('\n');
+        for op in clause {
+            match op {
+                OpCode::COMP(comparator) => {
+                    assertion(&mut source, contract, &parameters, &comparator)?
+                }
+                OpCode::CONJ(conjunction) => source.push_str(conjunctive(&conjunction)?),
+                OpCode::GROUP(Group {
+                    op: OpGroup::OPEN,
+                    invert,
+                }) => source.push_str(if invert { "not (" } else { "(" }),
+                OpCode::GROUP(Group {
+                    op: OpGroup::CLOSE, ..
+                }) => source.push(')'),
+                OpCode::REQUIRE(_) | OpCode::ARITH(_) | OpCode::MASK(_) => {
+                    return Err(DecompileErr::Unrepresentable(op.into()))
+                }
+            }
+        }
+    }
+    source.push('\n');
+    Ok(source)
+}
+
+/// Return names for at least `input_count` parameters, from the contract `metadata` when it
+/// holds enough distinct, valid identifiers, otherwise by input index
+fn parameter_names(metadata: Option<&Metadata>, input_count: usize) -> Vec<String> {
+    if let Some(metadata) = metadata {
+        let names = &metadata.parameters;
+        let valid = names.len() >= input_count
+            && names.iter().enumerate().all(|(i, name)| {
+                !name.is_empty()
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && !names[..i].contains(name)
+            });
+        if valid {
+            return names.iter().map(|name| format!("${}", name)).collect();
+        }
+    }
+    (0..input_count).map(|i| format!("$input{}", i)).collect()
+}
+
+/// Parse `bytecode` into clauses of terms joined by conjunctions,
+/// where a term is a comparator or a group
+fn clauses(bytecode: &[u8]) -> Result<Vec<Vec<OpCode>>, DecompileErr> {
+    let mut clauses = Vec::<Vec<OpCode>>::new();
+    let mut scanner = bytecode.iter();
+    // Whether the next OpCode must begin a term
+    let mut expect_term = false;
+    let mut depth = 0_usize;
+    while let Some(op) = OpCode::parse(&mut scanner)? {
+        let unexpected = DecompileErr::UnexpectedOpCode(op.into());
+        match op {
+            OpCode::COMP(_)
+            | OpCode::GROUP(Group {
+                op: OpGroup::OPEN, ..
+            }) => {
+                if !expect_term {
+                    // Only a term outside of any group may begin a new clause
+                    if depth > 0 {
+                        return Err(unexpected);
+                    }
+                    clauses.push(Vec::new());
+                }
+                if let OpCode::GROUP(_) = op {
+                    if depth == MAX_GROUP_DEPTH {
+                        return Err(DecompileErr::Unrepresentable(op.into()));
+                    }
+                    depth += 1;
+                    expect_term = true;
+                } else {
+                    expect_term = false;
+                }
+            }
+            OpCode::CONJ(_) if expect_term || clauses.is_empty() => return Err(unexpected),
+            OpCode::CONJ(_) => expect_term = true,
+            OpCode::GROUP(_) if expect_term || depth == 0 => return Err(unexpected),
+            OpCode::GROUP(_) => depth -= 1,
+            // The DSL has no extended OpCodes which would require a page
+            OpCode::REQUIRE(_) | OpCode::ARITH(_) | OpCode::MASK(_) => {
+                return Err(DecompileErr::Unrepresentable(op.into()))
+            }
+        }
+        if let Some(clause) = clauses.last_mut() {
+            clause.push(op);
+        }
+    }
+    if expect_term || depth > 0 {
+        return Err(DecompileErr::IncompleteClause);
+    }
+    Ok(clauses)
+}
+
+/// Write a comparator as an assertion e.g. `$input0 must not be greater than 5`
+fn assertion(
+    source: &mut String,
+    contract: &Contract,
+    parameters: &[String],
+    comparator: &Comparator,
+) -> Result<(), DecompileErr> {
+    let (imperative, phrase) = match (comparator.op, comparator.invert) {
+        (OpComp::EQ, false) => ("must be", "equal to"),
+        (OpComp::EQ, true) => ("must not be", "equal to"),
+        (OpComp::GT, false) => ("must be", "greater than"),
+        (OpComp::GT, true) => ("must be", "less than or equal to"),
+        (OpComp::GTE, false) => ("must be", "greater than or equal to"),
+        (OpComp::GTE, true) => ("must be", "less than"),
+        (OpComp::IN, false) => ("must be", "one of"),
+        (OpComp::IN, true) => ("must not be", "one of"),
+        // The DSL has no string, length or bitmask operations
+        (
+            OpComp::PREFIX
+            | OpComp::SUFFIX
+            | OpComp::CONTAINS
+            | OpComp::GLOB
+            | OpComp::LEN_EQ
+            | OpComp::LEN_GT
+            | OpComp::LEN_GTE
+            | OpComp::MASK_ALL
+            | OpComp::MASK_ANY,
+            _,
+        ) => {
+            return Err(DecompileErr::Unrepresentable(
+                OpCode::COMP(*comparator).into(),
+            ));
+        }
+    };
+    let _ = write!(
+        source,
+        "{} {} {} ",
+        parameters[usize::from(comparator.indices.lhs)],
+        imperative,
+        phrase
+    );
+    match comparator.load {
+        OpLoad::INPUT_VS_INPUT => {
+            source.push_str(&parameters[usize::from(comparator.indices.rhs)]);
+        }
+        OpLoad::INPUT_VS_USER => {
+            let value = contract
+                .data_table
+                .as_ref()
+                .get(usize::from(comparator.indices.rhs))
+                .ok_or(DecompileErr::MissingIndex(comparator.indices.rhs))?;
+            literal(source, value);
+        }
+    }
+    Ok(())
+}
+
+/// The DSL spelling of a conjunction, with surrounding whitespace
+fn conjunctive(conjunction: &Conjunction) -> Result<&'static str, DecompileErr> {
+    if conjunction.invert {
+        return Err(DecompileErr::Unrepresentable(
+            OpCode::CONJ(*conjunction).into(),
+        ));
+    }
+    Ok(match conjunction.op {
+        OpConj::AND => " and ",
+        OpConj::OR => " or ",
+        OpConj::XOR => " xor ",
+    })
+}
+
+/// Write `value` as a DSL literal
+fn literal(source: &mut String, value: &PactType) {
+    match value {
+        PactType::Numeric(n) => {
+            let _ = write!(source, "{}", n.0);
+        }
+        PactType::Signed(n) => {
+            let _ = write!(source, "{:+}", n.0);
+        }
+        PactType::Bool(b) => {
+            let _ = write!(source, "{}", b);
+        }
+        PactType::StringLike(s) => match core::str::from_utf8(&s.0) {
+            Ok(text)
+                if !text
+                    .chars()
+                    .any(|c| c.is_control() && c != '\n' && c != '\t') =>
+            {
+                source.push('"');
+                for c in text.chars() {
+                    match c {
+                        '"' => source.push_str("\\\""),
+                        '\\' => source.push_str("\\\\"),
+                        '\n' => source.push_str("\\n"),
+                        '\t' => source.push_str("\\t"),
+                        c => source.push(c),
+                    }
+                }
+                source.push('"');
+            }
+            _ => {
+                source.push_str("0x");
+                for b in s.0.iter() {
+                    let _ = write!(source, "{:02x}", b);
+                }
+            }
+        },
+        PactType::List(elements) => {
+            source.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    source.push_str(", ");
+                }
+                literal(source, element);
+            }
+            source.push(']');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::opcode::OpPage;
+    use crate::types::{DataTable, Numeric, StringLike};
+
+    fn contract(data_table: Vec<PactType>, ops: &[OpCode]) -> Contract {
+        let mut bytecode = Vec::new();
+        for op in ops {
+            op.compile(&mut bytecode);
+        }
+        Contract {
+            data_table: DataTable::new(data_table),
+            bytecode,
+        }
+    }
+
+    #[test]
+    fn it_decompiles_comparators() {
+        let contract = contract(
+            vec![
+                PactType::Numeric(Numeric(100.into())),
+                PactType::StringLike(StringLike(b"say \"hi\"".to_vec())),
+                PactType::StringLike(StringLike(vec![0xde, 0xad, 0xbe, 0xef])),
+            ],
+            &[
+                OpCode::COMP(Comparator::new(OpComp::GTE).invert().indices(0, 0)),
+                OpCode::COMP(Comparator::new(OpComp::GT).invert().indices(0, 0)),
+                OpCode::COMP(Comparator::new(OpComp::EQ).invert().indices(1, 1)),
+                OpCode::COMP(Comparator::new(OpComp::EQ).indices(2, 2)),
+                OpCode::COMP(
+                    Comparator::new(OpComp::GT)
+                        .load(OpLoad::INPUT_VS_INPUT)
+                        .indices(0, 3),
+                ),
+            ],
+        );
+        assert_eq!(
+            decompile(&contract),
+            Ok(r#"given parameters $input0, $input1, $input2, $input3
+
+$input0 must be less than 100
+$input0 must be less than or equal to 100
+$input1 must not be equal to "say \"hi\""
+$input2 must be equal to 0xdeadbeef
+$input0 must be greater than $input3
+"#
+            .into())
+        );
+    }
+
+    #[test]
+    fn it_decompiles_conjunctions() {
+        let contract = contract(
+            vec![PactType::List(vec![
+                PactType::Numeric(Numeric(1.into())),
+                PactType::Numeric(Numeric(2.into())),
+            ])],
+            &[
+                OpCode::COMP(Comparator::new(OpComp::IN)),
+                OpCode::CONJ(Conjunction::new(OpConj::XOR)),
+                OpCode::COMP(Comparator::new(OpComp::IN).invert().indices(1, 0)),
+            ],
+        );
+        assert_eq!(
+            decompile(&contract),
+            Ok("given parameters $input0, $input1
+
+$input0 must be one of [1, 2] xor $input1 must not be one of [1, 2]
+"
+            .into())
+        );
+    }
+
+    #[test]
+    fn it_names_parameters_from_metadata() {
+        let contract = contract(
+            vec![PactType::Numeric(Numeric(1.into()))],
+            &[
+                OpCode::COMP(Comparator::new(OpComp::EQ)),
+                OpCode::CONJ(Conjunction::new(OpConj::AND)),
+                OpCode::COMP(
+                    Comparator::new(OpComp::EQ)
+                        .load(OpLoad::INPUT_VS_INPUT)
+                        .indices(1, 0),
+                ),
+            ],
+        );
+        let tests: Vec<(Vec<&str>, &str)> = vec![
+            (
+                vec!["amount", "limit", "unused"],
+                "$amount, $limit, $unused",
+            ),
+            (vec!["amount"], "$input0, $input1"),
+            (vec!["amount", "amount"], "$input0, $input1"),
+            (vec!["amount", "not valid"], "$input0, $input1"),
+            (vec!["amount", ""], "$input0, $input1"),
+        ];
+        let mut contract = AnnotatedContract {
+            contract,
+            metadata: None,
+        };
+        for (names, parameters) in tests {
+            contract.metadata = Some(Metadata {
+                parameters: names.into_iter().map(String::from).collect(),
+                ..Default::default()
+            });
+            let names: Vec<&str> = parameters.split(", ").collect();
+            assert_eq!(
+                decompile_annotated(&contract),
+                Ok(format!(
+                    "given parameters {}\n\n{} must be equal to 1 and {} must be equal to {}\n",
+                    parameters, names[0], names[1], names[0]
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn it_decompiles_groups() {
+        let contract = contract(
+            vec![PactType::Numeric(Numeric(1.into()))],
+            &[
+                OpCode::GROUP(Group::new(OpGroup::OPEN)),
+                OpCode::COMP(Comparator::new(OpComp::EQ)),
+                OpCode::CONJ(Conjunction::new(OpConj::OR)),
+                OpCode::COMP(Comparator::new(OpComp::EQ).indices(1, 0)),
+                OpCode::GROUP(Group::new(OpGroup::CLOSE)),
+                OpCode::CONJ(Conjunction::new(OpConj::AND)),
+                OpCode::GROUP(Group::new(OpGroup::OPEN).invert()),
+                OpCode::COMP(Comparator::new(OpComp::GT)),
+                OpCode::GROUP(Group::new(OpGroup::CLOSE)),
+                OpCode::GROUP(Group::new(OpGroup::OPEN)),
+                OpCode::COMP(Comparator::new(OpComp::GT).indices(1, 0)),
+                OpCode::GROUP(Group::new(OpGroup::CLOSE)),
+            ],
+        );
+        assert_eq!(
+            decompile(&contract),
+            Ok("given parameters $input0, $input1
+
+($input0 must be equal to 1 or $input1 must be equal to 1) and not ($input0 must be greater than 1)
+($input1 must be greater than 1)
+"
+            .into())
+        );
+    }
+
+    #[test]
+    fn it_fails_on_unbalanced_groups() {
+        let table = vec![PactType::Numeric(Numeric(1.into()))];
+        let open = OpCode::GROUP(Group::new(OpGroup::OPEN));
+        let close = OpCode::GROUP(Group::new(OpGroup::CLOSE));
+        let eq = OpCode::COMP(Comparator::new(OpComp::EQ));
+        assert_eq!(
+            decompile(&contract(table.clone(), &[open, eq])),
+            Err(DecompileErr::IncompleteClause)
+        );
+        assert_eq!(
+            decompile(&contract(table.clone(), &[eq, close])),
+            Err(DecompileErr::UnexpectedOpCode(0x29))
+        );
+        assert_eq!(
+            decompile(&contract(table.clone(), &[open, close])),
+            Err(DecompileErr::UnexpectedOpCode(0x29))
+        );
+        // Clauses are only implicitly joined outside of groups
+        assert_eq!(
+            decompile(&contract(table, &[open, eq, eq, close])),
+            Err(DecompileErr::UnexpectedOpCode(0x00))
+        );
+    }
+
+    #[test]
+    fn it_fails_on_malformed_bytecode() {
+        let table = vec![PactType::Numeric(Numeric(1.into()))];
+        assert_eq!(
+            decompile(&contract(
+                table.clone(),
+                &[OpCode::CONJ(Conjunction::new(OpConj::AND))]
+            )),
+            Err(DecompileErr::UnexpectedOpCode(0x20))
+        );
+        assert_eq!(
+            decompile(&contract(
+                table.clone(),
+                &[
+                    OpCode::COMP(Comparator::new(OpComp::EQ)),
+                    OpCode::CONJ(Conjunction::new(OpConj::AND))
+                ]
+            )),
+            Err(DecompileErr::IncompleteClause)
+        );
+        assert_eq!(
+            decompile(&contract(
+                table.clone(),
+                &[OpCode::COMP(Comparator::new(OpComp::EQ).indices(0, 1))]
+            )),
+            Err(DecompileErr::MissingIndex(1))
+        );
+        assert_eq!(
+            decompile(&contract(
+                table.clone(),
+                &[
+                    OpCode::REQUIRE(OpPage::new(1).unwrap()),
+                    OpCode::COMP(Comparator::new(OpComp::EQ))
+                ]
+            )),
+            Err(DecompileErr::Unrepresentable(0xc1))
+        );
+        assert_eq!(
+            decompile(&contract(
+                table,
+                &[
+                    OpCode::COMP(Comparator::new(OpComp::EQ)),
+                    OpCode::CONJ(Conjunction::new(OpConj::AND).invert()),
+                    OpCode::COMP(Comparator::new(OpComp::EQ)),
+                ]
+            )),
+            Err(DecompileErr::Unrepresentable(0x30))
+        );
+        assert_eq!(
+            decompile(&Contract {
+                data_table: DataTable::new(vec![]),
+                bytecode: vec![0x07, 0x00],
+            }),
+            Err(DecompileErr::InvalidBytecode(InterpErr::InvalidOpCode(
+                0x07
+            )))
+        );
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2023-2024 Futureverse Corporation Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Compiler diagnostics
+//!
+use super::CompileErr;
+use alloc::string::String;
+use core::fmt;
+
+/// A 1-based line and column (in characters) within the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A compiler error resolved against its source text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub error: CompileErr,
+    /// Where the offending source begins
+    pub start: Location,
+    /// Where the offending source ends (exclusive)
+    pub end: Location,
+    /// The source line on which the error begins
+    pub snippet: String,
+    /// A suggested fix
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    /// Resolve `error` against `source`
+    pub fn new(error: CompileErr, source: &str) -> Self {
+        let span = error.span();
+        let start = location(source, span.start);
+        let end = location(source, span.end);
+        let line_start = source[..floor_char_boundary(source, span.start)]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        let snippet = source[line_start..]
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .into();
+        Diagnostic {
+            help: error.help(),
+            error,
+            start,
+            end,
+            snippet,
+        }
+    }
+}
+
+/// Render the diagnostic e.g.
+/// ```text
+/// error: undeclared identifier `$amuont`
+///  --> 3:1
+///   |
+/// 3 | $amuont must be less than 100
+///   | ^^^^^^^
+///   = help: did you mean `$amount`?
+/// ```
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line_number = self.start.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let width = if self.end.line == self.start.line {
+            self.end.column.saturating_sub(self.start.column)
+        } else {
+            self.snippet.chars().count() + 1 - self.start.column
+        };
+
+        writeln!(f, "error: {}", self.error)?;
+        writeln!(f, "{}--> {}:{}", gutter, self.start.line, self.start.column)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line_number, self.snippet)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(self.start.column - 1),
+            "^".repeat(width.max(1))
+        )?;
+        if let Some(help) = &self.help {
+            write!(f, "\n{} = help: {}", gutter, help)?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the line and column of byte `offset` into `source`
+fn location(source: &str, offset: usize) -> Location {
+    let before = &source[..floor_char_boundary(source, offset)];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Location {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+/// Clamp `offset` to a char boundary at or before it
+fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::compile;
+    use super::*;
+
+    #[test]
+    fn it_resolves_locations() {
+        let source = "given $amount\n\n  $amuont must be less than 100";
+        let diagnostics = compile(source).expect_err("it fails");
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.start, Location { line: 3, column: 3 });
+        assert_eq!(
+            diagnostic.end,
+            Location {
+                line: 3,
+                column: 10
+            }
+        );
+        assert_eq!(diagnostic.snippet, "  $amuont must be less than 100");
+        assert_eq!(diagnostic.help.as_deref(), Some("did you mean `$amount`?"));
+    }
+
+    #[test]
+    fn it_renders() {
+        let source = "given $a\n$a must be greater than \"z\"";
+        let diagnostics = compile(source).expect_err("it fails");
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: `greater than` is not supported on a string
+ --> 2:1
+  |
+2 | $a must be greater than \"z\"
+  | ^^^^^^^^^^^^^^^^^^^^^^^^^^^
+  = help: strings may only be compared using `equal to` or `one of`"
+        );
+    }
+
+    #[test]
+    fn it_renders_end_of_input() {
+        let source = "given $a\n$a must be";
+        let diagnostics = compile(source).expect_err("it fails");
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: expected a value, found end of input
+ --> 2:11
+  |
+2 | $a must be
+  |           ^"
+        );
+    }
+}
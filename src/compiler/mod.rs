@@ -0,0 +1,852 @@
+// Copyright 2023-2024 Futureverse Corporation Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! The pact DSL compiler
+//!
+//! Lowers an `ast::Contract` into a `Contract` i.e. a `DataTable` and bytecode.
+//! Static values are deduplicated into the data table in order of first use.
+//! `compile_annotated` also records the parameter names as contract metadata.
+//!
+//! Compilation does not stop at the first error, every problem found in the source is
+//! returned as a `Diagnostic`.
+//!
+mod decompiler;
+mod diagnostic;
+
+pub use decompiler::{decompile, decompile_annotated, DecompileErr};
+pub use diagnostic::{Diagnostic, Location};
+
+use crate::interpreter::MAX_GROUP_DEPTH;
+use crate::parser::{self, ast, ParseErr};
+use crate::types::opcode::{
+    Comparator, Conjunction, Group, LoadSource, OpCode, OpGroup, SubjectSource,
+};
+use crate::types::{
+    AnnotatedContract, Contract, DataTable, Metadata, Numeric, PactType, Signed, StringLike,
+    MAX_LIST_DEPTH,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The most input parameters addressable by an `OpIndices` byte
+const MAX_INPUTS: usize = u8::MAX as usize + 1;
+/// The most entries a data table may hold in `BinaryFormat::V0`, where its length is a single byte.
+/// Compiled contracts keep to it so they encode in every format, v1 lengths are compact
+const MAX_DATA_TABLE_ENTRIES: usize = u8::MAX as usize;
+
+/// A compiler error
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileErr {
+    /// The source failed to parse
+    Parse(ParseErr),
+    /// An identifier was used without being declared.
+    /// Carries the closest declared name, if any
+    Undeclared {
+        name: String,
+        similar: Option<String>,
+        span: ast::Span,
+    },
+    /// An identifier was declared more than once
+    Redeclared(String, ast::Span),
+    /// Both sides of an assertion are static values
+    StaticAssertion(ast::Span),
+    /// A static value was tested for membership in an input
+    UnsupportedOperands(ast::Span),
+    /// The comparator is not supported by the static operand's type
+    /// and would fail at runtime with `InterpErr::BadTypeOperation`
+    BadTypeOperation {
+        comparator: ast::Comparator,
+        type_name: &'static str,
+        span: ast::Span,
+    },
+    /// An input parameter index does not fit the bytecode encoding
+    TooManyInputs(ast::Span),
+    /// A data table index does not fit the bytecode encoding
+    TooManyDataTableEntries(ast::Span),
+    /// Groups are nested deeper than the interpreter supports
+    TooDeeplyNested(ast::Span),
+    /// A list literal is nested deeper than the codec supports
+    ListTooDeeplyNested(ast::Span),
+}
+
+impl CompileErr {
+    /// Return the source span of the error
+    pub fn span(&self) -> ast::Span {
+        match self {
+            CompileErr::Parse(err) => err.span(),
+            CompileErr::Undeclared { span, .. }
+            | CompileErr::Redeclared(_, span)
+            | CompileErr::StaticAssertion(span)
+            | CompileErr::UnsupportedOperands(span)
+            | CompileErr::BadTypeOperation { span, .. }
+            | CompileErr::TooManyInputs(span)
+            | CompileErr::TooManyDataTableEntries(span)
+            | CompileErr::TooDeeplyNested(span)
+            | CompileErr::ListTooDeeplyNested(span) => *span,
+        }
+    }
+
+    /// Return a suggested fix for the error
+    pub fn help(&self) -> Option<String> {
+        match self {
+            CompileErr::Parse(err) => err.help().map(Into::into),
+            CompileErr::Undeclared {
+                similar: Some(similar),
+                ..
+            } => Some(format!("did you mean `${}`?", similar)),
+            CompileErr::Undeclared { name, .. } => Some(format!(
+                "add `${}` to the `given` parameters or declare it with `define ${} as <value>`",
+                name, name
+            )),
+            CompileErr::Redeclared(name, _) => {
+                Some(format!("rename one of the declarations of `${}`", name))
+            }
+            CompileErr::StaticAssertion(_) => {
+                Some("one side of an assertion must be an input parameter".into())
+            }
+            CompileErr::UnsupportedOperands(_) => {
+                Some("place the input on the left e.g. `$x must be one of [1, 2]`".into())
+            }
+            CompileErr::BadTypeOperation {
+                comparator: ast::Comparator::OneOf,
+                ..
+            } => Some("`one of` expects a list e.g. `[1, 2]`".into()),
+            CompileErr::BadTypeOperation {
+                type_name: "list", ..
+            } => Some("lists may only be compared using `one of`".into()),
+            CompileErr::BadTypeOperation { type_name, .. } => Some(format!(
+                "{}s may only be compared using `equal to` or `one of`",
+                type_name
+            )),
+            CompileErr::TooManyInputs(_) => Some(format!(
+                "only the first {} parameters may be used in assertions",
+                MAX_INPUTS
+            )),
+            CompileErr::TooManyDataTableEntries(_) => Some(format!(
+                "a contract may use at most {} distinct static values",
+                MAX_DATA_TABLE_ENTRIES
+            )),
+            CompileErr::TooDeeplyNested(_) => Some(format!(
+                "groups may be nested at most {} deep",
+                MAX_GROUP_DEPTH
+            )),
+            CompileErr::ListTooDeeplyNested(_) => Some(format!(
+                "lists may be nested at most {} deep",
+                MAX_LIST_DEPTH
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CompileErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileErr::Parse(err) => err.fmt(f),
+            CompileErr::Undeclared { name, .. } => write!(f, "undeclared identifier `${}`", name),
+            CompileErr::Redeclared(name, _) => write!(f, "`${}` is already declared", name),
+            CompileErr::StaticAssertion(_) => write!(f, "assertion compares two static values"),
+            CompileErr::UnsupportedOperands(_) => {
+                write!(
+                    f,
+                    "a static value can't be tested for membership in an input"
+                )
+            }
+            CompileErr::BadTypeOperation {
+                comparator,
+                type_name,
+                ..
+            } => write!(
+                f,
+                "`{}` is not supported on a {}",
+                comparator_name(comparator),
+                type_name
+            ),
+            CompileErr::TooManyInputs(_) => write!(f, "too many input parameters"),
+            CompileErr::TooManyDataTableEntries(_) => write!(f, "too many data table entries"),
+            CompileErr::TooDeeplyNested(_) => write!(f, "groups are nested too deeply"),
+            CompileErr::ListTooDeeplyNested(_) => write!(f, "list is nested too deeply"),
+        }
+    }
+}
+
+impl From<ParseErr> for CompileErr {
+    fn from(err: ParseErr) -> Self {
+        CompileErr::Parse(err)
+    }
+}
+
+/// Compile pact DSL `source` into a contract.
+/// Returns a diagnostic for every error found on failure.
+pub fn compile(source: &str) -> Result<Contract, Vec<Diagnostic>> {
+    compile_annotated(source).map(|annotated| annotated.contract)
+}
+
+/// Compile pact DSL `source` into a contract with its parameter names as metadata.
+/// Returns a diagnostic for every error found on failure.
+pub fn compile_annotated(source: &str) -> Result<AnnotatedContract, Vec<Diagnostic>> {
+    let (contract, parse_errors) = parser::parse_partial(source);
+    let mut errors: Vec<CompileErr> = parse_errors.into_iter().map(CompileErr::Parse).collect();
+
+    // Without a header every identifier would be reported as undeclared
+    if !contract.parameters.is_empty() {
+        match compile_ast_annotated(&contract) {
+            Ok(contract) if errors.is_empty() => return Ok(contract),
+            Ok(_) => {}
+            Err(compile_errors) => errors.extend(compile_errors),
+        }
+    }
+
+    errors.sort_by_key(|e| e.span().start);
+    Err(errors
+        .into_iter()
+        .map(|e| Diagnostic::new(e, source))
+        .collect())
+}
+
+/// Compile a parsed pact contract.
+/// Returns every error found on failure.
+pub fn compile_ast(contract: &ast::Contract) -> Result<Contract, Vec<CompileErr>> {
+    compile_ast_annotated(contract).map(|annotated| annotated.contract)
+}
+
+/// Compile a parsed pact contract with its parameter names as metadata
+fn compile_ast_annotated(contract: &ast::Contract) -> Result<AnnotatedContract, Vec<CompileErr>> {
+    let mut compiler = Compiler::default();
+    for parameter in &contract.parameters {
+        match compiler.declare(&parameter.name, parameter.span) {
+            Ok(()) => compiler.parameters.push(parameter.name.clone()),
+            Err(err) => compiler.errors.push(err),
+        }
+    }
+    for statement in &contract.statements {
+        match statement {
+            ast::Statement::Definition(definition) => {
+                if let Err(err) = compiler.define(definition) {
+                    compiler.errors.push(err);
+                }
+            }
+            ast::Statement::Clause(clause) => compiler.clause(clause, 0),
+        }
+    }
+
+    if !compiler.errors.is_empty() {
+        return Err(compiler.errors);
+    }
+    let contract = Contract {
+        data_table: DataTable::new(compiler.data_table),
+        bytecode: compiler.bytecode,
+    };
+    Ok(contract.with_metadata(Metadata {
+        parameters: compiler.parameters,
+        ..Default::default()
+    }))
+}
+
+/// Compilation state
+#[derive(Default)]
+struct Compiler {
+    /// Input parameter names in call order
+    parameters: Vec<String>,
+    /// Named static values
+    definitions: Vec<(String, PactType)>,
+    /// The deduplicated data table
+    data_table: Vec<PactType>,
+    bytecode: Vec<u8>,
+    errors: Vec<CompileErr>,
+}
+
+impl Compiler {
+    /// Check `name` is not already in scope
+    fn declare(&self, name: &str, span: ast::Span) -> Result<(), CompileErr> {
+        let exists = self.parameters.iter().any(|p| p == name)
+            || self.definitions.iter().any(|(d, _)| d == name);
+        if exists {
+            return Err(CompileErr::Redeclared(name.into(), span));
+        }
+        Ok(())
+    }
+
+    fn define(&mut self, definition: &ast::Definition) -> Result<(), CompileErr> {
+        self.declare(&definition.ident.name, definition.ident.span)?;
+        let value = match &definition.value {
+            ast::Value::Literal(literal, span) => static_value(literal, *span)?,
+            ast::Value::Ident(ident) => self.resolve_definition(ident)?,
+        };
+        self.definitions
+            .push((definition.ident.name.clone(), value));
+        Ok(())
+    }
+
+    /// Compile each term of the clause, recording errors as they are found.
+    /// `depth` is the number of groups enclosing the clause
+    fn clause(&mut self, clause: &ast::Clause, depth: usize) {
+        self.term(&clause.head, depth);
+        for (conjunctive, term) in &clause.tail {
+            OpCode::CONJ(Conjunction::from(conjunctive)).compile(&mut self.bytecode);
+            self.term(term, depth);
+        }
+    }
+
+    fn term(&mut self, term: &ast::Term, depth: usize) {
+        match term {
+            ast::Term::Assertion(assertion) => {
+                if let Err(err) = self.assertion(assertion) {
+                    self.errors.push(err);
+                }
+            }
+            ast::Term::Group(group) if depth == MAX_GROUP_DEPTH => {
+                self.errors.push(CompileErr::TooDeeplyNested(group.span));
+            }
+            ast::Term::Group(group) => {
+                OpCode::GROUP(Group::from(group)).compile(&mut self.bytecode);
+                self.clause(&group.clause, depth + 1);
+                OpCode::GROUP(Group::new(OpGroup::CLOSE)).compile(&mut self.bytecode);
+            }
+        }
+    }
+
+    fn assertion(&mut self, assertion: &ast::Assertion) -> Result<(), CompileErr> {
+        let lhs = self.subject(&assertion.subject);
+        let rhs = self.subject(&assertion.object);
+        let ((lhs, lhs_value), (rhs, rhs_value)) = match (lhs, rhs) {
+            (Ok(lhs), Ok(rhs)) => (lhs, rhs),
+            (Err(err), Ok(_)) | (Ok(_), Err(err)) => return Err(err),
+            (Err(lhs_err), Err(rhs_err)) => {
+                self.errors.push(lhs_err);
+                return Err(rhs_err);
+            }
+        };
+
+        match (lhs.load_source, rhs.load_source) {
+            (LoadSource::DataTable, LoadSource::DataTable) => {
+                return Err(CompileErr::StaticAssertion(assertion.span))
+            }
+            // `IN` can't be flipped to place the input on the LHS
+            (LoadSource::DataTable, LoadSource::Input)
+                if assertion.comparator == ast::Comparator::OneOf =>
+            {
+                return Err(CompileErr::UnsupportedOperands(assertion.span))
+            }
+            _ => {}
+        }
+        if let Some(value) = rhs_value.as_ref() {
+            check_type(assertion, value, true)?;
+        }
+        if let Some(value) = lhs_value.as_ref() {
+            check_type(assertion, value, false)?;
+        }
+
+        let comparator = Comparator::from(&assertion.comparator)
+            .apply_imperative(&assertion.imperative)
+            .loads_from_subjects(lhs, rhs)
+            .ok_or(CompileErr::UnsupportedOperands(assertion.span))?;
+        OpCode::COMP(comparator).compile(&mut self.bytecode);
+        Ok(())
+    }
+
+    /// Resolve an assertion operand to its load source and index.
+    /// Static operands are returned with their value.
+    fn subject(
+        &mut self,
+        value: &ast::Value,
+    ) -> Result<(SubjectSource, Option<PactType>), CompileErr> {
+        match value {
+            ast::Value::Ident(ident) => {
+                if let Some(index) = self.parameters.iter().position(|p| *p == ident.name) {
+                    if index >= MAX_INPUTS {
+                        return Err(CompileErr::TooManyInputs(ident.span));
+                    }
+                    let source = SubjectSource {
+                        load_source: LoadSource::Input,
+                        index: index as u8,
+                    };
+                    return Ok((source, None));
+                }
+                let value = self.resolve_definition(ident)?;
+                Ok((self.intern(value.clone(), ident.span)?, Some(value)))
+            }
+            ast::Value::Literal(literal, span) => {
+                let value = static_value(literal, *span)?;
+                Ok((self.intern(value.clone(), *span)?, Some(value)))
+            }
+        }
+    }
+
+    fn resolve_definition(&self, ident: &ast::Ident) -> Result<PactType, CompileErr> {
+        self.definitions
+            .iter()
+            .find(|(name, _)| *name == ident.name)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| {
+                let declared = self
+                    .parameters
+                    .iter()
+                    .chain(self.definitions.iter().map(|(name, _)| name));
+                CompileErr::Undeclared {
+                    name: ident.name.clone(),
+                    similar: similar_name(&ident.name, declared),
+                    span: ident.span,
+                }
+            })
+    }
+
+    /// Return the data table index of `value`, appending it if not already present
+    fn intern(&mut self, value: PactType, span: ast::Span) -> Result<SubjectSource, CompileErr> {
+        let index = match self.data_table.iter().position(|v| *v == value) {
+            Some(index) => index,
+            None => {
+                self.data_table.push(value);
+                self.data_table.len() - 1
+            }
+        };
+        if index >= MAX_DATA_TABLE_ENTRIES {
+            return Err(CompileErr::TooManyDataTableEntries(span));
+        }
+        Ok(SubjectSource {
+            load_source: LoadSource::DataTable,
+            index: index as u8,
+        })
+    }
+}
+
+/// Check the comparator is supported by a static operand, mirroring the runtime checks of
+/// the interpreter. `is_object` is whether the value is the RHS of the assertion as written.
+fn check_type(
+    assertion: &ast::Assertion,
+    value: &PactType,
+    is_object: bool,
+) -> Result<(), CompileErr> {
+    let type_name = match value {
+        PactType::Numeric(_) => "number",
+        PactType::Signed(_) => "signed number",
+        PactType::StringLike(_) => "string",
+        PactType::Bool(_) => "bool",
+        PactType::List(_) => "list",
+    };
+    let supported = match (value, assertion.comparator) {
+        (PactType::List(_), ast::Comparator::OneOf) => is_object,
+        (PactType::List(_), _) => false,
+        (_, ast::Comparator::OneOf) => !is_object,
+        (PactType::StringLike(_), ast::Comparator::Equal) => true,
+        (PactType::StringLike(_), _) => false,
+        (PactType::Bool(_), ast::Comparator::Equal) => true,
+        (PactType::Bool(_), _) => false,
+        (PactType::Numeric(_), _) | (PactType::Signed(_), _) => true,
+    };
+    if supported {
+        Ok(())
+    } else {
+        Err(CompileErr::BadTypeOperation {
+            comparator: assertion.comparator,
+            type_name,
+            span: assertion.span,
+        })
+    }
+}
+
+/// The DSL spelling of a comparator
+fn comparator_name(comparator: &ast::Comparator) -> &'static str {
+    match comparator {
+        ast::Comparator::Equal => "equal to",
+        ast::Comparator::GreaterThan => "greater than",
+        ast::Comparator::GreaterThanOrEqual => "greater than or equal to",
+        ast::Comparator::LessThan => "less than",
+        ast::Comparator::LessThanOrEqual => "less than or equal to",
+        ast::Comparator::OneOf => "one of",
+    }
+}
+
+/// Return the declared name closest to `name`, if any is a plausible typo
+fn similar_name<'a>(name: &str, declared: impl Iterator<Item = &'a String>) -> Option<String> {
+    declared
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= candidate.len().max(name.len()) / 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+/// Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Convert a literal into its `PactType` representation, checking it can be encoded
+fn static_value(literal: &ast::Literal, span: ast::Span) -> Result<PactType, CompileErr> {
+    let value = pact_type(literal);
+    if value.list_depth() > MAX_LIST_DEPTH {
+        return Err(CompileErr::ListTooDeeplyNested(span));
+    }
+    Ok(value)
+}
+
+/// Convert a literal into its `PactType` representation
+fn pact_type(literal: &ast::Literal) -> PactType {
+    match literal {
+        ast::Literal::Integer(n) => PactType::Numeric(Numeric(*n)),
+        ast::Literal::Signed(n) => PactType::Signed(Signed(*n)),
+        ast::Literal::Bool(b) => PactType::Bool(*b),
+        ast::Literal::String(s) => PactType::StringLike(StringLike(s.clone())),
+        ast::Literal::List(elements) => PactType::List(elements.iter().map(pact_type).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::opcode::{OpComp, OpConj, OpLoad};
+
+    fn comp(comparator: Comparator) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        OpCode::COMP(comparator).compile(&mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn it_compiles_the_readme_example() {
+        let contract = compile(
+            r#"
+            given parameters $payee, $amount, $asset_id
+
+            define $assets as [16001, 16010]
+
+            $payee must be equal to "alice" and $amount must not be greater than 100
+            $asset_id must be one of $assets
+            "#,
+        )
+        .expect("it compiles");
+
+        assert_eq!(
+            contract.data_table,
+            DataTable::new(vec![
+                PactType::StringLike(StringLike(b"alice".to_vec())),
+                PactType::Numeric(Numeric(100.into())),
+                PactType::List(vec![
+                    PactType::Numeric(Numeric(16001.into())),
+                    PactType::Numeric(Numeric(16010.into())),
+                ]),
+            ])
+        );
+        assert_eq!(
+            contract.bytecode,
+            [
+                comp(Comparator::new(OpComp::EQ).indices(0, 0)),
+                vec![OpCode::CONJ(Conjunction::new(OpConj::AND)).into()],
+                comp(Comparator::new(OpComp::GT).invert().indices(1, 1)),
+                comp(Comparator::new(OpComp::IN).indices(2, 2)),
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn it_deduplicates_the_data_table() {
+        let contract = compile(
+            "given $a, $b
+             define $limit as 10
+             $a must be less than 10 or $b must be equal to $limit",
+        )
+        .expect("it compiles");
+        assert_eq!(
+            contract.data_table,
+            DataTable::new(vec![PactType::Numeric(Numeric(10.into()))])
+        );
+        assert_eq!(
+            contract.bytecode,
+            [
+                comp(Comparator::new(OpComp::GTE).invert().indices(0, 0)),
+                vec![OpCode::CONJ(Conjunction::new(OpConj::OR)).into()],
+                comp(Comparator::new(OpComp::EQ).indices(1, 0)),
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn it_compiles_input_vs_input() {
+        let contract =
+            compile("given $a, $b $b must be greater than or equal to $a").expect("it compiles");
+        assert_eq!(
+            contract.bytecode,
+            comp(
+                Comparator::new(OpComp::GTE)
+                    .load(OpLoad::INPUT_VS_INPUT)
+                    .indices(1, 0)
+            )
+        );
+    }
+
+    #[test]
+    fn it_compiles_wide_indices() {
+        let parameters: Vec<String> = (0..20).map(|i| format!("$p{}", i)).collect();
+        let source = format!("given {} $p19 must be equal to $p3", parameters.join(", "));
+        let contract = compile(&source).expect("it compiles");
+        assert_eq!(contract.bytecode, vec![0x48, 19, 3]);
+    }
+
+    #[test]
+    fn it_compiles_groups() {
+        let contract =
+            compile("given $a, $b ($a must be 1 or $b must be 1) and not ($a must be 2)")
+                .expect("it compiles");
+        let open = OpCode::GROUP(Group::new(OpGroup::OPEN)).into();
+        let not = OpCode::GROUP(Group::new(OpGroup::OPEN).invert()).into();
+        let close = OpCode::GROUP(Group::new(OpGroup::CLOSE)).into();
+        assert_eq!(
+            contract.bytecode,
+            [
+                vec![open],
+                comp(Comparator::new(OpComp::EQ).indices(0, 0)),
+                vec![OpCode::CONJ(Conjunction::new(OpConj::OR)).into()],
+                comp(Comparator::new(OpComp::EQ).indices(1, 0)),
+                vec![
+                    close,
+                    OpCode::CONJ(Conjunction::new(OpConj::AND)).into(),
+                    not
+                ],
+                comp(Comparator::new(OpComp::EQ).indices(0, 1)),
+                vec![close],
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn it_compiles_signed_literals() {
+        let contract = compile("given $a $a must be greater than -5 and $a must not be +5")
+            .expect("it compiles");
+        assert_eq!(
+            contract.data_table,
+            DataTable::new(vec![
+                PactType::Signed(Signed(-5)),
+                PactType::Signed(Signed(5)),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_compiles_bool_literals() {
+        let contract = compile(
+            "given $keep_alive, $flags $keep_alive must be equal to true and $flags must be one of [false]",
+        )
+        .expect("it compiles");
+        assert_eq!(
+            contract.data_table,
+            DataTable::new(vec![
+                PactType::Bool(true),
+                PactType::List(vec![PactType::Bool(false)]),
+            ])
+        );
+        assert_eq!(
+            errors("given $a $a must be less than TRUE"),
+            vec![CompileErr::BadTypeOperation {
+                comparator: ast::Comparator::LessThan,
+                type_name: "bool",
+                span: span(9, 34),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_flips_static_subjects() {
+        // 5 < $a  =>  $a > 5
+        let contract = compile("given $a 5 must be less than $a").expect("it compiles");
+        assert_eq!(
+            contract.bytecode,
+            comp(Comparator::new(OpComp::GT).indices(0, 0))
+        );
+    }
+
+    /// Compile `source` returning its errors
+    fn errors(source: &str) -> Vec<CompileErr> {
+        compile(source)
+            .expect_err("it fails")
+            .into_iter()
+            .map(|d| d.error)
+            .collect()
+    }
+
+    fn span(start: usize, end: usize) -> ast::Span {
+        ast::Span { start, end }
+    }
+
+    #[test]
+    fn it_fails_on_undeclared_identifiers() {
+        assert_eq!(
+            errors("given $a $b must be 1"),
+            vec![CompileErr::Undeclared {
+                name: "b".into(),
+                similar: None,
+                span: span(9, 11)
+            }]
+        );
+        assert_eq!(
+            errors("given $amount $amuont must be 1"),
+            vec![CompileErr::Undeclared {
+                name: "amuont".into(),
+                similar: Some("amount".into()),
+                span: span(14, 21)
+            }]
+        );
+    }
+
+    #[test]
+    fn it_fails_on_redeclared_identifiers() {
+        assert_eq!(
+            errors("given $a define $a as 1"),
+            vec![CompileErr::Redeclared("a".into(), span(16, 18))]
+        );
+    }
+
+    #[test]
+    fn it_fails_on_static_assertions() {
+        assert_eq!(
+            errors("given $a 1 must be 2"),
+            vec![CompileErr::StaticAssertion(span(9, 20))]
+        );
+        assert_eq!(
+            errors("given $a 1 must be one of $a"),
+            vec![CompileErr::UnsupportedOperands(span(9, 28))]
+        );
+    }
+
+    #[test]
+    fn it_fails_on_bad_type_operations() {
+        assert_eq!(
+            errors(
+                r#"given $a
+                   $a must be greater than "alice"
+                   $a must be one of 5
+                   $a must not be equal to [1, 2]"#
+            ),
+            vec![
+                CompileErr::BadTypeOperation {
+                    comparator: ast::Comparator::GreaterThan,
+                    type_name: "string",
+                    span: span(28, 59),
+                },
+                CompileErr::BadTypeOperation {
+                    comparator: ast::Comparator::OneOf,
+                    type_name: "number",
+                    span: span(79, 98),
+                },
+                CompileErr::BadTypeOperation {
+                    comparator: ast::Comparator::Equal,
+                    type_name: "list",
+                    span: span(118, 148),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_fails_when_indices_overflow() {
+        let parameters: Vec<String> = (0..257).map(|i| format!("$p{}", i)).collect();
+        let source = format!("given {} $p256 must be 1", parameters.join(", "));
+        assert!(matches!(
+            errors(&source)[..],
+            [CompileErr::TooManyInputs(_)]
+        ));
+
+        let clauses: Vec<String> = (0..256).map(|i| format!("$a must be {}", i)).collect();
+        let source = format!("given $a {}", clauses.join("\n"));
+        assert!(matches!(
+            errors(&source)[..],
+            [CompileErr::TooManyDataTableEntries(_)]
+        ));
+    }
+
+    #[test]
+    fn it_fails_when_groups_nest_too_deeply() {
+        let source = format!(
+            "given $a {}$a must be 1{}",
+            "(".repeat(MAX_GROUP_DEPTH),
+            ")".repeat(MAX_GROUP_DEPTH)
+        );
+        assert!(compile(&source).is_ok());
+
+        let source = format!(
+            "given $a {}$a must be 1{}",
+            "(".repeat(MAX_GROUP_DEPTH + 1),
+            ")".repeat(MAX_GROUP_DEPTH + 1)
+        );
+        let innermost = 9 + MAX_GROUP_DEPTH;
+        assert_eq!(
+            errors(&source),
+            vec![CompileErr::TooDeeplyNested(span(innermost, innermost + 14))]
+        );
+    }
+
+    #[test]
+    fn it_fails_when_lists_nest_too_deeply() {
+        let list = |depth: usize| format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+        let source = format!("given $a $a must be one of {}", list(MAX_LIST_DEPTH));
+        assert!(compile(&source).is_ok());
+
+        let too_deep = list(MAX_LIST_DEPTH + 1);
+        let source = format!("given $a $a must be one of {}", too_deep);
+        assert_eq!(
+            errors(&source),
+            vec![CompileErr::ListTooDeeplyNested(span(
+                27,
+                27 + too_deep.len()
+            ))]
+        );
+        let source = format!("given $a define $l as {}", too_deep);
+        assert_eq!(
+            errors(&source),
+            vec![CompileErr::ListTooDeeplyNested(span(
+                22,
+                22 + too_deep.len()
+            ))]
+        );
+    }
+
+    #[test]
+    fn it_reports_every_error_in_one_pass() {
+        let source = r#"given $a, $b
+            $c must be 1 and $a must be greater than "x"
+            $a shall be 2
+            $b must be 3 @"#;
+        let errors = errors(source);
+        assert_eq!(errors.len(), 4);
+        assert!(matches!(errors[0], CompileErr::Undeclared { .. }));
+        assert!(matches!(errors[1], CompileErr::BadTypeOperation { .. }));
+        assert!(matches!(
+            errors[2],
+            CompileErr::Parse(ParseErr::UnexpectedToken {
+                expected: "must",
+                ..
+            })
+        ));
+        assert!(matches!(
+            errors[3],
+            CompileErr::Parse(ParseErr::UnexpectedChar(_))
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2019 Centrality Investments Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Glob pattern matching for `StringLike` values
+//!
+//! A pattern matches a whole value byte by byte. `*` matches any run of bytes, `?` matches any one
+//! byte and `\` escapes a following `*`, `?` or `\`. Any other byte matches itself.
+//! Matching simulates a nondeterministic automaton with one bit per pattern position, so it takes one
+//! step per byte of the value and never backtracks.
+//!
+
+/// The most `?`s and literal bytes in a pattern, each is a state of the automaton
+pub const MAX_PATTERN_LEN: usize = 127;
+
+/// A compiled pattern, bit `i` of a state set is the state after `i` bytes of the pattern
+struct Automaton {
+    /// The states entered on each byte value from the state before
+    literals: [u128; 256],
+    /// The states entered on any byte from the state before i.e. the states following a `?`
+    any: u128,
+    /// The states preceding a `*`, which remain active on any byte
+    stars: u128,
+    /// The state after the whole pattern
+    accept: u128,
+}
+
+/// Compile `pattern`, returning why if it is invalid i.e. it has a dangling or unknown escape
+/// or more than `MAX_PATTERN_LEN` bytes to match
+fn compile(pattern: &[u8]) -> Result<Automaton, &'static str> {
+    let mut automaton = Automaton {
+        literals: [0; 256],
+        any: 0,
+        stars: 0,
+        accept: 0,
+    };
+    let mut len = 0;
+    let mut bytes = pattern.iter();
+    while let Some(b) = bytes.next() {
+        let literal = match b {
+            b'*' => {
+                automaton.stars |= 1 << len;
+                continue;
+            }
+            b'?' => None,
+            b'\\' => match bytes.next() {
+                Some(escaped @ (b'*' | b'?' | b'\\')) => Some(escaped),
+                _ => return Err("invalid escape"),
+            },
+            _ => Some(b),
+        };
+        if len == MAX_PATTERN_LEN {
+            return Err("pattern too long");
+        }
+        len += 1;
+        match literal {
+            Some(literal) => automaton.literals[usize::from(*literal)] |= 1 << len,
+            None => automaton.any |= 1 << len,
+        }
+    }
+    automaton.accept = 1 << len;
+    Ok(automaton)
+}
+
+/// Return whether `pattern` is a valid glob pattern
+pub(crate) fn is_valid(pattern: &[u8]) -> bool {
+    compile(pattern).is_ok()
+}
+
+/// Return whether `value` matches the glob `pattern`, or why the pattern is invalid
+pub(crate) fn matches(pattern: &[u8], value: &[u8]) -> Result<bool, &'static str> {
+    let automaton = compile(pattern)?;
+    let mut active: u128 = 1;
+    for b in value {
+        let entered = automaton.literals[usize::from(*b)] | automaton.any;
+        active = ((active << 1) & entered) | (active & automaton.stars);
+        if active == 0 {
+            return Ok(false);
+        }
+    }
+    Ok(active & automaton.accept != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn it_matches_patterns() {
+        let tests: [(&[u8], &[u8], bool); 22] = [
+            (b"", b"", true),
+            (b"", b"a", false),
+            (b"*", b"", true),
+            (b"*", b"anything", true),
+            (b"**", b"", true),
+            (b"?", b"", false),
+            (b"?", b"a", true),
+            (b"?", b"ab", false),
+            (b"?x*", b"axyz", true),
+            (b"?x*", b"xx", true),
+            (b"?x*", b"x", false),
+            (b"collection-*-gold", b"collection-7-gold", true),
+            (b"collection-*-gold", b"collection--gold", true),
+            (b"collection-*-gold", b"collection-7-gold-gold", true),
+            (b"collection-*-gold", b"collection-7-silver", false),
+            (b"collection-*-gold", b"collection-gold", false),
+            (b"a*b*c", b"aXbYbZc", true),
+            (b"a*b*c", b"acb", false),
+            (b"*ab", b"aab", true),
+            (br"\*\?\\", br"*?\", true),
+            (br"\*", b"a", false),
+            (b"\xff*", b"\xff\x00", true),
+        ];
+        for (pattern, value, expected) in tests {
+            assert_eq!(
+                matches(pattern, value),
+                Ok(expected),
+                "{:?} {:?}",
+                pattern,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_patterns() {
+        let longest = vec![b'?'; MAX_PATTERN_LEN];
+        assert!(is_valid(&longest));
+        assert_eq!(matches(&longest, &longest), Ok(true));
+        // Stars do not count towards the length
+        assert!(is_valid(&[&longest[..], b"***"].concat()));
+
+        let tests: [(&[u8], &str); 4] = [
+            (&[&longest[..], b"a"].concat(), "pattern too long"),
+            (b"\\", "invalid escape"),
+            (b"a\\", "invalid escape"),
+            (b"\\a", "invalid escape"),
+        ];
+        for (pattern, err) in tests {
+            assert!(!is_valid(pattern), "{:?}", pattern);
+            assert_eq!(matches(pattern, b"a"), Err(err));
+        }
+    }
+
+    #[test]
+    fn it_matches_without_backtracking() {
+        // A pathological pattern for backtracking matchers
+        let pattern = [&b"a*".repeat(60)[..], b"b"].concat();
+        let value = vec![b'a'; 100_000];
+        assert_eq!(matches(&pattern, &value), Ok(false));
+        assert_eq!(matches(&pattern, &[&value[..], b"b"].concat()), Ok(true));
+    }
+}
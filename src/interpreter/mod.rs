@@ -16,21 +16,30 @@
 //!
 //! The pact bytecode interpreter
 //!
-use crate::types::PactType;
+use crate::types::{Numeric, PactTypeRef, Table};
+
+pub(crate) mod glob;
 
 pub use crate::types::opcode::{
-    Comparator, Conjunction, OpCode, OpComp, OpConj, OpIndices, OpLoad,
+    Arithmetic, Comparator, Conjunction, Features, Group, LoadSource, MaskEq, OpArith, OpCode,
+    OpComp, OpConj, OpGroup, OpIndices, OpLoad, OpPage, SubjectSource,
 };
+pub use glob::MAX_PATTERN_LEN;
+
+/// The maximum nesting depth of groups
+pub const MAX_GROUP_DEPTH: usize = 16;
 
 /// Interpret some pact byte code (`source`) with input data registers (`input_data`) and
 /// user data registers (`user_data`).
 /// Returns a boolean indicating whether the pact contract was validated or not,
 /// An `InterpErr` is returned on a runtime error e.g. malformed byte code, missing data, invalid OpCode etc.
-pub fn interpret(
-    input_data: &[PactType],
-    user_data: &[PactType],
-    source: &[u8],
-) -> Result<bool, InterpErr> {
+/// Data may be given as slices of `PactType`s or borrowed from an encoded contract
+/// e.g. `ContractRef::data_table`, in which case interpretation does not allocate.
+pub fn interpret<I, U>(input_data: &I, user_data: &U, source: &[u8]) -> Result<bool, InterpErr>
+where
+    I: Table + ?Sized,
+    U: Table + ?Sized,
+{
     let mut interpreter = Interpreter::new(input_data, user_data);
     let mut scanner = source.iter();
     while let Some(op) = OpCode::parse(&mut scanner)? {
@@ -42,21 +51,100 @@ pub fn interpret(
     }
 
     match interpreter.state {
+        State::Failed => Ok(false),
+        // Groups must be closed
+        _ if interpreter.depth > 0 => Err(InterpErr::UnexpectedEOI("unclosed group")),
         State::AssertionTrue => Ok(true),
-        State::Failed | State::AssertionFalse => Ok(false),
+        State::AssertionFalse => Ok(false),
         // Any other state is an Unexpected end of input
         _invalid => Err(InterpErr::UnexpectedEOI("incomplete operation")),
     }
 }
 
+/// Check `source` is a complete, well formed program without evaluating it
+/// i.e. every OpCode is valid, terms and conjunctions alternate and groups are balanced.
+/// Unlike `interpret`, OpCodes following a failed clause are checked.
+pub fn validate(source: &[u8]) -> Result<(), InterpErr> {
+    let mut scanner = source.iter();
+    let mut depth = 0;
+    // Whether the next OpCode must begin a term i.e. a comparator or an opening group
+    let mut expect_term = true;
+    // The pages declared, and whether REQUIRE OpCodes may still be declared
+    let mut features = Features::default();
+    let mut declaring = true;
+    while let Some(op) = OpCode::parse(&mut scanner)? {
+        if let OpCode::REQUIRE(page) = op {
+            features = declare(features, page)
+                .filter(|_| declaring)
+                .ok_or(InterpErr::UnexpectedOpCode(op.into()))?;
+            continue;
+        }
+        declaring = false;
+        check_declared(features, op)?;
+        match op {
+            // Outside of a group, a term may also begin a new clause
+            OpCode::COMP(_) | OpCode::ARITH(_) | OpCode::MASK(_) if expect_term || depth == 0 => {
+                expect_term = false
+            }
+            OpCode::GROUP(Group {
+                op: OpGroup::OPEN, ..
+            }) if expect_term || depth == 0 => {
+                if depth == MAX_GROUP_DEPTH {
+                    return Err(InterpErr::StackOverflow);
+                }
+                depth += 1;
+                expect_term = true;
+            }
+            OpCode::CONJ(_) if !expect_term => expect_term = true,
+            OpCode::GROUP(Group {
+                op: OpGroup::CLOSE, ..
+            }) if !expect_term && depth > 0 => depth -= 1,
+            _ => return Err(InterpErr::UnexpectedOpCode(op.into())),
+        }
+    }
+
+    if depth > 0 {
+        Err(InterpErr::UnexpectedEOI("unclosed group"))
+    } else if expect_term {
+        Err(InterpErr::UnexpectedEOI("incomplete operation"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Return `features` with `page` declared, unless it is out of order
+/// Pages are declared once each, in ascending order
+fn declare(features: Features, page: OpPage) -> Option<Features> {
+    match features.iter().last() {
+        Some(last) if last >= page => None,
+        _ => Some(features.with(page)),
+    }
+}
+
+/// Check the page of an extended OpCode is one of the declared `features`
+fn check_declared(features: Features, op: OpCode) -> Result<(), InterpErr> {
+    let page = match op {
+        OpCode::COMP(comparator) => comparator.op.page(),
+        OpCode::ARITH(_) => Some(OpPage::ARITH),
+        OpCode::MASK(_) => Some(OpPage::BITMASK),
+        OpCode::CONJ(_) | OpCode::GROUP(_) | OpCode::REQUIRE(_) => None,
+    };
+    match page {
+        Some(page) if !features.contains(page) => Err(InterpErr::UndeclaredFeature(page.id())),
+        _ => Ok(()),
+    }
+}
+
 /// An interpreter error
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "scale", derive(codec::Encode, scale_info::TypeInfo))]
 pub enum InterpErr {
     /// A comparison operator failed with incompatible types on LHS and RHS
     TypeMismatch,
     /// A comparison operator failed because it is not supported on the type
     BadTypeOperation,
     /// Unexpected end of input
+    /// New messages must be added to the SCALE codec's known messages in `types::scale`
     UnexpectedEOI(&'static str),
     /// Encountered an unexpected OpCode given the context
     UnexpectedOpCode(u8),
@@ -66,34 +154,69 @@ pub enum InterpErr {
     InvalidOpCode(u8),
     /// A referenced index in the data table does not exist
     MissingIndex(u8),
+    /// Groups were nested deeper than `MAX_GROUP_DEPTH`
+    StackOverflow,
     /// Raised when trying to execute an OpCode from an interpreter which is in a failed state
     Refused,
+    /// The contract requires an OpCode page which the interpreter does not support
+    UnsupportedFeature(u8),
+    /// The contract uses an OpCode from a page it does not declare with a REQUIRE OpCode
+    UndeclaredFeature(u8),
+    /// A glob pattern is invalid, only possible for contracts which were not decoded
+    /// New messages must be added to the SCALE codec's known messages in `types::scale`
+    InvalidPattern(&'static str),
 }
 
 /// Evaluate a comparator OpCode returning its result
 fn eval_comparator(
     comparator: Comparator,
-    lhs: &PactType,
-    rhs: &PactType,
+    lhs: &PactTypeRef,
+    rhs: &PactTypeRef,
 ) -> Result<bool, InterpErr> {
+    // Length comparators compare the length of the LHS as a numeric
+    let length;
+    let lhs = match comparator.op.page() {
+        Some(OpPage::LENGTH) => {
+            length = PactTypeRef::Numeric(length_of(lhs)?);
+            &length
+        }
+        _ => lhs,
+    };
+
     let value = match (lhs, rhs) {
-        (PactType::Numeric(l), PactType::Numeric(r)) => match comparator.op {
+        (PactTypeRef::Numeric(l), PactTypeRef::Numeric(r)) => match comparator.op {
+            OpComp::EQ | OpComp::LEN_EQ => Ok(l == r),
+            OpComp::GT | OpComp::LEN_GT => Ok(l > r),
+            OpComp::GTE | OpComp::LEN_GTE => Ok(l >= r),
+            OpComp::MASK_ALL => Ok(l.0 & r.0 == r.0),
+            OpComp::MASK_ANY => Ok(!(l.0 & r.0).is_zero()),
+            _ => Err(InterpErr::BadTypeOperation),
+        },
+        (PactTypeRef::Signed(l), PactTypeRef::Signed(r)) => match comparator.op {
             OpComp::EQ => Ok(l == r),
             OpComp::GT => Ok(l > r),
             OpComp::GTE => Ok(l >= r),
             _ => Err(InterpErr::BadTypeOperation),
         },
-        (PactType::StringLike(l), PactType::StringLike(r)) => match comparator.op {
+        (PactTypeRef::Bool(l), PactTypeRef::Bool(r)) => match comparator.op {
             OpComp::EQ => Ok(l == r),
             _ => Err(InterpErr::BadTypeOperation),
         },
-        (PactType::List(_), _) => match comparator.op {
+        (PactTypeRef::StringLike(l), PactTypeRef::StringLike(r)) => match comparator.op {
+            OpComp::EQ => Ok(l == r),
+            OpComp::PREFIX => Ok(l.starts_with(r)),
+            OpComp::SUFFIX => Ok(l.ends_with(r)),
+            OpComp::CONTAINS => Ok(r.is_empty() || l.windows(r.len()).any(|w| w == *r)),
+            // Decoded contracts hold only valid patterns
+            OpComp::GLOB => glob::matches(r, l).map_err(InterpErr::InvalidPattern),
             _ => Err(InterpErr::BadTypeOperation),
         },
-        (l, PactType::List(r)) => match comparator.op {
-            OpComp::IN => Ok(r.contains(l)),
+        // Lists may contain lists, so the LHS of `IN` may be a list element
+        (l, PactTypeRef::List(r)) => match comparator.op {
+            OpComp::IN => Ok(r.iter().any(|element| element == *l)),
             _ => Err(InterpErr::BadTypeOperation),
         },
+        (PactTypeRef::List(_), _) => Err(InterpErr::BadTypeOperation),
         _ => Err(InterpErr::TypeMismatch),
     }?;
 
@@ -105,6 +228,64 @@ fn eval_comparator(
     }
 }
 
+/// Evaluate an arithmetic comparator OpCode returning its result,
+/// or `None` if the arithmetic overflows
+fn eval_arithmetic(
+    arithmetic: Arithmetic,
+    lhs: &PactTypeRef,
+    term: &PactTypeRef,
+    rhs: &PactTypeRef,
+) -> Result<Option<bool>, InterpErr> {
+    let (l, t, r) = match (lhs, term, rhs) {
+        (PactTypeRef::Numeric(l), PactTypeRef::Numeric(t), PactTypeRef::Numeric(r)) => (l, t, r),
+        (PactTypeRef::Numeric(_), _, _) => return Err(InterpErr::TypeMismatch),
+        _ => return Err(InterpErr::BadTypeOperation),
+    };
+    let value = match arithmetic.op {
+        OpArith::ADD => l.0.checked_add(t.0),
+        OpArith::SUB => l.0.checked_sub(t.0),
+        OpArith::MUL => l.0.checked_mul(t.0),
+    };
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let result = match arithmetic.comp {
+        OpComp::EQ => value == r.0,
+        OpComp::GT => value > r.0,
+        OpComp::GTE => value >= r.0,
+        _ => return Err(InterpErr::BadTypeOperation),
+    };
+
+    // Apply inversion if required
+    Ok(Some(result ^ arithmetic.invert))
+}
+
+/// Evaluate a masked equality comparator OpCode returning its result
+fn eval_mask_eq(
+    mask: MaskEq,
+    lhs: &PactTypeRef,
+    term: &PactTypeRef,
+    rhs: &PactTypeRef,
+) -> Result<bool, InterpErr> {
+    match (lhs, term, rhs) {
+        (PactTypeRef::Numeric(l), PactTypeRef::Numeric(t), PactTypeRef::Numeric(r)) => {
+            Ok((l.0 & t.0 == r.0) ^ mask.invert)
+        }
+        (PactTypeRef::Numeric(_), _, _) => Err(InterpErr::TypeMismatch),
+        _ => Err(InterpErr::BadTypeOperation),
+    }
+}
+
+/// Return the length of a `StringLike` in bytes or a `List` in elements
+fn length_of(value: &PactTypeRef) -> Result<Numeric, InterpErr> {
+    let length = match value {
+        PactTypeRef::StringLike(s) => s.len(),
+        PactTypeRef::List(l) => l.iter().count(),
+        _ => return Err(InterpErr::BadTypeOperation),
+    };
+    Ok(Numeric(length.into()))
+}
+
 /// Evaluate a conjunction OpCode given an LHS and RHS boolean
 fn eval_conjunction(conjunction: &Conjunction, lhs: bool, rhs: bool) -> Result<bool, InterpErr> {
     let value = match conjunction.op {
@@ -126,122 +307,260 @@ fn eval_conjunction(conjunction: &Conjunction, lhs: bool, rhs: bool) -> Result<b
 /// Uses the rust type system to encode state, see: https://hoverbear.org/2016/10/12/rust-state-machine-pattern/
 /// States provide transformations into other valid states and failure cases.
 #[cfg_attr(feature = "std", derive(Debug))]
-pub struct Interpreter<'a> {
+pub struct Interpreter<'a, I: ?Sized, U: ?Sized> {
     state: State,
-    input_data: &'a [PactType],
-    user_data: &'a [PactType],
+    /// The enclosing states of open groups, innermost last
+    groups: [Frame; MAX_GROUP_DEPTH],
+    /// The number of open groups
+    depth: usize,
+    /// The pages declared by the contract
+    features: Features,
+    /// Whether REQUIRE OpCodes may still be declared i.e. no other OpCode has been executed
+    declaring: bool,
+    input_data: &'a I,
+    user_data: &'a U,
+}
+
+/// An open group
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy)]
+struct Frame {
+    /// The interpreter state when the group was opened, restored when it closes
+    outer: State,
+    /// Whether to invert the result of the group
+    invert: bool,
 }
 
-impl<'a> Interpreter<'a> {
+impl<'a, I: Table + ?Sized, U: Table + ?Sized> Interpreter<'a, I, U> {
     /// Return a new interpreter, ready for execution
-    pub fn new(input_data: &'a [PactType], user_data: &'a [PactType]) -> Self {
+    pub fn new(input_data: &'a I, user_data: &'a U) -> Self {
         Interpreter {
             state: State::Initial,
+            groups: [Frame {
+                outer: State::Initial,
+                invert: false,
+            }; MAX_GROUP_DEPTH],
+            depth: 0,
+            features: Features::default(),
+            declaring: true,
             input_data,
             user_data,
         }
     }
 
+    /// Declare a page of OpCodes the contract requires
+    /// Fails unless the page is supported, so a contract is rejected before it is evaluated
+    fn require(&mut self, page: OpPage) -> Result<(), InterpErr> {
+        let features = declare(self.features, page)
+            .filter(|_| self.declaring)
+            .ok_or(InterpErr::UnexpectedOpCode(OpCode::REQUIRE(page).into()))?;
+        if !Features::SUPPORTED.contains(page) {
+            return Err(InterpErr::UnsupportedFeature(page.id()));
+        }
+        self.features = features;
+        Ok(())
+    }
+
     /// Executes a comparator OpCode
     /// This belongs to the interpreter state machine and will update state
     /// based on the outcome
-    fn execute_comparator(&mut self, op: OpCode) -> Result<(), InterpErr> {
-        match op {
-            OpCode::COMP(comparator) => {
-                // Gather left and right hand side values
-                let lhs = self
-                    .input_data
-                    .get(comparator.indices.lhs as usize)
-                    .ok_or(InterpErr::MissingIndex(comparator.indices.lhs))?;
-
-                let rhs = match comparator.load {
-                    OpLoad::INPUT_VS_USER => self
-                        .user_data
-                        .get(comparator.indices.rhs as usize)
-                        .ok_or(InterpErr::MissingIndex(comparator.indices.rhs)),
-                    OpLoad::INPUT_VS_INPUT => self
-                        .input_data
-                        .get(comparator.indices.rhs as usize)
-                        .ok_or(InterpErr::MissingIndex(comparator.indices.rhs)),
-                }?;
-
-                let mut result = eval_comparator(comparator, &lhs, rhs)?;
-
-                // Evaluate the conjunction if necessary
-                match &self.state {
-                    State::Conjunctive {
-                        last_assertion,
-                        conjunction,
-                    } => {
-                        result = eval_conjunction(conjunction, *last_assertion, result)?;
-                    }
-                    _ => {}
-                };
+    fn execute_comparator(&mut self, comparator: Comparator) -> Result<(), InterpErr> {
+        check_declared(self.features, OpCode::COMP(comparator))?;
 
-                // The assertions and operations upto this point have all been collapsed into
-                // a single boolean.
-                if result {
-                    self.state = State::AssertionTrue;
-                } else {
-                    self.state = State::AssertionFalse;
-                };
+        // Gather left and right hand side values
+        let lhs = Table::get(self.input_data, comparator.indices.lhs as usize)
+            .ok_or(InterpErr::MissingIndex(comparator.indices.lhs))?;
+
+        let rhs = match comparator.load {
+            OpLoad::INPUT_VS_USER => Table::get(self.user_data, comparator.indices.rhs as usize)
+                .ok_or(InterpErr::MissingIndex(comparator.indices.rhs)),
+            OpLoad::INPUT_VS_INPUT => Table::get(self.input_data, comparator.indices.rhs as usize)
+                .ok_or(InterpErr::MissingIndex(comparator.indices.rhs)),
+        }?;
+
+        let result = eval_comparator(comparator, &lhs, &rhs)?;
+        self.assert(result)
+    }
+
+    /// Executes an arithmetic comparator OpCode
+    /// Arithmetic which overflows fails the contract, whatever the enclosing clause and groups
+    fn execute_arithmetic(&mut self, arithmetic: Arithmetic) -> Result<(), InterpErr> {
+        check_declared(self.features, OpCode::ARITH(arithmetic))?;
+
+        let (lhs, term, rhs) =
+            self.load_with_term(arithmetic.load, arithmetic.indices, arithmetic.term)?;
+        match eval_arithmetic(arithmetic, &lhs, &term, &rhs)? {
+            Some(result) => self.assert(result),
+            None => {
+                self.state = State::Failed;
                 Ok(())
             }
-            _ => Err(InterpErr::UnexpectedOpCode(op.into())),
         }
     }
 
+    /// Executes a masked equality comparator OpCode
+    fn execute_mask_eq(&mut self, mask: MaskEq) -> Result<(), InterpErr> {
+        check_declared(self.features, OpCode::MASK(mask))?;
+
+        let (lhs, term, rhs) = self.load_with_term(mask.load, mask.indices, mask.term)?;
+        let result = eval_mask_eq(mask, &lhs, &term, &rhs)?;
+        self.assert(result)
+    }
+
+    /// Gather the LHS, term and RHS operands of a comparator which takes a term
+    fn load_with_term(
+        &self,
+        load: OpLoad,
+        indices: OpIndices,
+        term: SubjectSource,
+    ) -> Result<(PactTypeRef<'a>, PactTypeRef<'a>, PactTypeRef<'a>), InterpErr> {
+        let lhs = Table::get(self.input_data, indices.lhs as usize)
+            .ok_or(InterpErr::MissingIndex(indices.lhs))?;
+
+        let term_value = match term.load_source {
+            LoadSource::DataTable => Table::get(self.user_data, term.index as usize),
+            LoadSource::Input => Table::get(self.input_data, term.index as usize),
+        }
+        .ok_or(InterpErr::MissingIndex(term.index))?;
+
+        let rhs = match load {
+            OpLoad::INPUT_VS_USER => Table::get(self.user_data, indices.rhs as usize),
+            OpLoad::INPUT_VS_INPUT => Table::get(self.input_data, indices.rhs as usize),
+        }
+        .ok_or(InterpErr::MissingIndex(indices.rhs))?;
+
+        Ok((lhs, term_value, rhs))
+    }
+
+    /// Record the result of an assertion or group
+    /// Evaluates the pending conjunction if necessary
+    fn assert(&mut self, mut result: bool) -> Result<(), InterpErr> {
+        if let State::Conjunctive {
+            last_assertion,
+            conjunction,
+        } = &self.state
+        {
+            result = eval_conjunction(conjunction, *last_assertion, result)?;
+        }
+
+        // The assertions and operations upto this point have all been collapsed into
+        // a single boolean.
+        if result {
+            self.state = State::AssertionTrue;
+        } else {
+            self.state = State::AssertionFalse;
+        };
+        Ok(())
+    }
+
+    /// Open a group, saving the current state until it is closed
+    fn open_group(&mut self, invert: bool) -> Result<(), InterpErr> {
+        if self.depth == MAX_GROUP_DEPTH {
+            return Err(InterpErr::StackOverflow);
+        }
+        self.groups[self.depth] = Frame {
+            outer: self.state,
+            invert,
+        };
+        self.depth += 1;
+        self.state = State::Initial;
+        Ok(())
+    }
+
+    /// Close the innermost group, asserting its result in the enclosing state
+    fn close_group(&mut self, result: bool) -> Result<(), InterpErr> {
+        self.depth -= 1;
+        let frame = self.groups[self.depth];
+        self.state = frame.outer;
+        self.assert(result ^ frame.invert)
+    }
+
     /// Interpreter state machine
     pub fn interpret(&mut self, op: OpCode) -> Result<(), InterpErr> {
+        // REQUIRE OpCodes precede all others
+        match (op, &self.state) {
+            (_, State::Failed) => return Err(InterpErr::Refused),
+            (OpCode::REQUIRE(page), _) => return self.require(page),
+            _ => self.declaring = false,
+        }
         match &self.state {
-            // First op code must be a comparator
-            State::Initial => self.execute_comparator(op),
+            // First op code must be a comparator or group
+            State::Initial => self.execute_term(op),
             State::AssertionTrue => match op {
-                OpCode::COMP(_) => self.execute_comparator(op),
-                OpCode::CONJ(conjunction) => {
-                    self.state = State::Conjunctive {
-                        last_assertion: true,
-                        conjunction: conjunction,
-                    };
-                    Ok(())
+                // An assertion outside of a group begins a new clause
+                OpCode::COMP(_)
+                | OpCode::ARITH(_)
+                | OpCode::MASK(_)
+                | OpCode::GROUP(Group {
+                    op: OpGroup::OPEN, ..
+                }) if self.depth == 0 => {
+                    self.state = State::Initial;
+                    self.execute_term(op)
                 }
+                _ => self.continue_assertion(op, true),
             },
             State::AssertionFalse => {
                 match op {
                     // There is no continuation of the last assertion.
                     // This is now considered a failed clause, and hence the contract has failed
-                    OpCode::COMP(_) => {
+                    OpCode::COMP(_)
+                    | OpCode::ARITH(_)
+                    | OpCode::MASK(_)
+                    | OpCode::GROUP(Group {
+                        op: OpGroup::OPEN, ..
+                    }) if self.depth == 0 => {
                         self.state = State::Failed;
                         Ok(())
                     }
-                    // The conjunction will determine whether the contract has failed or succeeded
-                    OpCode::CONJ(conjunction) => {
-                        self.state = State::Conjunctive {
-                            last_assertion: false,
-                            conjunction: conjunction,
-                        };
-                        Ok(())
-                    }
+                    _ => self.continue_assertion(op, false),
                 }
             }
             State::Conjunctive {
                 last_assertion: _,
                 conjunction: _,
             } => {
-                // A Conjunction must be followed by a comparator
-                match op {
-                    OpCode::COMP(_) => self.execute_comparator(op),
-                    OpCode::CONJ(_) => {
-                        return Err(InterpErr::UnexpectedOpCode(op.into()));
-                    }
-                }
+                // A Conjunction must be followed by a comparator or group
+                self.execute_term(op)
             }
             State::Failed => Err(InterpErr::Refused),
         }
     }
+
+    /// Execute an OpCode which begins a term i.e. a comparator or an opening group
+    fn execute_term(&mut self, op: OpCode) -> Result<(), InterpErr> {
+        match op {
+            OpCode::COMP(comparator) => self.execute_comparator(comparator),
+            OpCode::ARITH(arithmetic) => self.execute_arithmetic(arithmetic),
+            OpCode::MASK(mask) => self.execute_mask_eq(mask),
+            OpCode::GROUP(Group {
+                op: OpGroup::OPEN,
+                invert,
+            }) => self.open_group(invert),
+            _ => Err(InterpErr::UnexpectedOpCode(op.into())),
+        }
+    }
+
+    /// Execute an OpCode which follows an assertion with the given result
+    fn continue_assertion(&mut self, op: OpCode, last_assertion: bool) -> Result<(), InterpErr> {
+        match op {
+            // The conjunction will determine whether the assertion holds
+            OpCode::CONJ(conjunction) => {
+                self.state = State::Conjunctive {
+                    last_assertion,
+                    conjunction,
+                };
+                Ok(())
+            }
+            OpCode::GROUP(Group {
+                op: OpGroup::CLOSE, ..
+            }) if self.depth > 0 => self.close_group(last_assertion),
+            _ => Err(InterpErr::UnexpectedOpCode(op.into())),
+        }
+    }
 }
 
 #[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy)]
 pub enum State {
     /// The initial interpreter state
     Initial,
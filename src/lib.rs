@@ -21,5 +21,9 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std as alloc;
 
+#[cfg(feature = "compiler")]
+pub mod compiler;
 pub mod interpreter;
+#[cfg(feature = "compiler")]
+pub mod parser;
 pub mod types;
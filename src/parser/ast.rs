@@ -0,0 +1,158 @@
+// Copyright 2023-2024 Futureverse Corporation Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Pact DSL abstract syntax tree
+//!
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use primitive_types::U256;
+
+/// A byte range into the source text
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// Return a span covering both `self` and `other`
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+        }
+    }
+}
+
+/// A parsed pact contract
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contract {
+    /// The declared input parameters, in call order
+    pub parameters: Vec<Ident>,
+    /// Definitions and clauses, in source order
+    pub statements: Vec<Statement>,
+}
+
+/// A top level statement
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// `define $ident as <value>`
+    Definition(Definition),
+    /// One or more assertions joined by conjunctives
+    Clause(Clause),
+}
+
+/// A named, static value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub ident: Ident,
+    pub value: Value,
+}
+
+/// A chain of terms folded left to right e.g. `<a> and <b> or <c>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clause {
+    pub head: Term,
+    pub tail: Vec<(Conjunctive, Term)>,
+}
+
+/// An operand of a clause
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Assertion(Assertion),
+    Group(Group),
+}
+
+/// A parenthesized clause e.g. `not ($a must be 1 or $b must be 2)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    /// Whether the group is preceded by `not`
+    pub negated: bool,
+    pub clause: Box<Clause>,
+    pub span: Span,
+}
+
+/// A single assertion e.g. `$amount must not be greater than 100`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assertion {
+    pub subject: Value,
+    pub imperative: Imperative,
+    pub comparator: Comparator,
+    pub object: Value,
+    pub span: Span,
+}
+
+/// An identifier e.g. `$amount`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ident {
+    /// The identifier name without its leading `$`
+    pub name: String,
+    pub span: Span,
+}
+
+/// A value operand
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Ident(Ident),
+    Literal(Literal, Span),
+}
+
+impl Value {
+    /// Return the source span of the value
+    pub fn span(&self) -> Span {
+        match self {
+            Value::Ident(ident) => ident.span,
+            Value::Literal(_, span) => *span,
+        }
+    }
+}
+
+/// A literal value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Integer(U256),
+    Signed(i128),
+    Bool(bool),
+    String(Vec<u8>),
+    List(Vec<Literal>),
+}
+
+/// `must be` or `must not be`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Imperative {
+    MustBe,
+    MustNotBe,
+}
+
+/// A comparison between the subject and object of an assertion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparator {
+    Equal,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+    OneOf,
+}
+
+/// A logical join between two assertions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Conjunctive {
+    And,
+    Or,
+    Xor,
+}
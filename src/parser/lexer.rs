@@ -0,0 +1,373 @@
+// Copyright 2023-2024 Futureverse Corporation Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Pact DSL lexer
+//!
+use super::ast::Span;
+use super::ParseErr;
+use alloc::string::String;
+use alloc::vec::Vec;
+use primitive_types::U256;
+
+/// A lexical token
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// `$name`
+    Ident(String),
+    /// A bare word e.g. `must`, `greater`
+    Word(String),
+    /// An unsigned integer literal
+    Integer(U256),
+    /// An integer literal with an explicit sign e.g. `-5`, `+5`
+    Signed(i128),
+    /// A double quoted string literal or `0x` prefixed byte string
+    String(Vec<u8>),
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Comma,
+}
+
+/// A token and its location in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned {
+    pub token: Token,
+    pub span: Span,
+    /// Whether this is the first token on its line
+    pub line_start: bool,
+}
+
+/// Split `source` into tokens.
+/// Whitespace and `#` line comments are skipped.
+/// Lexing continues past invalid input so that every error is reported.
+pub fn tokenize(source: &str) -> (Vec<Spanned>, Vec<ParseErr>) {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::<Spanned>::new();
+    let mut errors = Vec::<ParseErr>::new();
+    let mut line_start = true;
+    let mut i = 0_usize;
+
+    while i < bytes.len() {
+        let start = i;
+        let token = match bytes[i] {
+            b'\n' => {
+                line_start = true;
+                i += 1;
+                continue;
+            }
+            b' ' | b'\t' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'[' => {
+                i += 1;
+                Token::LBracket
+            }
+            b']' => {
+                i += 1;
+                Token::RBracket
+            }
+            b'(' => {
+                i += 1;
+                Token::LParen
+            }
+            b')' => {
+                i += 1;
+                Token::RParen
+            }
+            b',' => {
+                i += 1;
+                Token::Comma
+            }
+            b'$' => {
+                i += 1;
+                while i < bytes.len() && is_ident_char(bytes[i]) {
+                    i += 1;
+                }
+                if i == start + 1 {
+                    errors.push(ParseErr::InvalidIdent(Span { start, end: i }));
+                    continue;
+                }
+                Token::Ident(source[start + 1..i].into())
+            }
+            b'"' => {
+                i += 1;
+                let mut value = Vec::<u8>::new();
+                loop {
+                    match bytes.get(i) {
+                        None | Some(b'\n') => {
+                            errors.push(ParseErr::UnterminatedString(Span { start, end: i }));
+                            break;
+                        }
+                        Some(b'"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some(b'\\') => {
+                            let escaped = match bytes.get(i + 1) {
+                                Some(b'"') => b'"',
+                                Some(b'\\') => b'\\',
+                                Some(b'n') => b'\n',
+                                Some(b't') => b'\t',
+                                _ => {
+                                    errors.push(ParseErr::InvalidEscape(Span {
+                                        start: i,
+                                        end: i + 1 + bytes.get(i + 1).map_or(0, |_| 1),
+                                    }));
+                                    i += 1;
+                                    continue;
+                                }
+                            };
+                            value.push(escaped);
+                            i += 2;
+                        }
+                        Some(b) => {
+                            value.push(*b);
+                            i += 1;
+                        }
+                    }
+                }
+                Token::String(value)
+            }
+            b'0' if bytes.get(i + 1) == Some(&b'x') => {
+                i += 2;
+                while i < bytes.len() && bytes[i].is_ascii_hexdigit() {
+                    i += 1;
+                }
+                let digits = &bytes[start + 2..i];
+                if !digits.len().is_multiple_of(2) {
+                    errors.push(ParseErr::InvalidHex(Span { start, end: i }));
+                }
+                Token::String(
+                    digits
+                        .chunks_exact(2)
+                        .map(|pair| hex_value(pair[0]) << 4 | hex_value(pair[1]))
+                        .collect(),
+                )
+            }
+            b'0'..=b'9' => {
+                while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let mut n = Some(U256::zero());
+                for b in bytes[start..i].iter().filter(|b| **b != b'_') {
+                    n = n
+                        .and_then(|n| n.checked_mul(10.into()))
+                        .and_then(|n| n.checked_add((b - b'0').into()));
+                }
+                if n.is_none() {
+                    errors.push(ParseErr::IntegerOverflow(Span { start, end: i }));
+                }
+                // Keep a placeholder token so the parser does not report a cascading error
+                Token::Integer(n.unwrap_or_default())
+            }
+            sign @ (b'+' | b'-') if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                i += 1;
+                while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                // Accumulate towards the sign so that `i128::MIN` is representable
+                let mut n = Some(0_i128);
+                for b in bytes[start + 1..i].iter().filter(|b| **b != b'_') {
+                    let digit = i128::from(b - b'0');
+                    n = n.and_then(|n| n.checked_mul(10)).and_then(|n| {
+                        if sign == b'-' {
+                            n.checked_sub(digit)
+                        } else {
+                            n.checked_add(digit)
+                        }
+                    });
+                }
+                if n.is_none() {
+                    errors.push(ParseErr::IntegerOverflow(Span { start, end: i }));
+                }
+                Token::Signed(n.unwrap_or_default())
+            }
+            b if b.is_ascii_alphabetic() => {
+                while i < bytes.len() && bytes[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                Token::Word(source[start..i].to_ascii_lowercase())
+            }
+            _ => {
+                // Step over the whole (possibly multi-byte) character
+                let width = source[start..].chars().next().map_or(1, char::len_utf8);
+                i += width;
+                errors.push(ParseErr::UnexpectedChar(Span { start, end: i }));
+                continue;
+            }
+        };
+        tokens.push(Spanned {
+            token,
+            span: Span { start, end: i },
+            line_start,
+        });
+        line_start = false;
+    }
+
+    (tokens, errors)
+}
+
+fn is_ident_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// The value of an ASCII hex digit
+fn hex_value(b: u8) -> u8 {
+    match b {
+        b'0'..=b'9' => b - b'0',
+        b'a'..=b'f' => b - b'a' + 10,
+        _ => b - b'A' + 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        let (tokens, errors) = tokenize(source);
+        assert_eq!(errors, vec![]);
+        tokens.into_iter().map(|t| t.token).collect()
+    }
+
+    #[test]
+    fn it_tokenizes_an_assertion() {
+        assert_eq!(
+            tokens("$amount must not be greater than 1_000"),
+            vec![
+                Token::Ident("amount".into()),
+                Token::Word("must".into()),
+                Token::Word("not".into()),
+                Token::Word("be".into()),
+                Token::Word("greater".into()),
+                Token::Word("than".into()),
+                Token::Integer(1000.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_tokenizes_lists_and_strings() {
+        assert_eq!(
+            tokens(r#"["a\"b", 2] # trailing comment"#),
+            vec![
+                Token::LBracket,
+                Token::String(b"a\"b".to_vec()),
+                Token::Comma,
+                Token::Integer(2.into()),
+                Token::RBracket,
+            ]
+        );
+    }
+
+    #[test]
+    fn it_tokenizes_groups() {
+        assert_eq!(
+            tokens("not ($a)"),
+            vec![
+                Token::Word("not".into()),
+                Token::LParen,
+                Token::Ident("a".into()),
+                Token::RParen,
+            ]
+        );
+    }
+
+    #[test]
+    fn it_tokenizes_signed_integers() {
+        assert_eq!(
+            tokens("-5 +1_000 -170141183460469231731687303715884105728"),
+            vec![
+                Token::Signed(-5),
+                Token::Signed(1000),
+                Token::Signed(i128::MIN)
+            ]
+        );
+        assert_eq!(
+            tokenize("+170141183460469231731687303715884105728").1,
+            vec![ParseErr::IntegerOverflow(Span { start: 0, end: 40 })]
+        );
+        assert_eq!(
+            tokenize("- 5").1,
+            vec![ParseErr::UnexpectedChar(Span { start: 0, end: 1 })]
+        );
+    }
+
+    #[test]
+    fn it_tokenizes_byte_strings() {
+        assert_eq!(
+            tokens("0x00fF10 0x"),
+            vec![Token::String(vec![0x00, 0xff, 0x10]), Token::String(vec![])]
+        );
+        assert_eq!(
+            tokenize("0xabc").1,
+            vec![ParseErr::InvalidHex(Span { start: 0, end: 5 })]
+        );
+    }
+
+    #[test]
+    fn it_records_spans() {
+        let (spanned, _) = tokenize("  $x $y\n$z");
+        assert_eq!(spanned[0].span, Span { start: 2, end: 4 });
+        let line_starts: Vec<bool> = spanned.iter().map(|t| t.line_start).collect();
+        assert_eq!(line_starts, vec![true, false, true]);
+    }
+
+    #[test]
+    fn it_fails_on_bad_input() {
+        assert_eq!(
+            tokenize("\"open").1,
+            vec![ParseErr::UnterminatedString(Span { start: 0, end: 5 })]
+        );
+        // 2^256
+        let overflow =
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert_eq!(
+            tokenize(overflow).1,
+            vec![ParseErr::IntegerOverflow(Span { start: 0, end: 78 })]
+        );
+        assert_eq!(
+            tokenize("$x @").1,
+            vec![ParseErr::UnexpectedChar(Span { start: 3, end: 4 })]
+        );
+        assert_eq!(
+            tokenize("$ x").1,
+            vec![ParseErr::InvalidIdent(Span { start: 0, end: 1 })]
+        );
+    }
+
+    #[test]
+    fn it_reports_every_error() {
+        let (tokens, errors) = tokenize("$x @ \"a\\q\" ~ 1");
+        assert_eq!(
+            errors,
+            vec![
+                ParseErr::UnexpectedChar(Span { start: 3, end: 4 }),
+                ParseErr::InvalidEscape(Span { start: 7, end: 9 }),
+                ParseErr::UnexpectedChar(Span { start: 11, end: 12 }),
+            ]
+        );
+        assert_eq!(tokens.len(), 3);
+    }
+}
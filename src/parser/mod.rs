@@ -0,0 +1,741 @@
+// Copyright 2023-2024 Futureverse Corporation Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! The pact DSL parser
+//!
+//! Parses contract source text (see `design/pact/README.md`) into an `ast::Contract`.
+//!
+//! ```pact
+//! given parameters $payee, $amount, $asset_id
+//!
+//! define $assets as [16001, 16010]
+//!
+//! $payee must be equal to "alice" and $amount must not be greater than 100
+//! $asset_id must be one of $assets
+//! ($payee must be "bob" or $amount must be less than 10) and not ($asset_id must be 16010)
+//! ```
+//!
+//! String literals are written `"text"` or as `0x` prefixed hex for binary data.
+//! Integer literals with an explicit sign (`-5`, `+5`) are signed.
+//!
+pub mod ast;
+mod lexer;
+
+use crate::interpreter::MAX_GROUP_DEPTH;
+use crate::types::MAX_LIST_DEPTH;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use ast::Span;
+use core::fmt;
+use lexer::{Spanned, Token};
+
+/// A parser error
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErr {
+    /// A character which does not begin any token
+    UnexpectedChar(Span),
+    /// A string literal with no closing quote
+    UnterminatedString(Span),
+    /// An unknown escape sequence in a string literal
+    InvalidEscape(Span),
+    /// An integer literal which does not fit in 256-bits
+    IntegerOverflow(Span),
+    /// A `0x` byte string with an odd number of hex digits
+    InvalidHex(Span),
+    /// A `$` with no identifier name
+    InvalidIdent(Span),
+    /// Encountered a token which is not valid at this position
+    UnexpectedToken { expected: &'static str, span: Span },
+    /// The source ended while more input was expected
+    UnexpectedEOI { expected: &'static str, span: Span },
+    /// A group or list is nested too deeply to parse
+    TooDeeplyNested(Span),
+}
+
+impl ParseErr {
+    /// Return the source span of the error
+    pub fn span(&self) -> Span {
+        match self {
+            ParseErr::UnexpectedChar(span)
+            | ParseErr::UnterminatedString(span)
+            | ParseErr::InvalidEscape(span)
+            | ParseErr::IntegerOverflow(span)
+            | ParseErr::InvalidHex(span)
+            | ParseErr::InvalidIdent(span)
+            | ParseErr::TooDeeplyNested(span)
+            | ParseErr::UnexpectedToken { span, .. }
+            | ParseErr::UnexpectedEOI { span, .. } => *span,
+        }
+    }
+
+    /// Return a suggested fix for the error
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            ParseErr::UnexpectedChar(_) => Some("remove this character; comments begin with `#`"),
+            ParseErr::UnterminatedString(_) => Some("close the string with `\"`"),
+            ParseErr::InvalidEscape(_) => {
+                Some("supported escapes are `\\\"`, `\\\\`, `\\n` and `\\t`")
+            }
+            ParseErr::IntegerOverflow(_) => {
+                Some("integers must be less than 2^256, signed integers must fit in 128-bits")
+            }
+            ParseErr::InvalidHex(_) => Some("each byte must be written as two hex digits"),
+            ParseErr::InvalidIdent(_) => Some("identifiers are written as `$name`"),
+            ParseErr::TooDeeplyNested(_) => Some("reduce the nesting of groups and lists"),
+            ParseErr::UnexpectedToken { expected, .. } if *expected == COMPARATORS => Some(
+                "use one of `equal to`, `greater than`, `greater than or equal to`, \
+                 `less than`, `less than or equal to` or `one of`",
+            ),
+            ParseErr::UnexpectedToken { expected, .. } if *expected == "given" => {
+                Some("contracts begin with a header e.g. `given parameters $a, $b`")
+            }
+            ParseErr::UnexpectedToken { .. } | ParseErr::UnexpectedEOI { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErr::UnexpectedChar(_) => write!(f, "unexpected character"),
+            ParseErr::UnterminatedString(_) => write!(f, "unterminated string literal"),
+            ParseErr::InvalidEscape(_) => write!(f, "invalid escape sequence"),
+            ParseErr::IntegerOverflow(_) => write!(f, "integer literal is too large"),
+            ParseErr::InvalidHex(_) => write!(f, "invalid byte string literal"),
+            ParseErr::InvalidIdent(_) => write!(f, "expected an identifier name after `$`"),
+            ParseErr::TooDeeplyNested(_) => write!(f, "nested too deeply"),
+            ParseErr::UnexpectedToken { expected, .. } => write!(f, "expected {}", expected),
+            ParseErr::UnexpectedEOI { expected, .. } => {
+                write!(f, "expected {}, found end of input", expected)
+            }
+        }
+    }
+}
+
+/// The expectation reported for an unknown comparator
+const COMPARATORS: &str = "a comparator";
+
+/// The deepest nesting of groups and of lists the parser descends into.
+/// One level beyond the supported depth is parsed, so the compiler reports the offending group or list
+const MAX_PARSE_GROUP_DEPTH: usize = MAX_GROUP_DEPTH + 1;
+const MAX_PARSE_LIST_DEPTH: usize = MAX_LIST_DEPTH + 1;
+
+/// Parse pact DSL `source` into an AST.
+/// Returns every error encountered on failure.
+pub fn parse(source: &str) -> Result<ast::Contract, Vec<ParseErr>> {
+    let (contract, errors) = parse_partial(source);
+    if errors.is_empty() {
+        Ok(contract)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parse pact DSL `source`, recovering from errors.
+/// Returns the AST of every well formed statement along with all errors encountered.
+pub fn parse_partial(source: &str) -> (ast::Contract, Vec<ParseErr>) {
+    let (tokens, errors) = lexer::tokenize(source);
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        eoi: Span {
+            start: source.len(),
+            end: source.len(),
+        },
+        errors,
+    };
+    let contract = parser.contract();
+    let mut errors = parser.errors;
+    errors.sort_by_key(|e| e.span().start);
+    (contract, errors)
+}
+
+/// A recursive descent parser over a token stream
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    /// The (empty) span at the end of input
+    eoi: Span,
+    errors: Vec<ParseErr>,
+}
+
+impl Parser {
+    /// contract: header statement*
+    fn contract(&mut self) -> ast::Contract {
+        let parameters = match self.header() {
+            Ok(parameters) => parameters,
+            Err(err) => {
+                self.recover(err);
+                Vec::new()
+            }
+        };
+        let mut statements = Vec::<ast::Statement>::new();
+        while self.peek().is_some() {
+            let statement = if self.peek_word("define") {
+                self.definition().map(ast::Statement::Definition)
+            } else {
+                self.clause(0).map(ast::Statement::Clause)
+            };
+            match statement {
+                Ok(statement) => statements.push(statement),
+                Err(err) => self.recover(err),
+            }
+        }
+        ast::Contract {
+            parameters,
+            statements,
+        }
+    }
+
+    /// Record `err` and skip ahead to the next token which could begin a statement
+    /// i.e. the first `define`, `not`, `(`, identifier or literal on a new line.
+    fn recover(&mut self, err: ParseErr) {
+        self.errors.push(err);
+        while let Some(next) = self.tokens.get(self.pos) {
+            let starts_statement = match &next.token {
+                Token::Word(w) => matches!(w.as_str(), "define" | "not" | "true" | "false"),
+                Token::Ident(_)
+                | Token::Integer(_)
+                | Token::Signed(_)
+                | Token::String(_)
+                | Token::LBracket
+                | Token::LParen => true,
+                Token::RBracket | Token::RParen | Token::Comma => false,
+            };
+            if next.line_start && starts_statement {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// header: GIVEN [PARAMETERS] ident_list
+    fn header(&mut self) -> Result<Vec<ast::Ident>, ParseErr> {
+        self.expect_word("given")?;
+        if self.peek_word("parameters") {
+            self.pos += 1;
+        }
+        let mut parameters = vec![self.ident()?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            parameters.push(self.ident()?);
+        }
+        Ok(parameters)
+    }
+
+    /// definition: DEFINE ident AS literal
+    fn definition(&mut self) -> Result<ast::Definition, ParseErr> {
+        self.expect_word("define")?;
+        let ident = self.ident()?;
+        self.expect_word("as")?;
+        let value = self.literal()?;
+        Ok(ast::Definition { ident, value })
+    }
+
+    /// clause: term ((and | or | xor) term)*
+    /// `depth` is the number of groups enclosing the clause
+    fn clause(&mut self, depth: usize) -> Result<ast::Clause, ParseErr> {
+        let head = self.term(depth)?;
+        let mut tail = Vec::<(ast::Conjunctive, ast::Term)>::new();
+        loop {
+            let conjunctive = if self.peek_word("and") {
+                ast::Conjunctive::And
+            } else if self.peek_word("or") {
+                ast::Conjunctive::Or
+            } else if self.peek_word("xor") {
+                ast::Conjunctive::Xor
+            } else {
+                break;
+            };
+            self.pos += 1;
+            tail.push((conjunctive, self.term(depth)?));
+        }
+        Ok(ast::Clause { head, tail })
+    }
+
+    /// term: assertion | [NOT] '(' clause ')'
+    fn term(&mut self, depth: usize) -> Result<ast::Term, ParseErr> {
+        let negated = self.peek_word("not");
+        if !negated && self.peek() != Some(&Token::LParen) {
+            return self.assertion().map(ast::Term::Assertion);
+        }
+        let start = self.next("`(`")?;
+        if depth == MAX_PARSE_GROUP_DEPTH {
+            return Err(ParseErr::TooDeeplyNested(start.span));
+        }
+        if negated && self.next("`(`")?.token != Token::LParen {
+            return Err(ParseErr::UnexpectedToken {
+                expected: "`(`",
+                span: self.tokens[self.pos - 1].span,
+            });
+        }
+        let clause = self.clause(depth + 1)?;
+        let close = self.next("`)`")?;
+        if close.token != Token::RParen {
+            return Err(ParseErr::UnexpectedToken {
+                expected: "a conjunctive or `)`",
+                span: close.span,
+            });
+        }
+        Ok(ast::Term::Group(ast::Group {
+            negated,
+            clause: Box::new(clause),
+            span: start.span.to(close.span),
+        }))
+    }
+
+    /// assertion: value imperative comparator value
+    fn assertion(&mut self) -> Result<ast::Assertion, ParseErr> {
+        let subject = self.value()?;
+        let imperative = self.imperative()?;
+        let comparator = self.comparator()?;
+        let object = self.value()?;
+        Ok(ast::Assertion {
+            span: subject.span().to(object.span()),
+            subject,
+            imperative,
+            comparator,
+            object,
+        })
+    }
+
+    /// imperative: MUST BE | MUST NOT BE
+    fn imperative(&mut self) -> Result<ast::Imperative, ParseErr> {
+        self.expect_word("must")?;
+        let imperative = if self.peek_word("not") {
+            self.pos += 1;
+            ast::Imperative::MustNotBe
+        } else {
+            ast::Imperative::MustBe
+        };
+        self.expect_word("be")?;
+        Ok(imperative)
+    }
+
+    /// comparator: EQUAL TO | GREATER THAN [OR EQUAL TO] | LESS THAN [OR EQUAL TO] | ONE OF | IN
+    /// An omitted comparator is taken to mean `EQUAL TO` e.g. `$x must be 5`
+    fn comparator(&mut self) -> Result<ast::Comparator, ParseErr> {
+        let word = match self.peek() {
+            Some(Token::Word(word)) => word.clone(),
+            _ => return Ok(ast::Comparator::Equal),
+        };
+        self.pos += 1;
+        match word.as_str() {
+            "equal" => {
+                self.expect_word("to")?;
+                Ok(ast::Comparator::Equal)
+            }
+            "greater" => {
+                self.expect_word("than")?;
+                if self.or_equal_to()? {
+                    Ok(ast::Comparator::GreaterThanOrEqual)
+                } else {
+                    Ok(ast::Comparator::GreaterThan)
+                }
+            }
+            "less" => {
+                self.expect_word("than")?;
+                if self.or_equal_to()? {
+                    Ok(ast::Comparator::LessThanOrEqual)
+                } else {
+                    Ok(ast::Comparator::LessThan)
+                }
+            }
+            "one" => {
+                self.expect_word("of")?;
+                Ok(ast::Comparator::OneOf)
+            }
+            "in" => Ok(ast::Comparator::OneOf),
+            _ => Err(ParseErr::UnexpectedToken {
+                expected: COMPARATORS,
+                span: self.tokens[self.pos - 1].span,
+            }),
+        }
+    }
+
+    /// Consume a trailing `OR EQUAL TO` if present.
+    /// `or` alone is left for the clause as a conjunctive.
+    fn or_equal_to(&mut self) -> Result<bool, ParseErr> {
+        let is_or_equal = self.peek_word("or")
+            && matches!(
+                self.tokens.get(self.pos + 1).map(|t| &t.token),
+                Some(Token::Word(w)) if w == "equal"
+            );
+        if is_or_equal {
+            self.pos += 2;
+            self.expect_word("to")?;
+        }
+        Ok(is_or_equal)
+    }
+
+    /// value: ident | literal
+    fn value(&mut self) -> Result<ast::Value, ParseErr> {
+        if let Some(Token::Ident(_)) = self.peek() {
+            return Ok(ast::Value::Ident(self.ident()?));
+        }
+        self.literal()
+    }
+
+    /// literal: integer | signed | string | TRUE | FALSE | '[' literal (',' literal)* ']'
+    fn literal(&mut self) -> Result<ast::Value, ParseErr> {
+        let (literal, span) = self.literal_inner(0)?;
+        Ok(ast::Value::Literal(literal, span))
+    }
+
+    /// `depth` is the number of lists enclosing the literal
+    fn literal_inner(&mut self, depth: usize) -> Result<(ast::Literal, Span), ParseErr> {
+        let next = self.next("a value")?;
+        match next.token {
+            Token::Integer(n) => Ok((ast::Literal::Integer(n), next.span)),
+            Token::Signed(n) => Ok((ast::Literal::Signed(n), next.span)),
+            Token::Word(w) if w == "true" => Ok((ast::Literal::Bool(true), next.span)),
+            Token::Word(w) if w == "false" => Ok((ast::Literal::Bool(false), next.span)),
+            Token::String(s) => Ok((ast::Literal::String(s), next.span)),
+            Token::LBracket if depth == MAX_PARSE_LIST_DEPTH => {
+                Err(ParseErr::TooDeeplyNested(next.span))
+            }
+            Token::LBracket => {
+                let mut elements = Vec::<ast::Literal>::new();
+                if self.peek() != Some(&Token::RBracket) {
+                    elements.push(self.literal_inner(depth + 1)?.0);
+                    while self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                        elements.push(self.literal_inner(depth + 1)?.0);
+                    }
+                }
+                let close = self.next("`]`")?;
+                if close.token != Token::RBracket {
+                    return Err(ParseErr::UnexpectedToken {
+                        expected: "`,` or `]`",
+                        span: close.span,
+                    });
+                }
+                Ok((ast::Literal::List(elements), next.span.to(close.span)))
+            }
+            _ => Err(ParseErr::UnexpectedToken {
+                expected: "a value",
+                span: next.span,
+            }),
+        }
+    }
+
+    /// ident: $name
+    fn ident(&mut self) -> Result<ast::Ident, ParseErr> {
+        let next = self.next("an identifier")?;
+        match next.token {
+            Token::Ident(name) => Ok(ast::Ident {
+                name,
+                span: next.span,
+            }),
+            _ => Err(ParseErr::UnexpectedToken {
+                expected: "an identifier",
+                span: next.span,
+            }),
+        }
+    }
+
+    fn expect_word(&mut self, word: &'static str) -> Result<(), ParseErr> {
+        let next = self.next(word)?;
+        match next.token {
+            Token::Word(w) if w == word => Ok(()),
+            _ => Err(ParseErr::UnexpectedToken {
+                expected: word,
+                span: next.span,
+            }),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w == word)
+    }
+
+    fn next(&mut self, expected: &'static str) -> Result<Spanned, ParseErr> {
+        let next = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or(ParseErr::UnexpectedEOI {
+                expected,
+                span: self.eoi,
+            })?;
+        self.pos += 1;
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ast::*;
+    use super::*;
+
+    fn span(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    #[test]
+    fn it_parses_a_header() {
+        let contract = parse("given parameters $a, $b").expect("it parses");
+        assert_eq!(
+            contract.parameters,
+            vec![
+                Ident {
+                    name: "a".into(),
+                    span: span(17, 19)
+                },
+                Ident {
+                    name: "b".into(),
+                    span: span(21, 23)
+                },
+            ]
+        );
+        assert!(contract.statements.is_empty());
+    }
+
+    #[test]
+    fn it_parses_a_definition() {
+        let contract = parse("given $a define $set as [1, \"x\"]").expect("it parses");
+        assert_eq!(
+            contract.statements,
+            vec![Statement::Definition(Definition {
+                ident: Ident {
+                    name: "set".into(),
+                    span: span(16, 20)
+                },
+                value: Value::Literal(
+                    Literal::List(vec![
+                        Literal::Integer(1.into()),
+                        Literal::String(b"x".to_vec())
+                    ]),
+                    span(24, 32)
+                ),
+            })]
+        );
+    }
+
+    #[test]
+    fn it_parses_comparators() {
+        let cases = vec![
+            ("$a must be 1", Comparator::Equal),
+            ("$a must be equal to 1", Comparator::Equal),
+            ("$a must be greater than 1", Comparator::GreaterThan),
+            (
+                "$a must be greater than or equal to 1",
+                Comparator::GreaterThanOrEqual,
+            ),
+            ("$a must be less than 1", Comparator::LessThan),
+            (
+                "$a must be less than or equal to 1",
+                Comparator::LessThanOrEqual,
+            ),
+            ("$a must be one of [1]", Comparator::OneOf),
+            ("$a must be in [1]", Comparator::OneOf),
+        ];
+        for (source, expected) in cases {
+            let contract = parse(&format!("given $a {}", source)).expect("it parses");
+            match &contract.statements[0] {
+                Statement::Clause(Clause {
+                    head: Term::Assertion(assertion),
+                    ..
+                }) => assert_eq!(assertion.comparator, expected),
+                _ => panic!("expected a clause"),
+            }
+        }
+    }
+
+    #[test]
+    fn it_parses_conjunctives() {
+        let contract =
+            parse("given $a, $b $a must not be less than 1 or $b must be 2 and $a must be 3")
+                .expect("it parses");
+        match &contract.statements[0] {
+            Statement::Clause(
+                clause @ Clause {
+                    head: Term::Assertion(head),
+                    ..
+                },
+            ) => {
+                assert_eq!(head.imperative, Imperative::MustNotBe);
+                assert_eq!(head.comparator, Comparator::LessThan);
+                let conjunctives: Vec<Conjunctive> = clause.tail.iter().map(|(c, _)| *c).collect();
+                assert_eq!(conjunctives, vec![Conjunctive::Or, Conjunctive::And]);
+            }
+            _ => panic!("expected a clause"),
+        }
+        assert_eq!(contract.statements.len(), 1);
+    }
+
+    #[test]
+    fn it_parses_groups() {
+        let contract = parse("given $a, $b ($a must be 1 or $b must be 2) and not ($a must be 3)")
+            .expect("it parses");
+        let clause = match &contract.statements[..] {
+            [Statement::Clause(clause)] => clause,
+            _ => panic!("expected a clause"),
+        };
+        match &clause.head {
+            Term::Group(group) => {
+                assert!(!group.negated);
+                assert_eq!(group.span, span(13, 43));
+                assert_eq!(group.clause.tail.len(), 1);
+            }
+            _ => panic!("expected a group"),
+        }
+        match &clause.tail[..] {
+            [(Conjunctive::And, Term::Group(group))] => {
+                assert!(group.negated);
+                assert_eq!(group.span, span(48, 66));
+                assert!(matches!(group.clause.head, Term::Assertion(_)));
+            }
+            _ => panic!("expected a group"),
+        }
+    }
+
+    #[test]
+    fn it_fails_on_unbalanced_groups() {
+        assert_eq!(
+            parse("given $a ($a must be 1"),
+            Err(vec![ParseErr::UnexpectedEOI {
+                expected: "`)`",
+                span: span(22, 22)
+            }])
+        );
+        assert_eq!(
+            parse("given $a ($a must be 1 $a must be 2)"),
+            Err(vec![ParseErr::UnexpectedToken {
+                expected: "a conjunctive or `)`",
+                span: span(23, 25)
+            }])
+        );
+        assert_eq!(
+            parse("given $a not $a must be 1"),
+            Err(vec![ParseErr::UnexpectedToken {
+                expected: "`(`",
+                span: span(13, 15)
+            }])
+        );
+    }
+
+    #[test]
+    fn it_fails_on_nesting_too_deep_to_parse() {
+        // The compiler reports nesting one level beyond what it supports
+        let group = |depth: usize| {
+            format!(
+                "given $a {}$a must be 1{}",
+                "(".repeat(depth),
+                ")".repeat(depth)
+            )
+        };
+        assert!(parse(&group(MAX_PARSE_GROUP_DEPTH)).is_ok());
+        let innermost = 9 + MAX_PARSE_GROUP_DEPTH;
+        assert_eq!(
+            parse(&group(MAX_PARSE_GROUP_DEPTH + 1)),
+            Err(vec![ParseErr::TooDeeplyNested(span(
+                innermost,
+                innermost + 1
+            ))])
+        );
+
+        let list = |depth: usize| {
+            format!(
+                "given $a $a must be one of {}1{}",
+                "[".repeat(depth),
+                "]".repeat(depth)
+            )
+        };
+        assert!(parse(&list(MAX_PARSE_LIST_DEPTH)).is_ok());
+        let innermost = 27 + MAX_PARSE_LIST_DEPTH;
+        assert_eq!(
+            parse(&list(MAX_PARSE_LIST_DEPTH + 1)),
+            Err(vec![ParseErr::TooDeeplyNested(span(
+                innermost,
+                innermost + 1
+            ))])
+        );
+
+        // Deep nesting fails rather than exhausting the stack
+        assert!(parse(&group(100_000)).is_err());
+        assert!(parse(&list(100_000)).is_err());
+    }
+
+    #[test]
+    fn it_separates_clauses() {
+        let contract = parse("given $a $a must be 1 $a must be 2").expect("it parses");
+        assert_eq!(contract.statements.len(), 2);
+    }
+
+    #[test]
+    fn it_fails_on_missing_header() {
+        assert_eq!(
+            parse("$a must be 1"),
+            Err(vec![ParseErr::UnexpectedToken {
+                expected: "given",
+                span: span(0, 2)
+            }])
+        );
+    }
+
+    #[test]
+    fn it_fails_on_incomplete_assertion() {
+        assert_eq!(
+            parse("given $a $a must be greater than"),
+            Err(vec![ParseErr::UnexpectedEOI {
+                expected: "a value",
+                span: span(32, 32)
+            }])
+        );
+        assert_eq!(
+            parse("given $a $a should be 1"),
+            Err(vec![ParseErr::UnexpectedToken {
+                expected: "must",
+                span: span(12, 18)
+            }])
+        );
+    }
+
+    #[test]
+    fn it_recovers_and_reports_every_error() {
+        let source = "given $a, $b
+            $a should be 1
+            $b must be 2
+            $a must be bigger than 3 and $b must be 4
+            define $c as [1, 2";
+        let (contract, errors) = parse_partial(source);
+        assert_eq!(
+            errors,
+            vec![
+                ParseErr::UnexpectedToken {
+                    expected: "must",
+                    span: span(28, 34)
+                },
+                ParseErr::UnexpectedToken {
+                    expected: COMPARATORS,
+                    span: span(88, 94)
+                },
+                ParseErr::UnexpectedEOI {
+                    expected: "`]`",
+                    span: span(149, 149)
+                },
+            ]
+        );
+        // `$b must be 2` is still parsed
+        assert_eq!(contract.parameters.len(), 2);
+        assert_eq!(contract.statements.len(), 1);
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright 2019 Centrality Investments Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Checksummed text armor for sharing contracts
+//!
+//! An armored contract is the prefix `pact1`, an armor version character, the contract's binary
+//! format as base32 and a 6 character checksum. The checksum and alphabet follow bech32m (BIP-350),
+//! so typos are detected before the payload is decoded.
+//!
+use crate::types::BinaryFormatErr;
+use alloc::{string::String, vec::Vec};
+
+/// The human-readable part of an armored contract
+const HRP: &str = "pact";
+/// Separates the human-readable part from the data
+const SEPARATOR: u8 = b'1';
+/// The armor layout version, the first data character
+const ARMOR_VERSION: u8 = 0;
+/// The bech32 base32 alphabet, ordered by value
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// The number of checksum characters
+const CHECKSUM_LEN: usize = 6;
+/// The bech32m checksum constant
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+/// An armored contract error
+#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+pub enum ArmorErr {
+    /// The armor does not begin with `pact1`
+    InvalidPrefix,
+    /// The armor mixes upper and lower case characters
+    MixedCase,
+    /// The character at this byte offset is not in the base32 alphabet
+    InvalidChar(usize),
+    /// The armor is too short to hold a version and checksum
+    TooShort,
+    /// The checksum does not match, the armor has been corrupted
+    InvalidChecksum,
+    /// The armor version is not supported
+    UnsupportedVersion(u8),
+    /// The payload ends with non-zero or excess padding bits
+    InvalidPadding,
+    /// The payload is not a valid contract
+    InvalidContract(BinaryFormatErr),
+}
+
+/// Armor a binary format `payload` as text
+pub(crate) fn encode(payload: &[u8]) -> String {
+    let mut values = Vec::with_capacity(1 + (payload.len() * 8).div_ceil(5) + CHECKSUM_LEN);
+    values.push(ARMOR_VERSION);
+    to_base32(payload, &mut values);
+    let checksum = polymod(HRP, values.iter().copied().chain([0; CHECKSUM_LEN])) ^ BECH32M_CONST;
+    values.extend(
+        (0..CHECKSUM_LEN)
+            .rev()
+            .map(|i| (checksum >> (5 * i)) as u8 & 0x1f),
+    );
+
+    let mut armor = String::with_capacity(HRP.len() + 1 + values.len());
+    armor.push_str(HRP);
+    armor.push(char::from(SEPARATOR));
+    armor.extend(values.iter().map(|v| char::from(CHARSET[usize::from(*v)])));
+    armor
+}
+
+/// Decode the binary format payload of an armored contract
+/// Upper and lower case armor are both accepted
+pub(crate) fn decode(armor: &str) -> Result<Vec<u8>, ArmorErr> {
+    let armor = armor.as_bytes();
+    if armor.iter().any(u8::is_ascii_lowercase) && armor.iter().any(u8::is_ascii_uppercase) {
+        return Err(ArmorErr::MixedCase);
+    }
+    let data_offset = HRP.len() + 1;
+    match armor.get(..data_offset) {
+        Some([hrp @ .., SEPARATOR]) if hrp.eq_ignore_ascii_case(HRP.as_bytes()) => (),
+        _ => return Err(ArmorErr::InvalidPrefix),
+    }
+
+    let values = armor[data_offset..]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            CHARSET
+                .iter()
+                .position(|v| *v == c.to_ascii_lowercase())
+                .map(|v| v as u8)
+                .ok_or(ArmorErr::InvalidChar(data_offset + i))
+        })
+        .collect::<Result<Vec<u8>, ArmorErr>>()?;
+    if values.len() < 1 + CHECKSUM_LEN {
+        return Err(ArmorErr::TooShort);
+    }
+    if polymod(HRP, values.iter().copied()) != BECH32M_CONST {
+        return Err(ArmorErr::InvalidChecksum);
+    }
+    if values[0] != ARMOR_VERSION {
+        return Err(ArmorErr::UnsupportedVersion(values[0]));
+    }
+    from_base32(&values[1..values.len() - CHECKSUM_LEN])
+}
+
+/// Append `bytes` to `values` as 5 bit values, zero padding the final value
+fn to_base32(bytes: &[u8], values: &mut Vec<u8>) {
+    let mut acc: u16 = 0;
+    let mut bits = 0;
+    for b in bytes {
+        acc = (acc << 8) | u16::from(*b);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            values.push((acc >> bits) as u8 & 0x1f);
+        }
+    }
+    if bits > 0 {
+        values.push((acc << (5 - bits)) as u8 & 0x1f);
+    }
+}
+
+/// Return the bytes of 5 bit `values`
+/// Padding must be zero and shorter than a value, so each payload has one armor
+fn from_base32(values: &[u8]) -> Result<Vec<u8>, ArmorErr> {
+    let mut bytes = Vec::with_capacity(values.len() * 5 / 8);
+    let mut acc: u16 = 0;
+    let mut bits = 0;
+    for v in values {
+        acc = (acc << 5) | u16::from(*v);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    if bits >= 5 || acc & ((1 << bits) - 1) != 0 {
+        return Err(ArmorErr::InvalidPadding);
+    }
+    Ok(bytes)
+}
+
+/// Return the bech32 checksum polynomial of `hrp` and the 5 bit `values`
+fn polymod(hrp: &str, values: impl Iterator<Item = u8>) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let hrp = hrp.bytes();
+    let expanded = hrp
+        .clone()
+        .map(|c| c >> 5)
+        .chain([0])
+        .chain(hrp.map(|c| c & 0x1f));
+    expanded.chain(values).fold(1, |chk, v| {
+        let top = chk >> 25;
+        let chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(chk, |chk, (_, g)| chk ^ g)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn it_computes_bech32m_checksums() {
+        // Valid bech32m strings from BIP-350
+        for (hrp, data) in [
+            ("a", "lqfn3a"),
+            ("abcdef", "l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx"),
+            (
+                "split",
+                "checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            ),
+        ] {
+            let values = data
+                .bytes()
+                .map(|c| CHARSET.iter().position(|v| *v == c).unwrap() as u8);
+            assert_eq!(polymod(hrp, values), BECH32M_CONST, "{}1{}", hrp, data);
+        }
+    }
+
+    #[test]
+    fn it_round_trips_payloads() {
+        for len in 0..16_u8 {
+            let payload: Vec<u8> = (0..len).map(|b| b.wrapping_mul(37)).collect();
+            let armor = encode(&payload);
+            assert!(armor.starts_with("pact1q"));
+            assert_eq!(armor.len(), 5 + 1 + (usize::from(len) * 8).div_ceil(5) + 6);
+            assert_eq!(decode(&armor), Ok(payload.clone()));
+            assert_eq!(decode(&armor.to_uppercase()), Ok(payload));
+        }
+    }
+
+    #[test]
+    fn it_detects_corruption() {
+        let armor = encode(&[0x80, 0x01, 0x00, 0x00]);
+        let bytes = armor.as_bytes();
+        for i in 5..bytes.len() {
+            // Every substitution
+            for c in CHARSET.iter().filter(|c| **c != bytes[i]) {
+                let mut corrupted = bytes.to_vec();
+                corrupted[i] = *c;
+                let corrupted = String::from_utf8(corrupted).unwrap();
+                assert_eq!(decode(&corrupted), Err(ArmorErr::InvalidChecksum));
+            }
+            // Every adjacent transposition
+            if i + 1 < bytes.len() && bytes[i] != bytes[i + 1] {
+                let mut corrupted = bytes.to_vec();
+                corrupted.swap(i, i + 1);
+                let corrupted = String::from_utf8(corrupted).unwrap();
+                assert_eq!(decode(&corrupted), Err(ArmorErr::InvalidChecksum));
+            }
+            // Every deletion
+            let corrupted = [&armor[..i], &armor[i + 1..]].concat();
+            assert!(decode(&corrupted).is_err());
+        }
+    }
+
+    #[test]
+    fn it_fails_with_malformed_armor() {
+        let armor = encode(&[1, 2, 3]);
+        let tests: Vec<(String, ArmorErr)> = vec![
+            ("".into(), ArmorErr::InvalidPrefix),
+            ("pact".into(), ArmorErr::InvalidPrefix),
+            (armor.replacen("pact", "pacts", 1), ArmorErr::InvalidPrefix),
+            (armor.replacen("pact", "Pact", 1), ArmorErr::MixedCase),
+            (
+                armor.replacen("pact1", "pact1b", 1),
+                ArmorErr::InvalidChar(5),
+            ),
+            (armor.replacen('q', "é", 1), ArmorErr::InvalidChar(5)),
+            ("pact1qqqqq".into(), ArmorErr::TooShort),
+            (
+                encode(&[]).replacen("pact1q", "pact1p", 1),
+                ArmorErr::InvalidChecksum,
+            ),
+        ];
+        for (armor, err) in tests {
+            assert_eq!(decode(&armor), Err(err), "{}", armor);
+        }
+    }
+
+    /// Return armor for raw 5 bit values with a valid checksum
+    fn checksummed(values: &[u8]) -> String {
+        let checksum =
+            polymod(HRP, values.iter().copied().chain([0; CHECKSUM_LEN])) ^ BECH32M_CONST;
+        let values = values.iter().copied().chain(
+            (0..CHECKSUM_LEN)
+                .rev()
+                .map(|i| (checksum >> (5 * i)) as u8 & 0x1f),
+        );
+        let mut armor = String::from("pact1");
+        armor.extend(values.map(|v| char::from(CHARSET[usize::from(v)])));
+        armor
+    }
+
+    #[test]
+    fn it_fails_with_unsupported_versions_and_padding() {
+        assert_eq!(decode(&checksummed(&[0])), Ok(vec![]));
+        assert_eq!(
+            decode(&checksummed(&[1, 0, 0])),
+            Err(ArmorErr::UnsupportedVersion(1))
+        );
+        // 10 bits hold one byte and 2 zero padding bits
+        assert_eq!(decode(&checksummed(&[0, 0x1f, 0x1c])), Ok(vec![0xff]));
+        // Non-zero padding bits
+        assert_eq!(
+            decode(&checksummed(&[0, 0x1f, 0x1d])),
+            Err(ArmorErr::InvalidPadding)
+        );
+        // A whole value of padding
+        assert_eq!(
+            decode(&checksummed(&[0, 0x1f, 0x1c, 0])),
+            Err(ArmorErr::InvalidPadding)
+        );
+    }
+}
@@ -16,129 +16,276 @@
 //!
 //! Types in the pact interpreter aka "PactType"s
 //!
+use crate::types::{BinaryFormat, DecodeErr, EncodeErr, PactTypeRef};
 use alloc::vec::Vec;
 use bit_reverse::ParallelReverse;
+use core::convert::TryFrom;
+use primitive_types::U256;
+
+/// The maximum nesting depth of `PactType::List`s, a flat list has depth 1
+pub const MAX_LIST_DEPTH: usize = 4;
 
 /// A string-like type
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(
+    feature = "scale",
+    derive(codec::Encode, codec::Decode, scale_info::TypeInfo)
+)]
 #[derive(PartialEq, PartialOrd, Clone)]
 pub struct StringLike(pub Vec<u8>);
 
-/// A numeric type
+/// A numeric type, an unsigned integer of up to 256-bits
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(PartialEq, PartialOrd, Clone)]
-pub struct Numeric(pub u64);
+pub struct Numeric(pub(crate) U256);
+
+impl Numeric {
+    /// Create a numeric holding `value`
+    pub fn new(value: U256) -> Self {
+        Numeric(value)
+    }
+
+    /// Return the numeric's value
+    pub fn as_u256(&self) -> U256 {
+        self.0
+    }
+
+    /// Return the length of the numeric's encoding, the smallest of 8, 16 or 32 bytes
+    /// which holds its value
+    pub fn encoded_len(&self) -> usize {
+        match self.0.bits() {
+            0..=64 => 8,
+            65..=128 => 16,
+            _ => 32,
+        }
+    }
+}
+
+impl From<u64> for Numeric {
+    fn from(value: u64) -> Self {
+        Numeric(value.into())
+    }
+}
+
+/// A signed numeric type, a two's complement integer of up to 128-bits
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
+#[derive(PartialEq, PartialOrd, Clone)]
+pub struct Signed(pub i128);
+
+impl Signed {
+    /// Return the length of the signed numeric's encoding, the smallest of 8 or 16 bytes
+    /// which holds its value
+    pub fn encoded_len(&self) -> usize {
+        if i64::try_from(self.0).is_ok() {
+            8
+        } else {
+            16
+        }
+    }
+}
+
+/// Explicit conversion from an unsigned numeric, fails if the value exceeds `i128::MAX`
+impl TryFrom<Numeric> for Signed {
+    type Error = ();
+    fn try_from(n: Numeric) -> Result<Self, Self::Error> {
+        let value = u128::try_from(n.0).map_err(|_| ())?;
+        i128::try_from(value).map(Signed).map_err(|_| ())
+    }
+}
+
+/// Explicit conversion into an unsigned numeric, fails if the value is negative
+impl TryFrom<Signed> for Numeric {
+    type Error = ();
+    fn try_from(s: Signed) -> Result<Self, Self::Error> {
+        let value = u128::try_from(s.0).map_err(|_| ())?;
+        Ok(Numeric(value.into()))
+    }
+}
 
 /// Over-arching pact type system
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "scale", derive(codec::Encode, scale_info::TypeInfo))]
 #[derive(Clone, PartialEq)]
 pub enum PactType {
     StringLike(StringLike),
     Numeric(Numeric),
     List(Vec<PactType>),
+    Signed(Signed),
+    Bool(bool),
 }
 
 impl PactType {
-    /// Encode the PactType into `buf`c
+    /// Encode the PactType into `buf` using the v0 binary format
+    /// Lengths which do not fit the format are truncated, `try_encode` rejects them instead
     pub fn encode(&self, buf: &mut Vec<u8>) {
+        self.encode_with(BinaryFormat::V0, buf)
+    }
+
+    /// Encode the PactType into `buf` using the given binary `format`
+    pub fn encode_with(&self, format: BinaryFormat, buf: &mut Vec<u8>) {
         match self {
             PactType::StringLike(s) => {
                 buf.push(0);
-                buf.push((s.0.len() as u8).swap_bits());
+                format.encode_length(s.0.len(), buf);
                 buf.extend(s.0.iter());
             }
             PactType::Numeric(n) => {
                 buf.push(1.swap_bits());
-                // only supporting 64-bit numeric here.
-                buf.push(8.swap_bits());
-                for b in n.0.to_le_bytes().iter() {
+                let length = match format {
+                    BinaryFormat::V0 => n.encoded_len(),
+                    BinaryFormat::V1 => n.0.bits().div_ceil(8),
+                };
+                format.encode_length(length, buf);
+                let mut bytes = [0_u8; 32];
+                n.0.to_little_endian(&mut bytes);
+                for b in bytes[..length].iter() {
+                    buf.push(b.swap_bits())
+                }
+            }
+            PactType::Signed(n) => {
+                buf.push(3.swap_bits());
+                let bytes = n.0.to_le_bytes();
+                let length = match format {
+                    BinaryFormat::V0 => n.encoded_len(),
+                    BinaryFormat::V1 => minimal_signed_len(&bytes),
+                };
+                format.encode_length(length, buf);
+                for b in bytes[..length].iter() {
                     buf.push(b.swap_bits())
                 }
             }
+            PactType::Bool(b) => {
+                buf.push(4.swap_bits());
+                format.encode_length(1, buf);
+                buf.push(u8::from(*b).swap_bits());
+            }
             PactType::List(l) => {
                 let mut buf_elements: Vec<u8> = Vec::<u8>::default();
                 for element in l {
-                    match element {
-                        PactType::StringLike(_) => element.encode(&mut buf_elements),
-                        PactType::Numeric(_) => element.encode(&mut buf_elements),
-                        _ => {} // element not supported
-                    }
+                    element.encode_with(format, &mut buf_elements);
                 }
 
                 buf.push(2.swap_bits());
-                buf.push((buf_elements.len() as u8).swap_bits());
+                format.encode_length(buf_elements.len(), buf);
                 buf.append(&mut buf_elements);
-
-                //panic!("todo");
             }
         };
     }
-    /// Decode a pact type from the given buffer
-    /// Returns (decoded type, bytes read) or error on failure
-    pub fn decode(buf: Vec<u8>) -> Result<(Self, usize), &'static str> {
-        // Check type header bytes
-        match buf.len() {
-            0 => return Err("missing type ID byte"),
-            1 => return Err("missing type length byte"),
-            _ => (),
-        };
 
-        // 1 byte type ID + 1 byte length gives 2 offset
-        let mut read_offset = 2_usize;
+    /// Encode the PactType into `buf` using the v0 binary format
+    /// Fails without writing to `buf` if the value can not be represented
+    pub fn try_encode(&self, buf: &mut Vec<u8>) -> Result<(), EncodeErr> {
+        self.try_encode_with(BinaryFormat::V0, buf)
+    }
 
-        // Read length byte
-        let data_length = buf[1].swap_bits() as usize;
-        if data_length > buf[read_offset..].len() {
-            return Err("type length > buffer length");
-        }
+    /// Encode the PactType into `buf` using the given binary `format`
+    /// Fails without writing to `buf` if the value can not be represented
+    pub fn try_encode_with(
+        &self,
+        format: BinaryFormat,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), EncodeErr> {
+        self.check_encoding(format, 0)?;
+        self.encode_with(format, buf);
+        Ok(())
+    }
 
-        // Read type ID byte
-        match buf[0].swap_bits() {
-            0 => {
-                let read_length = read_offset + data_length;
-                let s = PactType::StringLike(StringLike(buf[read_offset..read_length].to_vec()));
-                Ok((s, read_length))
+    /// Check the type enclosed by `depth` lists is representable in `format`
+    /// Returns the length of its encoding
+    fn check_encoding(&self, format: BinaryFormat, depth: usize) -> Result<usize, EncodeErr> {
+        let data_length = match self {
+            PactType::StringLike(s) if s.0.len() > format.max_length() => {
+                return Err(EncodeErr::StringTooLong(s.0.len()))
             }
-            1 => {
-                let data_length = buf[1].swap_bits() as usize;
-                if data_length != 8 {
-                    return Err("implementation only supports 64-bit numerics");
+            PactType::StringLike(s) => s.0.len(),
+            PactType::List(_) if depth == MAX_LIST_DEPTH => return Err(EncodeErr::TooDeeplyNested),
+            PactType::List(l) => {
+                let mut length = 0;
+                for element in l {
+                    length += element.check_encoding(format, depth + 1)?;
                 }
-
-                let n = PactType::Numeric(Numeric(u64::from_le_bytes([
-                    buf[2].swap_bits(),
-                    buf[3].swap_bits(),
-                    buf[4].swap_bits(),
-                    buf[5].swap_bits(),
-                    buf[6].swap_bits(),
-                    buf[7].swap_bits(),
-                    buf[8].swap_bits(),
-                    buf[9].swap_bits(),
-                ])));
-                Ok((n, 10usize))
-            }
-            2 => {
-                let mut values: Vec<PactType> = Vec::<PactType>::default();
-                let mut remaining_length = data_length;
-
-                while remaining_length > 0 {
-                    let (new_value, offset) = Self::decode(buf[read_offset..].to_vec())?;
-                    read_offset = read_offset + offset;
-                    remaining_length = remaining_length
-                        .checked_sub(offset)
-                        .ok_or("list length overflow")?;
-                    values.push(new_value);
+                if length > format.max_length() {
+                    return Err(EncodeErr::ListTooLong(length));
                 }
-                Ok((PactType::List(values), read_offset))
+                length
             }
-            _ => Err("unsupported type ID"),
+            // Scalars are at most 32 bytes
+            PactType::Numeric(n) => match format {
+                BinaryFormat::V0 => n.encoded_len(),
+                BinaryFormat::V1 => n.0.bits().div_ceil(8),
+            },
+            PactType::Signed(n) => match format {
+                BinaryFormat::V0 => n.encoded_len(),
+                BinaryFormat::V1 => minimal_signed_len(&n.0.to_le_bytes()),
+            },
+            PactType::Bool(_) => 1,
+        };
+        Ok(1 + format.length_len(data_length) + data_length)
+    }
+
+    /// Return the nesting depth of the type, 0 for scalars and 1 for a flat list
+    pub fn list_depth(&self) -> usize {
+        match self {
+            PactType::List(l) => 1 + l.iter().map(Self::list_depth).max().unwrap_or(0),
+            _ => 0,
         }
     }
+
+    /// Decode a pact type from the given v0 binary format buffer
+    /// Returns (decoded type, bytes read) or error on failure
+    pub fn decode(buf: &[u8]) -> Result<(Self, usize), DecodeErr> {
+        Self::decode_with(buf, BinaryFormat::V0)
+    }
+
+    /// Decode a pact type from the given buffer in binary `format`
+    /// Returns (decoded type, bytes read) or error on failure
+    pub fn decode_with(buf: &[u8], format: BinaryFormat) -> Result<(Self, usize), DecodeErr> {
+        PactTypeRef::decode_with(buf, format).map(|(value, read)| (value.into_owned(), read))
+    }
+}
+
+/// Return the fewest little endian two's complement `bytes` which sign extend to the same value
+pub(crate) fn minimal_signed_len(bytes: &[u8]) -> usize {
+    let mut length = bytes.len();
+    while length > 0 {
+        // The sign of the value without its most significant byte
+        let negative = length > 1 && bytes[length - 2] & 0x80 != 0;
+        let redundant = match bytes[length - 1] {
+            0x00 => !negative,
+            0xff => length > 1 && negative,
+            _ => false,
+        };
+        if !redundant {
+            break;
+        }
+        length -= 1;
+    }
+    length
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::DecodeErrKind;
 
     #[test]
     fn it_encodes_string_like() {
@@ -152,7 +299,7 @@ mod tests {
 
     #[test]
     fn it_encodes_numeric() {
-        let n = PactType::Numeric(Numeric(123));
+        let n = PactType::Numeric(Numeric(123.into()));
         let buf: &mut Vec<u8> = &mut Vec::new();
         n.encode(buf);
 
@@ -187,8 +334,8 @@ mod tests {
     #[test]
     fn it_encodes_numeric_list() {
         let l = PactType::List(vec![
-            PactType::Numeric(Numeric(0x0123456789abcdef)),
-            PactType::Numeric(Numeric(0xfedcba9876543210)),
+            PactType::Numeric(Numeric(0x0123456789abcdef_u64.into())),
+            PactType::Numeric(Numeric(0xfedcba9876543210_u64.into())),
         ]);
         let buf: &mut Vec<u8> = &mut Vec::new();
         l.encode(buf);
@@ -206,7 +353,7 @@ mod tests {
         let mut buf = vec![0, 11];
         buf = buf.into_iter().map(|b| b.swap_bits()).collect(); // convert to LE bit orders
         buf.extend("hello world".as_bytes());
-        let (string_type, bytes_read) = PactType::decode(buf).expect("it decodes");
+        let (string_type, bytes_read) = PactType::decode(&buf).expect("it decodes");
 
         assert_eq!(
             string_type,
@@ -220,9 +367,9 @@ mod tests {
     fn it_decodes_numeric() {
         let mut encoded: Vec<u8> = vec![1, 8, 123, 0, 0, 0, 0, 0, 0, 0];
         encoded = encoded.into_iter().map(|b| b.swap_bits()).collect(); // convert to LE bit orders
-        let (numeric_type, bytes_read) = PactType::decode(encoded).expect("it decodes");
+        let (numeric_type, bytes_read) = PactType::decode(&encoded).expect("it decodes");
 
-        assert_eq!(numeric_type, PactType::Numeric(Numeric(123)));
+        assert_eq!(numeric_type, PactType::Numeric(Numeric(123.into())));
         assert_eq!(10usize, bytes_read,);
     }
 
@@ -247,7 +394,7 @@ mod tests {
         ]
         .concat();
 
-        let (list_type, bytes_read) = PactType::decode(buf).expect("it decodes");
+        let (list_type, bytes_read) = PactType::decode(&buf).expect("it decodes");
 
         let expected = PactType::List(vec![
             PactType::StringLike(StringLike(b"you know".to_vec())),
@@ -278,11 +425,11 @@ mod tests {
         .map(|b| b.swap_bits())
         .collect();
 
-        let (list_type, bytes_read) = PactType::decode(buf).expect("it decodes");
+        let (list_type, bytes_read) = PactType::decode(&buf).expect("it decodes");
 
         let expected = PactType::List(vec![
-            PactType::Numeric(Numeric(0xefcd_ab89_6745_2301)),
-            PactType::Numeric(Numeric(0xedfe_edfe_edfe_edfe)),
+            PactType::Numeric(Numeric(0xefcd_ab89_6745_2301_u64.into())),
+            PactType::Numeric(Numeric(0xedfe_edfe_edfe_edfe_u64.into())),
         ]);
 
         assert_eq!(list_type, expected,);
@@ -303,7 +450,10 @@ mod tests {
         .map(|b| b.swap_bits())
         .collect();
 
-        assert_eq!(PactType::decode(buf), Err("type length > buffer length"));
+        assert_eq!(
+            PactType::decode(&buf),
+            Err(DecodeErr::new(1, DecodeErrKind::Truncated))
+        );
 
         let list_header: Vec<u8> = vec![2, 5];
         let buf: Vec<u8> = [
@@ -316,54 +466,384 @@ mod tests {
         .map(|b| b.swap_bits())
         .collect();
 
-        assert_eq!(PactType::decode(buf), Err("list length overflow"));
+        assert_eq!(
+            PactType::decode(&buf),
+            Err(DecodeErr::new(2, DecodeErrKind::ListLengthOverflow))
+        );
     }
 
     #[test]
     fn it_fails_with_missing_type_id() {
-        assert_eq!(PactType::decode(vec![]), Err("missing type ID byte"));
+        assert_eq!(
+            PactType::decode(&[]),
+            Err(DecodeErr::new(0, DecodeErrKind::MissingTypeId))
+        );
     }
 
     #[test]
     fn it_fails_with_missing_type_length() {
         assert_eq!(
-            PactType::decode([0].to_vec()),
-            Err("missing type length byte")
+            PactType::decode(&[0]),
+            Err(DecodeErr::new(1, DecodeErrKind::MissingLength))
         );
     }
 
     #[test]
-    #[should_panic(expected = "type length > buffer length")]
+    #[should_panic(expected = "Truncated")]
     fn it_fails_with_short_string_like() {
-        PactType::decode([0, 11].to_vec()).unwrap();
+        PactType::decode(&[0, 11]).unwrap();
+    }
+
+    #[test]
+    fn it_encodes_wide_numerics() {
+        let tests = vec![
+            (U256::from(u64::MAX), 8),
+            (U256::from(u64::MAX) + 1, 16),
+            (U256::from(u128::MAX), 16),
+            (U256::from(u128::MAX) + 1, 32),
+            (U256::MAX, 32),
+        ];
+        for (value, length) in tests {
+            let buf: &mut Vec<u8> = &mut Vec::new();
+            let n = PactType::Numeric(Numeric(value));
+            n.encode(buf);
+            assert_eq!(buf[1].swap_bits() as usize, length);
+            assert_eq!(buf.len(), 2 + length);
+            assert_eq!(PactType::decode(buf), Ok((n, 2 + length)));
+        }
+    }
+
+    #[test]
+    fn it_decodes_u128_numeric() {
+        let mut encoded: Vec<u8> = vec![1, 16];
+        encoded.extend(u128::MAX.to_le_bytes());
+        encoded = encoded.into_iter().map(|b| b.swap_bits()).collect(); // convert to LE bit orders
+        assert_eq!(
+            PactType::decode(&encoded),
+            Ok((PactType::Numeric(Numeric(u128::MAX.into())), 18))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidNumericWidth")]
+    fn it_fails_with_unsupported_numeric_width() {
+        PactType::decode(&[1.swap_bits(), 4.swap_bits(), 0, 0, 0, 0]).unwrap();
+    }
+
+    #[test]
+    fn it_encodes_signed() {
+        let tests = vec![
+            (0_i128, vec![0, 0, 0, 0, 0, 0, 0, 0]),
+            (-1, vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            (-2, vec![0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            (
+                i64::MIN.into(),
+                vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80],
+            ),
+            (
+                i128::from(i64::MIN) - 1,
+                vec![
+                    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff,
+                    0xff, 0xff, 0xff,
+                ],
+            ),
+        ];
+        for (value, data) in tests {
+            let buf: &mut Vec<u8> = &mut Vec::new();
+            let n = PactType::Signed(Signed(value));
+            n.encode(buf);
+
+            let mut expected: Vec<u8> = vec![3, data.len() as u8];
+            expected.extend(data);
+            expected = expected.into_iter().map(|b| b.swap_bits()).collect(); // convert to LE bit orders
+            assert_eq!(buf, &expected);
+            assert_eq!(PactType::decode(buf), Ok((n, buf.len())));
+        }
+    }
+
+    #[test]
+    fn it_round_trips_signed_extremes() {
+        for value in [
+            i128::MIN,
+            i128::MAX,
+            i64::MAX.into(),
+            i128::from(i64::MAX) + 1,
+        ] {
+            let buf: &mut Vec<u8> = &mut Vec::new();
+            let n = PactType::Signed(Signed(value));
+            n.encode(buf);
+            assert_eq!(PactType::decode(buf), Ok((n, buf.len())));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "InvalidSignedWidth")]
+    fn it_fails_with_unsupported_signed_width() {
+        let buf: Vec<u8> = [3.swap_bits(), 32.swap_bits()]
+            .iter()
+            .chain(&[0; 32])
+            .copied()
+            .collect();
+        PactType::decode(&buf).unwrap();
+    }
+
+    #[test]
+    fn it_converts_between_signed_and_numeric() {
+        assert_eq!(Signed::try_from(Numeric(5.into())), Ok(Signed(5)));
+        assert_eq!(
+            Signed::try_from(Numeric(U256::from(i128::MAX as u128) + 1)),
+            Err(())
+        );
+        assert_eq!(Numeric::try_from(Signed(5)), Ok(Numeric(5.into())));
+        assert_eq!(Numeric::try_from(Signed(-5)), Err(()));
+    }
+
+    #[test]
+    fn it_encodes_bool() {
+        for (value, data) in [(false, 0_u8), (true, 1_u8)] {
+            let buf: &mut Vec<u8> = &mut Vec::new();
+            PactType::Bool(value).encode(buf);
+            let expected: Vec<u8> = vec![4, 1, data]
+                .into_iter()
+                .map(|b| b.swap_bits())
+                .collect();
+            assert_eq!(buf, &expected);
+            assert_eq!(PactType::decode(buf), Ok((PactType::Bool(value), 3)));
+        }
+    }
+
+    #[test]
+    fn it_fails_with_malformed_bool() {
+        for encoded in [vec![4, 1, 2], vec![4, 2, 1, 0], vec![4, 0]] {
+            let buf: Vec<u8> = encoded.into_iter().map(|b| b.swap_bits()).collect();
+            assert_eq!(
+                PactType::decode(&buf),
+                Err(DecodeErr::new(1, DecodeErrKind::InvalidBool))
+            );
+        }
+    }
+
+    #[test]
+    fn it_round_trips_nested_lists() {
+        let l = PactType::List(vec![
+            PactType::Numeric(Numeric(1.into())),
+            PactType::List(vec![
+                PactType::StringLike(StringLike(b"a".to_vec())),
+                PactType::List(vec![]),
+            ]),
+            PactType::List(vec![PactType::Bool(true)]),
+        ]);
+        let buf: &mut Vec<u8> = &mut Vec::new();
+        l.encode(buf);
+
+        let expected: Vec<u8> = vec![
+            2,
+            22, // [
+            1,
+            8,
+            1,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0, // 1
+            2,
+            5,
+            0,
+            1,
+            b'a'.swap_bits(),
+            2,
+            0, // ["a", []]
+            2,
+            3,
+            4,
+            1,
+            1, // [true]
+        ]
+        .into_iter()
+        .map(|b| b.swap_bits())
+        .collect();
+        assert_eq!(buf, &expected);
+        assert_eq!(l.list_depth(), 3);
+        assert_eq!(PactType::decode(buf), Ok((l, buf.len())));
+    }
+
+    #[test]
+    fn it_fails_decode_with_deeply_nested_lists() {
+        let nested = |depth: usize| {
+            (0..depth).fold(PactType::Bool(true), |inner, _| PactType::List(vec![inner]))
+        };
+
+        let buf: &mut Vec<u8> = &mut Vec::new();
+        let l = nested(MAX_LIST_DEPTH);
+        l.encode(buf);
+        assert_eq!(PactType::decode(buf), Ok((l, buf.len())));
+
+        let buf: &mut Vec<u8> = &mut Vec::new();
+        nested(MAX_LIST_DEPTH + 1).encode(buf);
+        assert_eq!(
+            PactType::decode(buf),
+            Err(DecodeErr::new(2 * MAX_LIST_DEPTH, DecodeErrKind::TooDeep))
+        );
+    }
+
+    #[test]
+    fn it_encodes_v1_numerics_at_minimal_width() {
+        let tests = vec![
+            (U256::zero(), 0),
+            (U256::from(1), 1),
+            (U256::from(0x1_00), 2),
+            (U256::from(u64::MAX), 8),
+            (U256::from(u64::MAX) + 1, 9),
+            (U256::MAX, 32),
+        ];
+        for (value, length) in tests {
+            let buf: &mut Vec<u8> = &mut Vec::new();
+            let n = PactType::Numeric(Numeric(value));
+            n.encode_with(BinaryFormat::V1, buf);
+            assert_eq!(buf[1].swap_bits() as usize, length);
+            assert_eq!(buf.len(), 2 + length);
+            assert_eq!(
+                PactType::decode_with(buf, BinaryFormat::V1),
+                Ok((n, 2 + length))
+            );
+        }
+    }
+
+    #[test]
+    fn it_encodes_v1_signed_at_minimal_width() {
+        let tests = vec![
+            (0_i128, vec![]),
+            (-1, vec![0xff]),
+            (127, vec![0x7f]),
+            (128, vec![0x80, 0x00]),
+            (-128, vec![0x80]),
+            (-129, vec![0x7f, 0xff]),
+            (i128::MIN, i128::MIN.to_le_bytes().to_vec()),
+        ];
+        for (value, data) in tests {
+            let buf: &mut Vec<u8> = &mut Vec::new();
+            let n = PactType::Signed(Signed(value));
+            n.encode_with(BinaryFormat::V1, buf);
+
+            let mut expected: Vec<u8> = vec![3, data.len() as u8];
+            expected.extend(data);
+            expected = expected.into_iter().map(|b| b.swap_bits()).collect(); // convert to LE bit orders
+            assert_eq!(buf, &expected);
+            assert_eq!(
+                PactType::decode_with(buf, BinaryFormat::V1),
+                Ok((n, buf.len()))
+            );
+        }
+    }
+
+    #[test]
+    fn it_round_trips_v1_long_strings_and_lists() {
+        let account_ids: Vec<PactType> = (0..8_u8)
+            .map(|n| PactType::StringLike(StringLike(vec![n; 32])))
+            .collect();
+        let long = PactType::StringLike(StringLike(vec![b'x'; 300]));
+        for value in [PactType::List(account_ids), long] {
+            let buf: &mut Vec<u8> = &mut Vec::new();
+            value.encode_with(BinaryFormat::V1, buf);
+            assert_eq!(
+                PactType::decode_with(buf, BinaryFormat::V1),
+                Ok((value, buf.len()))
+            );
+        }
+    }
+
+    #[test]
+    fn it_fails_v1_decode_with_non_minimal_numerics() {
+        let tests: Vec<(Vec<u8>, DecodeErr)> = vec![
+            (
+                vec![1, 2, 1, 0],
+                DecodeErr::new(2, DecodeErrKind::NonMinimalNumeric),
+            ),
+            (
+                vec![1, 33],
+                DecodeErr::new(1, DecodeErrKind::InvalidNumericWidth),
+            ),
+            (
+                vec![3, 2, 1, 0],
+                DecodeErr::new(2, DecodeErrKind::NonMinimalSigned),
+            ),
+            (
+                vec![3, 2, 0x80, 0xff],
+                DecodeErr::new(2, DecodeErrKind::NonMinimalSigned),
+            ),
+            (
+                vec![3, 17],
+                DecodeErr::new(1, DecodeErrKind::InvalidSignedWidth),
+            ),
+        ];
+        for (encoded, err) in tests {
+            let mut buf: Vec<u8> = encoded.into_iter().map(|b| b.swap_bits()).collect();
+            buf.resize(buf.len() + 33, 0);
+            assert_eq!(PactType::decode_with(&buf, BinaryFormat::V1), Err(err));
+        }
     }
 
     #[test]
-    #[should_panic(expected = "implementation only supports 64-bit numerics")]
-    fn it_fails_with_u128_numeric() {
-        PactType::decode(
-            [
-                1.swap_bits(),
-                16.swap_bits(),
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-            ]
-            .to_vec(),
-        )
-        .unwrap();
+    fn it_fails_to_encode_unrepresentable_values() {
+        let long_string = PactType::StringLike(StringLike(vec![0; 256]));
+        let long_list = PactType::List(vec![PactType::Numeric(Numeric(1.into())); 26]);
+        let deep_list =
+            (0..=MAX_LIST_DEPTH).fold(PactType::Bool(true), |inner, _| PactType::List(vec![inner]));
+        let tests = vec![
+            (&long_string, EncodeErr::StringTooLong(256)),
+            (&long_list, EncodeErr::ListTooLong(260)),
+            (&deep_list, EncodeErr::TooDeeplyNested),
+        ];
+        for (value, err) in tests {
+            let mut buf = vec![0xff];
+            assert_eq!(value.try_encode(&mut buf), Err(err));
+            assert_eq!(buf, vec![0xff]);
+        }
+
+        // Long values are representable in v1
+        for value in [long_string, long_list] {
+            let mut buf = Vec::new();
+            assert_eq!(value.try_encode_with(BinaryFormat::V1, &mut buf), Ok(()));
+            assert_eq!(
+                PactType::decode_with(&buf, BinaryFormat::V1),
+                Ok((value, buf.len()))
+            );
+        }
+        assert_eq!(
+            deep_list.try_encode_with(BinaryFormat::V1, &mut Vec::new()),
+            Err(EncodeErr::TooDeeplyNested)
+        );
+    }
+
+    #[test]
+    fn it_encodes_unrepresentable_values_without_panicking() {
+        let long_string = PactType::StringLike(StringLike(vec![0; 256]));
+        let deep_list =
+            (0..=MAX_LIST_DEPTH).fold(PactType::Bool(true), |inner, _| PactType::List(vec![inner]));
+        for value in [long_string, deep_list] {
+            let mut buf = Vec::new();
+            value.encode(&mut buf);
+            assert_ne!(PactType::decode(&buf), Ok((value, buf.len())));
+        }
+    }
+
+    #[test]
+    fn it_checks_the_encoded_length() {
+        let nested = PactType::List(vec![
+            PactType::StringLike(StringLike(vec![1; 200])),
+            PactType::List(vec![
+                PactType::Signed(Signed(-1)),
+                PactType::Numeric(Numeric(U256::MAX)),
+            ]),
+            PactType::Bool(false),
+        ]);
+        for format in [BinaryFormat::V0, BinaryFormat::V1] {
+            let mut buf = Vec::new();
+            nested.encode_with(format, &mut buf);
+            assert_eq!(nested.check_encoding(format, 0), Ok(buf.len()));
+        }
     }
 }
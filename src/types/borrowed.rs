@@ -0,0 +1,578 @@
+// Copyright 2019 Centrality Investments Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Borrowed views of pact types which decode without allocating
+//!
+use crate::interpreter::{glob, Comparator, OpCode, OpComp};
+use crate::types::base::minimal_signed_len;
+use crate::types::metadata::METADATA_FLAG;
+use crate::types::{
+    AnnotatedContract, BinaryFormat, BinaryFormatErr, Contract, DataTable, DecodeErr,
+    DecodeErrKind, DecodeLimits, MetadataRef, Numeric, PactType, Signed, StringLike,
+    MAX_LIST_DEPTH,
+};
+use alloc::vec::Vec;
+use bit_reverse::ParallelReverse;
+use primitive_types::U256;
+
+/// Limits for decoding data which has already been validated, only bounded by the type system
+const VALIDATED: DecodeLimits = DecodeLimits {
+    max_depth: MAX_LIST_DEPTH,
+    max_data_table_entries: usize::MAX,
+    max_list_len: usize::MAX,
+    max_bytes: usize::MAX,
+    max_bytecode_len: usize::MAX,
+};
+
+/// A borrowed `PactType`, string data is borrowed from the decoded buffer
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone)]
+pub enum PactTypeRef<'a> {
+    StringLike(&'a [u8]),
+    Numeric(Numeric),
+    List(ListRef<'a>),
+    Signed(Signed),
+    Bool(bool),
+}
+
+/// A borrowed `PactType::List`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy)]
+pub enum ListRef<'a> {
+    /// Elements of an owned list
+    Decoded(&'a [PactType]),
+    /// Validated, encoded elements which are decoded as they are iterated
+    Encoded { buf: &'a [u8], format: BinaryFormat },
+}
+
+impl<'a> ListRef<'a> {
+    /// Return an iterator over the list elements
+    pub fn iter(&self) -> ListIter<'a> {
+        match *self {
+            ListRef::Decoded(elements) => ListIter::Decoded(elements.iter()),
+            ListRef::Encoded { buf, format } => ListIter::Encoded { buf, format },
+        }
+    }
+}
+
+/// An iterator over the elements of a `ListRef`
+pub enum ListIter<'a> {
+    Decoded(core::slice::Iter<'a, PactType>),
+    Encoded { buf: &'a [u8], format: BinaryFormat },
+}
+
+impl<'a> Iterator for ListIter<'a> {
+    type Item = PactTypeRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ListIter::Decoded(elements) => elements.next().map(Into::into),
+            ListIter::Encoded { buf, format } => {
+                // Encoded lists are validated when decoded so this can not fail
+                let (element, read) =
+                    PactTypeRef::decode_with_limits(buf, *format, &VALIDATED).ok()?;
+                *buf = &buf[read..];
+                Some(element)
+            }
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match self {
+            ListIter::Decoded(elements) => elements.nth(n).map(Into::into),
+            ListIter::Encoded { buf, format } => {
+                // Skip elements by their type byte and length prefix, they were validated when decoded
+                for _ in 0..n {
+                    let (data_length, length_bytes) = format.decode_length(buf.get(1..)?).ok()?;
+                    *buf = buf.get(1 + length_bytes + data_length..)?;
+                }
+                self.next()
+            }
+        }
+    }
+}
+
+impl PartialEq for PactTypeRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PactTypeRef::StringLike(l), PactTypeRef::StringLike(r)) => l == r,
+            (PactTypeRef::Numeric(l), PactTypeRef::Numeric(r)) => l == r,
+            (PactTypeRef::List(l), PactTypeRef::List(r)) => l.iter().eq(r.iter()),
+            (PactTypeRef::Signed(l), PactTypeRef::Signed(r)) => l == r,
+            (PactTypeRef::Bool(l), PactTypeRef::Bool(r)) => l == r,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> From<&'a PactType> for PactTypeRef<'a> {
+    fn from(value: &'a PactType) -> Self {
+        match value {
+            PactType::StringLike(s) => PactTypeRef::StringLike(&s.0),
+            PactType::Numeric(n) => PactTypeRef::Numeric(n.clone()),
+            PactType::List(l) => PactTypeRef::List(ListRef::Decoded(l)),
+            PactType::Signed(n) => PactTypeRef::Signed(n.clone()),
+            PactType::Bool(b) => PactTypeRef::Bool(*b),
+        }
+    }
+}
+
+impl<'a> PactTypeRef<'a> {
+    /// Copy the borrowed data into an owned `PactType`
+    pub fn into_owned(self) -> PactType {
+        match self {
+            PactTypeRef::StringLike(s) => PactType::StringLike(StringLike(s.to_vec())),
+            PactTypeRef::Numeric(n) => PactType::Numeric(n),
+            PactTypeRef::List(l) => PactType::List(l.iter().map(Self::into_owned).collect()),
+            PactTypeRef::Signed(n) => PactType::Signed(n),
+            PactTypeRef::Bool(b) => PactType::Bool(b),
+        }
+    }
+
+    /// Decode a pact type from `buf` in binary `format`, borrowing its data
+    /// Returns (decoded type, bytes read) or error on failure
+    pub fn decode_with(buf: &'a [u8], format: BinaryFormat) -> Result<(Self, usize), DecodeErr> {
+        Self::decode_with_limits(buf, format, &DecodeLimits::default())
+    }
+
+    /// Decode a pact type from `buf` in binary `format` within resource `limits`
+    /// Returns (decoded type, bytes read) or error on failure
+    pub fn decode_with_limits(
+        buf: &'a [u8],
+        format: BinaryFormat,
+        limits: &DecodeLimits,
+    ) -> Result<(Self, usize), DecodeErr> {
+        Self::decode_nested(buf, format, limits, 0, 0)
+    }
+
+    /// Decode a pact type enclosed by `depth` lists from `buf`, which begins at byte `base` of
+    /// the input
+    fn decode_nested(
+        buf: &'a [u8],
+        format: BinaryFormat,
+        limits: &DecodeLimits,
+        depth: usize,
+        base: usize,
+    ) -> Result<(Self, usize), DecodeErr> {
+        let err = |offset: usize, kind: DecodeErrKind| DecodeErr::new(base + offset, kind);
+
+        // Read type ID byte
+        let type_id = buf
+            .first()
+            .ok_or_else(|| err(0, DecodeErrKind::MissingTypeId))?
+            .swap_bits();
+
+        // Read length, 1 byte type ID + the length prefix gives the data offset
+        let (data_length, length_bytes) = format
+            .decode_length(&buf[1..])
+            .map_err(|kind| err(1, kind))?;
+        let read_offset = 1 + length_bytes;
+        if data_length > buf[read_offset..].len() {
+            return Err(err(1, DecodeErrKind::Truncated));
+        }
+        let read_length = read_offset + data_length;
+        let data = &buf[read_offset..read_length];
+
+        let value = match type_id {
+            0 => PactTypeRef::StringLike(data),
+            1 => {
+                let valid = match format {
+                    BinaryFormat::V0 => matches!(data_length, 8 | 16 | 32),
+                    BinaryFormat::V1 => data_length <= 32,
+                };
+                if !valid {
+                    return Err(err(1, DecodeErrKind::InvalidNumericWidth));
+                }
+                if format == BinaryFormat::V1 && data.last() == Some(&0) {
+                    return Err(err(read_offset, DecodeErrKind::NonMinimalNumeric));
+                }
+
+                let mut bytes = [0_u8; 32];
+                for (b, encoded) in bytes.iter_mut().zip(data) {
+                    *b = encoded.swap_bits();
+                }
+                PactTypeRef::Numeric(Numeric(U256::from_little_endian(&bytes)))
+            }
+            2 => {
+                if depth >= limits.max_depth.min(MAX_LIST_DEPTH) {
+                    return Err(err(0, DecodeErrKind::TooDeep));
+                }
+
+                // Validate the elements so they may be iterated infallibly
+                let mut offset = read_offset;
+                let mut len = 0_usize;
+                while offset < read_length {
+                    if len == limits.max_list_len {
+                        return Err(err(offset, DecodeErrKind::ListTooLong));
+                    }
+                    let (_, read) = Self::decode_nested(
+                        &buf[offset..read_length],
+                        format,
+                        limits,
+                        depth + 1,
+                        base + offset,
+                    )
+                    .map_err(|e| match e.kind {
+                        DecodeErrKind::Truncated => err(offset, DecodeErrKind::ListLengthOverflow),
+                        _ => e,
+                    })?;
+                    offset += read;
+                    len += 1;
+                }
+                PactTypeRef::List(ListRef::Encoded { buf: data, format })
+            }
+            3 => {
+                let valid = match format {
+                    BinaryFormat::V0 => matches!(data_length, 8 | 16),
+                    BinaryFormat::V1 => data_length <= 16,
+                };
+                if !valid {
+                    return Err(err(1, DecodeErrKind::InvalidSignedWidth));
+                }
+
+                let mut bytes = [0_u8; 16];
+                for (b, encoded) in bytes.iter_mut().zip(data) {
+                    *b = encoded.swap_bits();
+                }
+                if format == BinaryFormat::V1
+                    && minimal_signed_len(&bytes[..data_length]) != data_length
+                {
+                    return Err(err(read_offset, DecodeErrKind::NonMinimalSigned));
+                }
+
+                // Sign extend from the encoded width
+                if data_length > 0 && bytes[data_length - 1] & 0x80 != 0 {
+                    bytes[data_length..].fill(0xff);
+                }
+                PactTypeRef::Signed(Signed(i128::from_le_bytes(bytes)))
+            }
+            4 => match (data_length, data.first().map(|b| b.swap_bits())) {
+                (1, Some(0)) => PactTypeRef::Bool(false),
+                (1, Some(1)) => PactTypeRef::Bool(true),
+                _ => return Err(err(1, DecodeErrKind::InvalidBool)),
+            },
+            _ => return Err(err(0, DecodeErrKind::UnsupportedTypeId(type_id))),
+        };
+        Ok((value, read_length))
+    }
+}
+
+/// A borrowed `DataTable`, entries are decoded as they are accessed
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy)]
+pub struct DataTableRef<'a> {
+    /// The validated, encoded entries
+    buf: &'a [u8],
+    len: usize,
+    format: BinaryFormat,
+}
+
+impl<'a> DataTableRef<'a> {
+    /// Decode a DataTable from `buf` in binary `format`, borrowing its data.
+    /// Return the DataTable and # of bytes read or error on failure.
+    pub fn decode_with(buf: &'a [u8], format: BinaryFormat) -> Result<(Self, usize), DecodeErr> {
+        Self::decode_with_limits(buf, format, &DecodeLimits::default())
+    }
+
+    /// Decode a DataTable from `buf` in binary `format` within resource `limits`.
+    /// Return the DataTable and # of bytes read or error on failure.
+    pub fn decode_with_limits(
+        buf: &'a [u8],
+        format: BinaryFormat,
+        limits: &DecodeLimits,
+    ) -> Result<(Self, usize), DecodeErr> {
+        let (len, header) = format
+            .decode_length(buf)
+            .map_err(|kind| DecodeErr::new(0, kind))?;
+        if len > limits.max_data_table_entries {
+            return Err(DecodeErr::new(0, DecodeErrKind::TooManyEntries));
+        }
+        let mut offset = header;
+        for _ in 0..len {
+            let (_, read) = PactTypeRef::decode_nested(&buf[offset..], format, limits, 0, offset)?;
+            offset += read;
+        }
+        let table = DataTableRef {
+            buf: &buf[header..offset],
+            len,
+            format,
+        };
+        Ok((table, offset))
+    }
+
+    /// Return the number of entries in the table
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether the table has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return an iterator over the table entries
+    pub fn iter(&self) -> ListIter<'a> {
+        ListRef::Encoded {
+            buf: self.buf,
+            format: self.format,
+        }
+        .iter()
+    }
+
+    /// Return the entry at `index`, if any
+    /// Earlier entries are skipped by their length prefix rather than decoded again
+    pub fn get(&self, index: usize) -> Option<PactTypeRef<'a>> {
+        if index >= self.len {
+            return None;
+        }
+        self.iter().nth(index)
+    }
+
+    /// Copy the borrowed data into an owned `DataTable`
+    pub fn into_owned(self) -> DataTable {
+        DataTable::new(self.iter().map(PactTypeRef::into_owned).collect())
+    }
+}
+
+/// Check the glob patterns matched by `bytecode` are valid
+/// OpCodes are checked up to the first malformed OpCode, which evaluation would not pass
+fn check_patterns(data_table: &DataTableRef, bytecode: &[u8]) -> Result<(), BinaryFormatErr> {
+    let mut scanner = bytecode.iter();
+    while let Ok(Some(op)) = OpCode::parse(&mut scanner) {
+        let OpCode::COMP(Comparator {
+            op: OpComp::GLOB,
+            indices,
+            ..
+        }) = op
+        else {
+            continue;
+        };
+        if let Some(PactTypeRef::StringLike(pattern)) = data_table.get(usize::from(indices.rhs)) {
+            if !glob::is_valid(pattern) {
+                return Err(BinaryFormatErr::InvalidPattern(indices.rhs));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A borrowed `Contract`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy)]
+pub struct ContractRef<'a> {
+    pub data_table: DataTableRef<'a>,
+    pub bytecode: &'a [u8],
+    pub metadata: Option<MetadataRef<'a>>,
+}
+
+impl<'a> ContractRef<'a> {
+    /// Decode a pact contract from any supported binary format version, borrowing its data
+    pub fn decode(buf: &'a [u8]) -> Result<Self, BinaryFormatErr> {
+        Self::decode_with_limits(buf, &DecodeLimits::default())
+    }
+
+    /// Decode a pact contract from any supported binary format version within resource `limits`,
+    /// borrowing its data
+    pub fn decode_with_limits(
+        buf: &'a [u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self, BinaryFormatErr> {
+        if buf.len() > limits.max_bytes {
+            return Err(BinaryFormatErr::LimitExceeded(DecodeErr::new(
+                limits.max_bytes,
+                DecodeErrKind::TooManyBytes,
+            )));
+        }
+        if buf.len() < 2 {
+            return Err(BinaryFormatErr::TooShort);
+        }
+        let version = buf[0].swap_bits();
+        let format = BinaryFormat::from_version(version & !METADATA_FLAG)
+            .ok_or(BinaryFormatErr::UnsupportedVersion)?;
+        let has_metadata = version & METADATA_FLAG != 0;
+        // The v0 format has no metadata section
+        if has_metadata && format == BinaryFormat::V0 {
+            return Err(BinaryFormatErr::UnsupportedVersion);
+        }
+        let (data_table, mut offset) = DataTableRef::decode_with_limits(&buf[1..], format, limits)
+            .map_err(|err| {
+                // Report offsets from the start of the contract
+                BinaryFormatErr::MalformedDataTable(DecodeErr::new(err.offset + 1, err.kind))
+            })?;
+        offset += 1;
+        let metadata = if has_metadata {
+            let (metadata, read) = MetadataRef::decode(&buf[offset..]).map_err(|err| {
+                BinaryFormatErr::MalformedMetadata(DecodeErr::new(err.offset + offset, err.kind))
+            })?;
+            offset += read;
+            Some(metadata)
+        } else {
+            None
+        };
+        let bytecode = &buf[offset..];
+        if bytecode.len() > limits.max_bytecode_len {
+            return Err(BinaryFormatErr::LimitExceeded(DecodeErr::new(
+                offset + limits.max_bytecode_len,
+                DecodeErrKind::BytecodeTooLong,
+            )));
+        }
+        check_patterns(&data_table, bytecode)?;
+        Ok(Self {
+            data_table,
+            bytecode,
+            metadata,
+        })
+    }
+
+    /// Return the binary format the contract was decoded from
+    pub fn format(&self) -> BinaryFormat {
+        self.data_table.format
+    }
+
+    /// Encode the contract into `buf` without its metadata e.g. for compact on-chain storage
+    /// The data table and bytecode are copied as they were decoded
+    pub fn encode_without_metadata(&self, buf: &mut Vec<u8>) {
+        let format = self.data_table.format;
+        buf.push(format.version().swap_bits());
+        format.encode_length(self.data_table.len, buf);
+        buf.extend(self.data_table.buf);
+        buf.extend(self.bytecode);
+    }
+
+    /// Copy the borrowed data into an owned `Contract`, dropping any metadata
+    pub fn into_owned(self) -> Contract {
+        Contract {
+            data_table: self.data_table.into_owned(),
+            bytecode: self.bytecode.to_vec(),
+        }
+    }
+
+    /// Copy the borrowed data into an owned `AnnotatedContract`, keeping any metadata
+    pub fn into_annotated(self) -> AnnotatedContract {
+        AnnotatedContract {
+            metadata: self.metadata.map(MetadataRef::into_owned),
+            contract: self.into_owned(),
+        }
+    }
+}
+
+/// Indexed pact type storage which the interpreter loads operands from
+pub trait Table {
+    /// Return the value at `index`, if any
+    fn get(&self, index: usize) -> Option<PactTypeRef<'_>>;
+}
+
+impl<T: AsRef<[PactType]> + ?Sized> Table for T {
+    fn get(&self, index: usize) -> Option<PactTypeRef<'_>> {
+        self.as_ref().get(index).map(Into::into)
+    }
+}
+
+impl Table for DataTableRef<'_> {
+    fn get(&self, index: usize) -> Option<PactTypeRef<'_>> {
+        DataTableRef::get(self, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_borrows_decoded_data() {
+        let table = DataTable::new(vec![
+            PactType::StringLike(StringLike(b"hello".to_vec())),
+            PactType::List(vec![
+                PactType::Numeric(Numeric(1.into())),
+                PactType::List(vec![PactType::Bool(true)]),
+            ]),
+            PactType::Signed(Signed(-1)),
+        ]);
+        for format in [BinaryFormat::V0, BinaryFormat::V1] {
+            let mut buf = Vec::new();
+            table.encode_with(format, &mut buf);
+
+            let (table_ref, read) = DataTableRef::decode_with(&buf, format).expect("it decodes");
+            assert_eq!(read, buf.len());
+            assert_eq!(table_ref.len(), 3);
+            match table_ref.get(0) {
+                Some(PactTypeRef::StringLike(s)) => {
+                    assert!(buf.as_ptr_range().contains(&s.as_ptr()))
+                }
+                other => panic!("unexpected entry: {:?}", other),
+            }
+            for (index, value) in table.as_ref().iter().enumerate() {
+                assert_eq!(table_ref.get(index), Some(value.into()));
+            }
+            assert_eq!(table_ref.get(3), None);
+            assert_eq!(table_ref.into_owned(), table);
+        }
+    }
+
+    #[test]
+    fn it_compares_owned_and_encoded_lists() {
+        let list = PactType::List(vec![
+            PactType::Numeric(Numeric(1.into())),
+            PactType::StringLike(StringLike(b"a".to_vec())),
+        ]);
+        let mut buf = Vec::new();
+        list.encode_with(BinaryFormat::V1, &mut buf);
+        let (encoded, _) = PactTypeRef::decode_with(&buf, BinaryFormat::V1).expect("it decodes");
+
+        assert_eq!(encoded, PactTypeRef::from(&list));
+        assert_ne!(
+            encoded,
+            PactTypeRef::from(&PactType::List(vec![PactType::Numeric(Numeric(1.into()))]))
+        );
+        assert_eq!(encoded.into_owned(), list);
+    }
+
+    #[test]
+    fn it_skips_earlier_entries_without_decoding_them() {
+        // Corrupt the first entry's element, `get` only reads the entry's length prefix
+        let mut buf = Vec::new();
+        PactType::List(vec![PactType::Bool(true)]).encode_with(BinaryFormat::V0, &mut buf);
+        PactType::StringLike(StringLike(b"a".to_vec())).encode_with(BinaryFormat::V0, &mut buf);
+        buf[2] = 0xff;
+        let table_ref = DataTableRef {
+            buf: &buf,
+            len: 2,
+            format: BinaryFormat::V0,
+        };
+        assert!(PactTypeRef::decode_with(&buf, BinaryFormat::V0).is_err());
+        assert_eq!(table_ref.get(1), Some(PactTypeRef::StringLike(b"a")));
+        assert_eq!(table_ref.get(2), None);
+
+        let elements = vec![
+            PactType::List(vec![PactType::Bool(true), PactType::Signed(Signed(-1))]),
+            PactType::StringLike(StringLike(b"hello".to_vec())),
+            PactType::Numeric(Numeric(7.into())),
+        ];
+        let list = PactType::List(elements.clone());
+        let mut buf = Vec::new();
+        list.encode_with(BinaryFormat::V1, &mut buf);
+        let (encoded, _) = PactTypeRef::decode_with(&buf, BinaryFormat::V1).expect("it decodes");
+        let PactTypeRef::List(encoded) = encoded else {
+            panic!("unexpected entry: {:?}", encoded)
+        };
+        for index in 0..4 {
+            assert_eq!(
+                encoded.iter().nth(index),
+                ListRef::Decoded(&elements).iter().nth(index)
+            );
+        }
+    }
+}
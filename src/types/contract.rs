@@ -16,9 +16,15 @@
 //!
 //! Contract struct
 //!
-use crate::types::DataTable;
-use alloc::vec::Vec;
+use crate::interpreter::{self, Features, InterpErr, LoadSource, OpCode, OpLoad, OpPage};
+use crate::types::metadata::METADATA_FLAG;
+use crate::types::{
+    armor, migrate, ArmorErr, BinaryFormat, ContractRef, DataTable, DecodeErr, DecodeLimits,
+    EncodeErr, Metadata, MigrateErr,
+};
+use alloc::{string::String, vec::Vec};
 use bit_reverse::ParallelReverse;
+use blake2::{digest::consts::U32, Blake2b, Digest};
 
 #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
 /// A binary format error
@@ -26,49 +32,343 @@ pub enum BinaryFormatErr {
     /// Version mismatch
     UnsupportedVersion,
     /// DataTable is invalid
-    MalformedDataTable(&'static str),
+    MalformedDataTable(DecodeErr),
+    /// The metadata section is invalid
+    MalformedMetadata(DecodeErr),
+    /// The contract exceeds a decoding limit
+    LimitExceeded(DecodeErr),
     // The buffer is to short to be valid
     TooShort,
+    /// The data table entry at this index is matched as an invalid glob pattern
+    InvalidPattern(u8),
+    /// The contract is valid but not in canonical form
+    NonCanonical(CanonicalErr),
 }
 
+/// A reason a contract is not in canonical form
+/// A canonical contract is encoded in the latest binary format, its bytecode is a well formed program
+/// with minimal OpCodes which requires only the OpCode pages it uses, and its data table holds
+/// distinct entries in order of first use.
+#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+pub enum CanonicalErr {
+    /// The contract is encoded in an older binary format
+    OutdatedFormat,
+    /// The bytecode is not a complete, well formed program
+    InvalidBytecode(InterpErr),
+    /// The OpCode at this bytecode offset has a shorter encoding
+    NonMinimalOpCode(usize),
+    /// The bytecode references a data table entry which does not exist
+    MissingEntry(u8),
+    /// The data table entry is referenced before the entry preceding it
+    OutOfOrderEntry(usize),
+    /// The data table entry is never referenced
+    UnusedEntry(usize),
+    /// The data table entry is equal to an earlier entry
+    DuplicateEntry(usize),
+    /// The OpCode page is required but no OpCode uses it
+    UnusedFeature(u8),
+}
+
+/// A contract's ID, the blake2-256 hash of its encoding without metadata
+pub type ContractId = [u8; 32];
+
 /// A pact contract
 /// It has byte code and an accompanying data section
 #[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contract {
     pub data_table: DataTable,
+    #[cfg_attr(feature = "serde", serde(with = "crate::types::serialize::hex_bytes"))]
     pub bytecode: Vec<u8>,
 }
 
+/// A contract and its optional descriptive metadata
+/// Metadata is not evaluated, and is not encoded in the v0 format
+#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnnotatedContract {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub contract: Contract,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub metadata: Option<Metadata>,
+}
+
+/// A contract and the binary format version it was decoded from
+#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+pub struct VersionedContract {
+    pub format: BinaryFormat,
+    pub contract: Contract,
+}
+
 impl Contract {
-    /// Encode the contract as v0 binary format into `buf`
+    /// Encode the contract as the v0 binary format into `buf`
+    /// Use `encode_with` for later formats
+    /// Lengths which do not fit the format are truncated, `try_encode` rejects them instead
     pub fn encode(&self, buf: &mut Vec<u8>) {
-        buf.push(0); // binary format version: `0`
-        self.data_table.encode(buf);
-        buf.extend(self.bytecode.clone());
-    }
-    /// Decode a pact contract from v0 binary format
-    pub fn decode(buf: &Vec<u8>) -> Result<Self, BinaryFormatErr> {
-        if buf.len() < 2 {
-            return Err(BinaryFormatErr::TooShort);
+        self.encode_with(BinaryFormat::V0, buf)
+    }
+    /// Encode the contract as binary `format` into `buf`
+    pub fn encode_with(&self, format: BinaryFormat, buf: &mut Vec<u8>) {
+        self.encode_parts(format, None, buf)
+    }
+    /// Encode the contract as binary `format` into `buf` with the given `metadata`
+    fn encode_parts(&self, format: BinaryFormat, metadata: Option<&Metadata>, buf: &mut Vec<u8>) {
+        let flag = if metadata.is_some() { METADATA_FLAG } else { 0 };
+        buf.push((format.version() | flag).swap_bits());
+        self.data_table.encode_with(format, buf);
+        if let Some(metadata) = metadata {
+            metadata.encode(buf);
+        }
+        buf.extend(self.bytecode.iter());
+    }
+    /// Encode the contract as the v0 binary format into `buf`
+    /// Fails without writing to `buf` if a value can not be represented
+    pub fn try_encode(&self, buf: &mut Vec<u8>) -> Result<(), EncodeErr> {
+        self.try_encode_with(BinaryFormat::V0, buf)
+    }
+    /// Encode the contract as binary `format` into `buf`
+    /// Fails without writing to `buf` if a value can not be represented
+    pub fn try_encode_with(
+        &self,
+        format: BinaryFormat,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), EncodeErr> {
+        self.try_encode_parts(format, None, buf)
+    }
+    /// Encode the contract as binary `format` into `buf` with the given `metadata`
+    /// Fails without writing to `buf` if a value can not be represented
+    fn try_encode_parts(
+        &self,
+        format: BinaryFormat,
+        metadata: Option<&Metadata>,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), EncodeErr> {
+        let mut data_table = Vec::new();
+        self.data_table.try_encode_with(format, &mut data_table)?;
+        if let Some(metadata) = metadata {
+            metadata.check_encoding()?;
         }
-        if buf[0].swap_bits() != 0 {
-            return Err(BinaryFormatErr::UnsupportedVersion);
+        let flag = if metadata.is_some() { METADATA_FLAG } else { 0 };
+        buf.push((format.version() | flag).swap_bits());
+        buf.append(&mut data_table);
+        if let Some(metadata) = metadata {
+            metadata.encode(buf);
         }
-        let (data_table, offset) = DataTable::decode(buf[1..].to_vec())
-            .map_err(|err| BinaryFormatErr::MalformedDataTable(err))?;
-        let bytecode = buf[1usize + offset..].to_vec();
-        Ok(Self {
-            data_table,
-            bytecode,
+        buf.extend(self.bytecode.iter());
+        Ok(())
+    }
+    /// Attach descriptive `metadata` to the contract
+    pub fn with_metadata(self, metadata: Metadata) -> AnnotatedContract {
+        AnnotatedContract {
+            contract: self,
+            metadata: Some(metadata),
+        }
+    }
+    /// Decode a pact contract from any supported binary format version, dropping any metadata
+    pub fn decode(buf: &[u8]) -> Result<Self, BinaryFormatErr> {
+        ContractRef::decode(buf).map(ContractRef::into_owned)
+    }
+    /// Decode a pact contract from any supported binary format version within resource `limits`
+    pub fn decode_with_limits(buf: &[u8], limits: &DecodeLimits) -> Result<Self, BinaryFormatErr> {
+        ContractRef::decode_with_limits(buf, limits).map(ContractRef::into_owned)
+    }
+    /// Decode a pact contract from any supported binary format version, tagged with its version
+    pub fn decode_versioned(buf: &[u8]) -> Result<VersionedContract, BinaryFormatErr> {
+        let contract = ContractRef::decode(buf)?;
+        Ok(VersionedContract {
+            format: contract.format(),
+            contract: contract.into_owned(),
         })
     }
+    /// Re-encode the contract in `buf` as binary `format` e.g. to reissue a v0 contract as v1
+    /// The migrated contract is checked to evaluate identically to the original over inputs
+    /// generated from its data table, see `MigrateErr::Diverged`.
+    /// Metadata is dropped when migrating to v0.
+    pub fn migrate(buf: &[u8], format: BinaryFormat) -> Result<Vec<u8>, MigrateErr> {
+        let original = ContractRef::decode(buf).map_err(MigrateErr::InvalidContract)?;
+        let mut migrated = Vec::new();
+        original
+            .into_annotated()
+            .try_encode_with(format, &mut migrated)
+            .map_err(MigrateErr::Unrepresentable)?;
+        let decoded = ContractRef::decode(&migrated).map_err(MigrateErr::InvalidContract)?;
+        migrate::check_equivalent(&original, &decoded)?;
+        Ok(migrated)
+    }
+    /// Decode a pact contract, failing unless `buf` is its canonical encoding
+    /// A canonical contract has exactly one encoding, and so one ID. Any metadata is dropped
+    pub fn decode_canonical(buf: &[u8]) -> Result<Self, BinaryFormatErr> {
+        let contract = Self::decode(buf)?;
+        // The latest format has one encoding per contract, older formats may not
+        if buf[0].swap_bits() & !METADATA_FLAG != BinaryFormat::LATEST.version() {
+            return Err(BinaryFormatErr::NonCanonical(CanonicalErr::OutdatedFormat));
+        }
+        contract
+            .check_canonical()
+            .map_err(BinaryFormatErr::NonCanonical)?;
+        Ok(contract)
+    }
+    /// Check the contract is in canonical form, see `CanonicalErr`
+    pub fn check_canonical(&self) -> Result<(), CanonicalErr> {
+        interpreter::validate(&self.bytecode).map_err(CanonicalErr::InvalidBytecode)?;
+        let required =
+            Features::required_by(&self.bytecode).map_err(CanonicalErr::InvalidBytecode)?;
+        // The pages of the extended OpCodes used
+        let mut features = Features::default();
+
+        let entries = self.data_table.as_ref();
+        // The number of distinct entries referenced so far
+        let mut used = 0;
+        let mut scanner = self.bytecode.iter();
+        let mut minimal = Vec::with_capacity(3);
+        loop {
+            let offset = self.bytecode.len() - scanner.as_slice().len();
+            let Ok(Some(op)) = OpCode::parse(&mut scanner) else {
+                break;
+            };
+            minimal.clear();
+            op.compile(&mut minimal);
+            if minimal.len() < self.bytecode.len() - scanner.as_slice().len() - offset {
+                return Err(CanonicalErr::NonMinimalOpCode(offset));
+            }
+            // The page of the OpCode and the data table entries it references, in order
+            let (page, references) = match op {
+                OpCode::COMP(comparator) => (
+                    comparator.op.page(),
+                    [
+                        None,
+                        (comparator.load == OpLoad::INPUT_VS_USER)
+                            .then_some(comparator.indices.rhs),
+                    ],
+                ),
+                OpCode::ARITH(arithmetic) => (
+                    Some(OpPage::ARITH),
+                    [
+                        (arithmetic.term.load_source == LoadSource::DataTable)
+                            .then_some(arithmetic.term.index),
+                        (arithmetic.load == OpLoad::INPUT_VS_USER)
+                            .then_some(arithmetic.indices.rhs),
+                    ],
+                ),
+                OpCode::MASK(mask) => (
+                    Some(OpPage::BITMASK),
+                    [
+                        (mask.term.load_source == LoadSource::DataTable).then_some(mask.term.index),
+                        (mask.load == OpLoad::INPUT_VS_USER).then_some(mask.indices.rhs),
+                    ],
+                ),
+                OpCode::CONJ(_) | OpCode::GROUP(_) | OpCode::REQUIRE(_) => continue,
+            };
+            if let Some(page) = page {
+                features = features.with(page);
+            }
+            for reference in references.into_iter().flatten() {
+                let index = reference as usize;
+                if index >= entries.len() {
+                    return Err(CanonicalErr::MissingEntry(reference));
+                }
+                if index > used {
+                    return Err(CanonicalErr::OutOfOrderEntry(index));
+                }
+                if index == used {
+                    used += 1;
+                }
+            }
+        }
+        if used < entries.len() {
+            return Err(CanonicalErr::UnusedEntry(used));
+        }
+        if let Some(page) = required.iter().find(|page| !features.contains(*page)) {
+            return Err(CanonicalErr::UnusedFeature(page.id()));
+        }
+
+        for (i, entry) in entries.iter().enumerate() {
+            if entries[..i].contains(entry) {
+                return Err(CanonicalErr::DuplicateEntry(i));
+            }
+        }
+        Ok(())
+    }
+    /// Encode the contract as checksummed text e.g. `pact1qzq...`, for sharing outside of a chain
+    /// The armor holds the latest binary format encoding, failing if a value can not be represented
+    pub fn to_armored(&self) -> Result<String, EncodeErr> {
+        let mut buf = Vec::new();
+        self.try_encode_with(BinaryFormat::LATEST, &mut buf)?;
+        Ok(armor::encode(&buf))
+    }
+    /// Decode a contract from checksummed text, failing on corrupted armor before decoding
+    /// Any metadata is dropped
+    pub fn from_armored(armor: &str) -> Result<Self, ArmorErr> {
+        let buf = armor::decode(armor)?;
+        Self::decode(&buf).map_err(ArmorErr::InvalidContract)
+    }
+    /// Return the contract's ID, the blake2-256 hash of its encoding in the latest binary format
+    /// Canonical contracts which are equal have the same ID, and their ID is the hash of their
+    /// canonical encoding. Fails if a value can not be represented
+    pub fn id(&self) -> Result<ContractId, EncodeErr> {
+        let mut buf = Vec::new();
+        self.try_encode_with(BinaryFormat::LATEST, &mut buf)?;
+        Ok(Blake2b::<U32>::digest(&buf).into())
+    }
+}
+
+impl AnnotatedContract {
+    /// Encode the contract and its metadata as binary `format` into `buf`
+    /// Lengths which do not fit the format are truncated, `try_encode_with` rejects them instead
+    pub fn encode_with(&self, format: BinaryFormat, buf: &mut Vec<u8>) {
+        self.contract
+            .encode_parts(format, self.metadata_for(format), buf)
+    }
+    /// Encode the contract and its metadata as binary `format` into `buf`
+    /// Fails without writing to `buf` if a value can not be represented
+    pub fn try_encode_with(
+        &self,
+        format: BinaryFormat,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), EncodeErr> {
+        self.contract
+            .try_encode_parts(format, self.metadata_for(format), buf)
+    }
+    /// Return the metadata to encode in binary `format`, if any
+    fn metadata_for(&self, format: BinaryFormat) -> Option<&Metadata> {
+        self.metadata
+            .as_ref()
+            .filter(|_| format != BinaryFormat::V0)
+    }
+    /// Decode a pact contract and its metadata from any supported binary format version
+    pub fn decode(buf: &[u8]) -> Result<Self, BinaryFormatErr> {
+        ContractRef::decode(buf).map(ContractRef::into_annotated)
+    }
+    /// Decode a pact contract and its metadata from any supported binary format version within
+    /// resource `limits`
+    pub fn decode_with_limits(buf: &[u8], limits: &DecodeLimits) -> Result<Self, BinaryFormatErr> {
+        ContractRef::decode_with_limits(buf, limits).map(ContractRef::into_annotated)
+    }
+    /// Encode the contract as checksummed text, see `Contract::to_armored`
+    /// The armor holds the latest binary format encoding, including metadata
+    pub fn to_armored(&self) -> Result<String, EncodeErr> {
+        let mut buf = Vec::new();
+        self.try_encode_with(BinaryFormat::LATEST, &mut buf)?;
+        Ok(armor::encode(&buf))
+    }
+    /// Decode a contract and its metadata from checksummed text, failing on corrupted armor
+    /// before decoding
+    pub fn from_armored(armor: &str) -> Result<Self, ArmorErr> {
+        let buf = armor::decode(armor)?;
+        Self::decode(&buf).map_err(ArmorErr::InvalidContract)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::interpreter::{Comparator, Conjunction, OpCode, OpComp, OpConj, OpLoad};
-    use crate::types::{Numeric, PactType, StringLike};
+    use crate::interpreter::{
+        Arithmetic, Comparator, Conjunction, OpArith, OpCode, OpComp, OpConj, OpLoad, OpPage,
+    };
+    use crate::types::{DecodeErrKind, Numeric, PactType, StringLike, MAX_LIST_DEPTH};
 
     #[test]
     fn contract_binary_format_unsupported_version() {
@@ -78,6 +378,68 @@ mod test {
         );
     }
 
+    #[test]
+    fn contract_binary_format_v1_supported_v2_unsupported() {
+        assert_eq!(
+            Contract::decode(&[BinaryFormat::V1.version().swap_bits(), 0]),
+            Ok(Contract {
+                data_table: DataTable::new(vec![]),
+                bytecode: vec![],
+            })
+        );
+        assert_eq!(
+            Contract::decode(&[2.swap_bits(), 0]),
+            Err(BinaryFormatErr::UnsupportedVersion)
+        );
+    }
+
+    #[test]
+    fn contract_binary_format_versions() {
+        let contract = Contract {
+            data_table: DataTable::new(vec![PactType::StringLike(StringLike(vec![7; 256]))]),
+            bytecode: vec![OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00],
+        };
+
+        // The latest format is written on request
+        let mut encoded = vec![];
+        contract.encode_with(BinaryFormat::LATEST, &mut encoded);
+        assert_eq!(encoded[0].swap_bits(), BinaryFormat::LATEST.version());
+        assert_eq!(Contract::decode(&encoded), Ok(contract));
+
+        // v0 is written by default and still decodes
+        let contract = Contract {
+            data_table: DataTable::new(vec![PactType::Numeric(Numeric(10.into()))]),
+            bytecode: vec![OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00],
+        };
+        let mut encoded = vec![];
+        contract.encode(&mut encoded);
+        assert_eq!(encoded[0], 0);
+        assert_eq!(Contract::decode(&encoded), Ok(contract));
+    }
+
+    #[test]
+    fn contract_try_encode() {
+        let contract = Contract {
+            data_table: DataTable::new(vec![PactType::StringLike(StringLike(vec![7; 256]))]),
+            bytecode: vec![OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00],
+        };
+
+        let mut encoded = vec![];
+        assert_eq!(
+            contract.try_encode(&mut encoded),
+            Err(EncodeErr::StringTooLong(256))
+        );
+        assert!(encoded.is_empty());
+
+        assert_eq!(
+            contract.try_encode_with(BinaryFormat::V1, &mut encoded),
+            Ok(())
+        );
+        let mut expected = vec![];
+        contract.encode_with(BinaryFormat::V1, &mut expected);
+        assert_eq!(encoded, expected);
+    }
+
     #[test]
     fn contract_binary_format_too_short() {
         assert_eq!(
@@ -90,8 +452,8 @@ mod test {
     fn contract_encode_1() {
         let contract = Contract {
             data_table: DataTable::new(vec![
-                PactType::Numeric(Numeric(10)),
-                PactType::Numeric(Numeric(20)),
+                PactType::Numeric(Numeric(10.into())),
+                PactType::Numeric(Numeric(20.into())),
             ]),
             bytecode: vec![OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00],
         };
@@ -104,7 +466,7 @@ mod test {
     fn contract_encode_2() {
         let contract = Contract {
             data_table: DataTable::new(vec![
-                PactType::Numeric(Numeric(10)),
+                PactType::Numeric(Numeric(10.into())),
                 PactType::StringLike(StringLike(b"hello, world".to_vec())),
             ]),
             bytecode: vec![
@@ -123,7 +485,7 @@ mod test {
     fn contract_encode_3() {
         let contract = Contract {
             data_table: DataTable::new(vec![
-                PactType::Numeric(Numeric(10)),
+                PactType::Numeric(Numeric(10.into())),
                 PactType::StringLike(StringLike(b"hello, world".to_vec())),
             ]),
             bytecode: vec![
@@ -142,8 +504,8 @@ mod test {
     fn contract_encode_4() {
         let contract = Contract {
             data_table: DataTable::new(vec![
-                PactType::Numeric(Numeric(10)),
-                PactType::Numeric(Numeric(20)),
+                PactType::Numeric(Numeric(10.into())),
+                PactType::Numeric(Numeric(20.into())),
             ]),
             bytecode: vec![
                 OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
@@ -155,4 +517,392 @@ mod test {
         contract.encode(&mut encoded_payload);
         println!("{:?}", encoded_payload);
     }
+
+    /// A contract comparing input 0 and 1 to data table entries `rhs`
+    fn contract_using(table: Vec<PactType>, rhs: &[u8]) -> Contract {
+        let mut bytecode = vec![];
+        for (lhs, rhs) in rhs.iter().enumerate() {
+            OpCode::COMP(Comparator::new(OpComp::EQ).indices(lhs as u8, *rhs))
+                .compile(&mut bytecode);
+        }
+        Contract {
+            data_table: DataTable::new(table),
+            bytecode,
+        }
+    }
+
+    #[test]
+    fn contract_check_canonical() {
+        let numeric = |n: u64| PactType::Numeric(Numeric(n.into()));
+        let tests = vec![
+            (
+                contract_using(vec![numeric(1), numeric(2)], &[0, 1]),
+                Ok(()),
+            ),
+            (
+                contract_using(vec![numeric(1), numeric(2)], &[0, 1, 0]),
+                Ok(()),
+            ),
+            (
+                contract_using(vec![numeric(1), numeric(2)], &[1, 0]),
+                Err(CanonicalErr::OutOfOrderEntry(1)),
+            ),
+            (
+                contract_using(vec![numeric(1), numeric(2)], &[0]),
+                Err(CanonicalErr::UnusedEntry(1)),
+            ),
+            (
+                contract_using(vec![numeric(1), numeric(1)], &[0, 1]),
+                Err(CanonicalErr::DuplicateEntry(1)),
+            ),
+            (
+                contract_using(vec![numeric(1)], &[0, 1]),
+                Err(CanonicalErr::MissingEntry(1)),
+            ),
+            (
+                Contract {
+                    data_table: DataTable::new(vec![numeric(1)]),
+                    bytecode: vec![OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00, 0x00],
+                },
+                Err(CanonicalErr::InvalidBytecode(InterpErr::UnexpectedEOI(
+                    "expected index",
+                ))),
+            ),
+            (
+                Contract {
+                    data_table: DataTable::new(vec![numeric(1)]),
+                    bytecode: vec![
+                        OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                        0x00,
+                        u8::from(OpCode::COMP(Comparator::new(OpComp::EQ))) | 0b0100_0000,
+                        0x00,
+                        0x00,
+                    ],
+                },
+                Err(CanonicalErr::NonMinimalOpCode(2)),
+            ),
+            (
+                Contract {
+                    data_table: DataTable::new(vec![numeric(1)]),
+                    bytecode: vec![
+                        OpCode::REQUIRE(OpPage::new(2).unwrap()).into(),
+                        OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                        0x00,
+                    ],
+                },
+                Err(CanonicalErr::UnusedFeature(2)),
+            ),
+            (
+                Contract {
+                    data_table: DataTable::new(vec![numeric(1)]),
+                    bytecode: vec![
+                        OpCode::REQUIRE(OpPage::STRING).into(),
+                        OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                        0x00,
+                    ],
+                },
+                Err(CanonicalErr::UnusedFeature(1)),
+            ),
+            (
+                Contract {
+                    data_table: DataTable::new(vec![]),
+                    bytecode: vec![
+                        OpCode::COMP(Comparator::new(OpComp::PREFIX)).into(),
+                        0x01,
+                        0x00,
+                    ],
+                },
+                Err(CanonicalErr::InvalidBytecode(InterpErr::UndeclaredFeature(
+                    1,
+                ))),
+            ),
+        ];
+        for (contract, expected) in tests {
+            assert_eq!(contract.check_canonical(), expected);
+        }
+
+        // Inputs compared with each other do not use the data table
+        let contract = Contract {
+            data_table: DataTable::new(vec![]),
+            bytecode: vec![
+                OpCode::COMP(Comparator::new(OpComp::EQ).load(OpLoad::INPUT_VS_INPUT)).into(),
+                0x01,
+            ],
+        };
+        assert_eq!(contract.check_canonical(), Ok(()));
+
+        // An arithmetic comparator references its term before its RHS
+        let arithmetic = |term| {
+            let mut bytecode = vec![OpCode::REQUIRE(OpPage::ARITH).into()];
+            OpCode::ARITH(
+                Arithmetic::new(OpArith::ADD, OpComp::GT)
+                    .indices(0, 1)
+                    .term(LoadSource::DataTable, term),
+            )
+            .compile(&mut bytecode);
+            Contract {
+                data_table: DataTable::new(vec![numeric(1), numeric(2)]),
+                bytecode,
+            }
+            .check_canonical()
+        };
+        assert_eq!(arithmetic(0), Ok(()));
+        assert_eq!(arithmetic(1), Err(CanonicalErr::OutOfOrderEntry(1)));
+        assert_eq!(arithmetic(2), Err(CanonicalErr::MissingEntry(2)));
+
+        // A declared page is used by an extended comparator
+        let contract = Contract {
+            data_table: DataTable::new(vec![]),
+            bytecode: vec![
+                OpCode::REQUIRE(OpPage::STRING).into(),
+                OpCode::COMP(Comparator::new(OpComp::SUFFIX).load(OpLoad::INPUT_VS_INPUT)).into(),
+                0x01,
+                0x01,
+            ],
+        };
+        assert_eq!(contract.check_canonical(), Ok(()));
+    }
+
+    #[test]
+    fn contract_decode_canonical() {
+        let contract = contract_using(vec![PactType::Numeric(Numeric(1.into()))], &[0]);
+
+        let mut encoded = vec![];
+        contract.encode_with(BinaryFormat::LATEST, &mut encoded);
+        assert_eq!(Contract::decode_canonical(&encoded), Ok(contract));
+
+        // Trailing garbage
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert_eq!(
+            Contract::decode_canonical(&trailing),
+            Err(BinaryFormatErr::NonCanonical(
+                CanonicalErr::InvalidBytecode(InterpErr::UnexpectedEOI("expected index"))
+            ))
+        );
+
+        // An older format
+        let contract = Contract::decode(&encoded).unwrap();
+        let mut v0 = vec![];
+        contract.encode_with(BinaryFormat::V0, &mut v0);
+        assert_eq!(
+            Contract::decode_canonical(&v0),
+            Err(BinaryFormatErr::NonCanonical(CanonicalErr::OutdatedFormat))
+        );
+
+        // Malformed contracts fail as usual
+        assert_eq!(
+            Contract::decode_canonical(&[2.swap_bits(), 0]),
+            Err(BinaryFormatErr::UnsupportedVersion)
+        );
+    }
+
+    #[test]
+    fn contract_id() {
+        let table = vec![
+            PactType::StringLike(StringLike(b"alice".to_vec())),
+            PactType::StringLike(StringLike(b"bob".to_vec())),
+        ];
+        let contract = contract_using(table.clone(), &[0, 1]);
+        let mut encoded = vec![];
+        contract.encode_with(BinaryFormat::LATEST, &mut encoded);
+
+        // The ID of a canonical contract is the blake2-256 hash of its bytes
+        assert_eq!(
+            contract.id().unwrap(),
+            Blake2b::<U32>::digest(&encoded).as_slice()
+        );
+        assert_eq!(
+            Contract::decode_canonical(&encoded).unwrap().id().unwrap(),
+            contract.id().unwrap()
+        );
+        assert_eq!(
+            contract.id(),
+            Ok([
+                0x18, 0x5b, 0x9b, 0xe4, 0xbd, 0xa8, 0xd6, 0xa9, 0xcb, 0xde, 0x7e, 0x7f, 0xe0, 0x31,
+                0x01, 0x30, 0x52, 0xba, 0xd2, 0xd2, 0x62, 0x7e, 0xae, 0x35, 0x11, 0x22, 0xd3, 0x65,
+                0x51, 0x7b, 0xac, 0xc3
+            ])
+        );
+
+        // Any change to the contract changes its ID
+        assert_ne!(
+            contract_using(table.clone(), &[0, 1, 1]).id().unwrap(),
+            contract.id().unwrap()
+        );
+        assert_ne!(
+            contract_using(table.into_iter().rev().collect(), &[0, 1])
+                .id()
+                .unwrap(),
+            contract.id().unwrap()
+        );
+
+        // A contract which can not be encoded has no ID or armor
+        let deep =
+            (0..=MAX_LIST_DEPTH).fold(PactType::Bool(true), |inner, _| PactType::List(vec![inner]));
+        let contract = contract_using(vec![deep], &[0]);
+        assert_eq!(contract.id(), Err(EncodeErr::TooDeeplyNested));
+        assert_eq!(contract.to_armored(), Err(EncodeErr::TooDeeplyNested));
+    }
+
+    #[test]
+    fn contract_metadata() {
+        let contract = || contract_using(vec![PactType::Numeric(Numeric(1.into()))], &[0]);
+        let annotated = contract().with_metadata(Metadata {
+            parameters: vec!["amount".into()],
+            module: Some("generic-asset".into()),
+            method: Some("transfer".into()),
+            ..Default::default()
+        });
+
+        let mut encoded = vec![];
+        annotated.encode_with(BinaryFormat::LATEST, &mut encoded);
+        assert_eq!(
+            encoded[0].swap_bits(),
+            BinaryFormat::LATEST.version() | METADATA_FLAG
+        );
+        let mut try_encoded = vec![];
+        assert_eq!(
+            annotated.try_encode_with(BinaryFormat::LATEST, &mut try_encoded),
+            Ok(())
+        );
+        assert_eq!(try_encoded, encoded);
+        assert_eq!(AnnotatedContract::decode(&encoded), Ok(annotated));
+
+        // Metadata is canonical, is dropped by `Contract::decode` and does not change the ID
+        let decoded = Contract::decode_canonical(&encoded).expect("it decodes");
+        assert_eq!(decoded, contract());
+        assert_eq!(decoded.id().unwrap(), contract().id().unwrap());
+
+        // Stripping the metadata leaves the encoding without it
+        let borrowed = ContractRef::decode(&encoded).unwrap();
+        assert!(borrowed.metadata.is_some());
+        let mut stripped = vec![];
+        borrowed.encode_without_metadata(&mut stripped);
+        let mut expected = vec![];
+        contract().encode_with(BinaryFormat::LATEST, &mut expected);
+        assert_eq!(stripped, expected);
+    }
+
+    #[test]
+    fn contract_metadata_is_omitted_from_v0() {
+        let contract = || contract_using(vec![PactType::Numeric(Numeric(1.into()))], &[0]);
+        let annotated = contract().with_metadata(Metadata {
+            description: Some("v0 has no metadata".into()),
+            ..Default::default()
+        });
+        let mut encoded = vec![];
+        annotated.encode_with(BinaryFormat::V0, &mut encoded);
+        assert_eq!(
+            AnnotatedContract::decode(&encoded),
+            Ok(AnnotatedContract {
+                contract: contract(),
+                metadata: None,
+            })
+        );
+
+        // The metadata flag is not valid in v0
+        encoded[0] = METADATA_FLAG.swap_bits();
+        assert_eq!(
+            Contract::decode(&encoded),
+            Err(BinaryFormatErr::UnsupportedVersion)
+        );
+    }
+
+    #[test]
+    fn contract_malformed_metadata() {
+        let contract = contract_using(vec![PactType::Bool(true)], &[0]);
+        let mut encoded = vec![];
+        contract.encode_with(BinaryFormat::LATEST, &mut encoded);
+        // Set the flag without a metadata section, the bytecode is read as an empty one
+        encoded[0] = (BinaryFormat::V1.version() | METADATA_FLAG).swap_bits();
+        assert_eq!(
+            Contract::decode(&encoded),
+            Err(BinaryFormatErr::MalformedMetadata(DecodeErr::new(
+                6,
+                DecodeErrKind::Truncated
+            )))
+        );
+    }
+
+    #[test]
+    fn contract_invalid_glob_pattern() {
+        let glob = |pattern: &[u8]| {
+            let mut encoded = vec![];
+            Contract {
+                data_table: DataTable::new(vec![
+                    PactType::StringLike(StringLike(br"a\".to_vec())),
+                    PactType::StringLike(StringLike(pattern.to_vec())),
+                ]),
+                bytecode: vec![
+                    OpCode::REQUIRE(OpPage::GLOB).into(),
+                    OpCode::COMP(Comparator::new(OpComp::GLOB).indices(0, 1)).into(),
+                    OpPage::GLOB.into(),
+                    0x01,
+                    // An unescaped backslash is only invalid in a pattern
+                    OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                    0x00,
+                ],
+            }
+            .encode(&mut encoded);
+            Contract::decode(&encoded)
+        };
+        assert!(glob(br"a*\\").is_ok());
+        assert_eq!(glob(br"a*\"), Err(BinaryFormatErr::InvalidPattern(1)));
+        assert_eq!(glob(br"\a"), Err(BinaryFormatErr::InvalidPattern(1)));
+    }
+
+    #[test]
+    fn contract_armor_with_invalid_payload() {
+        // A well formed armor holding an unsupported binary format version
+        let armor = armor::encode(&[2.swap_bits(), 0]);
+        assert_eq!(
+            Contract::from_armored(&armor),
+            Err(ArmorErr::InvalidContract(
+                BinaryFormatErr::UnsupportedVersion
+            ))
+        );
+    }
+
+    #[test]
+    fn contract_decode_versioned() {
+        let contract = || Contract {
+            data_table: DataTable::new(vec![PactType::Numeric(Numeric(1.into()))]),
+            bytecode: vec![OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00],
+        };
+        for format in [BinaryFormat::V0, BinaryFormat::V1] {
+            let mut encoded = Vec::new();
+            contract().encode_with(format, &mut encoded);
+            assert_eq!(
+                Contract::decode_versioned(&encoded),
+                Ok(VersionedContract {
+                    format,
+                    contract: contract()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn contract_migrate() {
+        let contract = Contract {
+            data_table: DataTable::new(vec![PactType::StringLike(StringLike(vec![7; 256]))]),
+            bytecode: vec![OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00],
+        };
+        let mut encoded = Vec::new();
+        contract.encode_with(BinaryFormat::LATEST, &mut encoded);
+        assert_eq!(
+            Contract::migrate(&encoded, BinaryFormat::V1),
+            Ok(encoded.clone())
+        );
+        // The string is too long for v0
+        assert_eq!(
+            Contract::migrate(&encoded, BinaryFormat::V0),
+            Err(MigrateErr::Unrepresentable(EncodeErr::StringTooLong(256)))
+        );
+        assert_eq!(
+            Contract::migrate(&encoded[..1], BinaryFormat::V1),
+            Err(MigrateErr::InvalidContract(BinaryFormatErr::TooShort))
+        );
+    }
 }
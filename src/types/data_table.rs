@@ -13,42 +13,76 @@
 // along with Pact. If not, see:
 //   <https://futureverse.com/licenses/apachev2.txt>
 
-use crate::types::PactType;
+use crate::types::{BinaryFormat, DataTableRef, DecodeErr, EncodeErr, PactType};
 use alloc::vec::Vec;
-use bit_reverse::ParallelReverse;
 
 /// A pact contract's static data table
 #[cfg_attr(feature = "std", derive(PartialEq, Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(codec::Encode, codec::Decode, scale_info::TypeInfo)
+)]
 pub struct DataTable(Vec<PactType>);
 
 impl DataTable {
     /// Create a new `DataTable` with `values`
     pub fn new(values: Vec<PactType>) -> Self {
-        Self { 0: values }
+        Self(values)
     }
     /// Push a PactType value into the table
     pub fn push(&mut self, val: PactType) {
         self.0.push(val);
     }
-    /// Encode the data table
+    /// Encode the data table using the v0 binary format
+    /// Lengths which do not fit the format are truncated, `try_encode` rejects them instead
     pub fn encode(&self, buf: &mut Vec<u8>) {
-        buf.push((self.0.len() as u8).swap_bits());
+        self.encode_with(BinaryFormat::V0, buf)
+    }
+    /// Encode the data table using the given binary `format`
+    pub fn encode_with(&self, format: BinaryFormat, buf: &mut Vec<u8>) {
+        format.encode_length(self.0.len(), buf);
         for t in self.0.iter() {
-            t.encode(buf);
+            t.encode_with(format, buf);
         }
     }
-    /// Decode a DataTable from `buf`.
-    /// Return the DataTable and # of bytes read or error on failure.
-    pub fn decode(buf: Vec<u8>) -> Result<(Self, usize), &'static str> {
-        let mut table = DataTable(Default::default());
-        let mut offset: usize = 1;
-        let l = buf[0].swap_bits();
-        for _ in 0..l {
-            let (pact_type, read) = PactType::decode(buf[offset..].to_vec())?;
-            table.push(pact_type);
-            offset += read;
+    /// Encode the data table using the v0 binary format.
+    /// Fails without writing to `buf` if a value can not be represented.
+    pub fn try_encode(&self, buf: &mut Vec<u8>) -> Result<(), EncodeErr> {
+        self.try_encode_with(BinaryFormat::V0, buf)
+    }
+    /// Encode the data table using the given binary `format`.
+    /// Fails without writing to `buf` if a value can not be represented.
+    pub fn try_encode_with(
+        &self,
+        format: BinaryFormat,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), EncodeErr> {
+        if self.0.len() > format.max_length() {
+            return Err(EncodeErr::TooManyDataTableEntries(self.0.len()));
         }
-        Ok((table, offset))
+        let start = buf.len();
+        for t in self.0.iter() {
+            if let Err(err) = t.try_encode_with(format, buf) {
+                buf.truncate(start);
+                return Err(err);
+            }
+        }
+        // Prefix the entries with their count
+        let mut length = Vec::new();
+        format.encode_length(self.0.len(), &mut length);
+        buf.splice(start..start, length);
+        Ok(())
+    }
+    /// Decode a DataTable from v0 binary format `buf`.
+    /// Return the DataTable and # of bytes read or error on failure.
+    pub fn decode(buf: &[u8]) -> Result<(Self, usize), DecodeErr> {
+        Self::decode_with(buf, BinaryFormat::V0)
+    }
+    /// Decode a DataTable from `buf` in binary `format`.
+    /// Return the DataTable and # of bytes read or error on failure.
+    pub fn decode_with(buf: &[u8], format: BinaryFormat) -> Result<(Self, usize), DecodeErr> {
+        DataTableRef::decode_with(buf, format).map(|(table, read)| (table.into_owned(), read))
     }
 }
 
@@ -62,12 +96,13 @@ impl AsRef<[PactType]> for DataTable {
 mod tests {
     use super::*;
     use crate::types::{Numeric, StringLike};
+    use bit_reverse::ParallelReverse;
 
     #[test]
     fn it_encodes() {
         let table = DataTable::new(vec![
-            PactType::Numeric(Numeric(111)),
-            PactType::Numeric(Numeric(333)),
+            PactType::Numeric(Numeric(111.into())),
+            PactType::Numeric(Numeric(333.into())),
             PactType::StringLike(StringLike(b"testing".to_vec())),
         ]);
         let mut encoded: Vec<u8> = Vec::new();
@@ -102,13 +137,65 @@ mod tests {
         buf.extend("testing".as_bytes());
 
         let expected = DataTable::new(vec![
-            PactType::Numeric(Numeric(111)),
-            PactType::Numeric(Numeric(333)),
+            PactType::Numeric(Numeric(111.into())),
+            PactType::Numeric(Numeric(333.into())),
             PactType::StringLike(StringLike(b"testing".to_vec())),
         ]);
-        let (result, bytes_read) = DataTable::decode(buf.clone()).expect("it decodes");
+        let (result, bytes_read) = DataTable::decode(&buf).expect("it decodes");
 
         assert_eq!(result, expected);
         assert_eq!(bytes_read, buf.len() as usize);
     }
+
+    #[test]
+    fn it_round_trips_v1_with_more_than_255_entries() {
+        let table = DataTable::new(
+            (0..256_u32)
+                .map(|n| PactType::Numeric(Numeric(n.into())))
+                .collect(),
+        );
+        let mut encoded: Vec<u8> = Vec::new();
+        table.encode_with(BinaryFormat::V1, &mut encoded);
+
+        // 256 entries, all addressable by wide comparators, as a 2 byte compact length
+        assert_eq!(encoded[0].swap_bits(), 0b1000_0000);
+        assert_eq!(encoded[1].swap_bits(), 0b0000_0010);
+        assert_eq!(
+            DataTable::decode_with(&encoded, BinaryFormat::V1),
+            Ok((table, encoded.len()))
+        );
+    }
+
+    #[test]
+    fn it_fails_to_encode_too_many_entries_for_v0() {
+        let table = DataTable::new(vec![PactType::Bool(true); 256]);
+        let mut encoded: Vec<u8> = Vec::new();
+        assert_eq!(
+            table.try_encode(&mut encoded),
+            Err(EncodeErr::TooManyDataTableEntries(256))
+        );
+        assert!(encoded.is_empty());
+
+        assert_eq!(
+            table.try_encode_with(BinaryFormat::V1, &mut encoded),
+            Ok(())
+        );
+        let mut expected: Vec<u8> = Vec::new();
+        table.encode_with(BinaryFormat::V1, &mut expected);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn it_fails_to_encode_an_unrepresentable_entry() {
+        let table = DataTable::new(vec![
+            PactType::Bool(true),
+            PactType::StringLike(StringLike(vec![0; 300])),
+        ]);
+        let mut encoded: Vec<u8> = vec![1, 2, 3];
+        assert_eq!(
+            table.try_encode(&mut encoded),
+            Err(EncodeErr::StringTooLong(300))
+        );
+        assert_eq!(encoded, vec![1, 2, 3]);
+    }
 }
@@ -0,0 +1,323 @@
+// Copyright 2019 Centrality Investments Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Pact binary format versions, their length encodings and coding errors
+//!
+use crate::types::MAX_LIST_DEPTH;
+use alloc::vec::Vec;
+use bit_reverse::ParallelReverse;
+use core::fmt;
+
+/// A value which can not be represented in a binary format
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum EncodeErr {
+    /// A string is longer than the format's maximum length
+    StringTooLong(usize),
+    /// A list's encoding is longer than the format's maximum length
+    ListTooLong(usize),
+    /// The data table has more entries than the format's maximum length
+    TooManyDataTableEntries(usize),
+    /// The metadata section is longer than the format's maximum length
+    MetadataTooLong(usize),
+    /// Lists are nested deeper than `MAX_LIST_DEPTH`
+    TooDeeplyNested,
+}
+
+impl fmt::Display for EncodeErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeErr::StringTooLong(len) => write!(f, "string of {} bytes is too long", len),
+            EncodeErr::ListTooLong(len) => write!(f, "list of {} encoded bytes is too long", len),
+            EncodeErr::TooManyDataTableEntries(len) => {
+                write!(f, "data table of {} entries is too long", len)
+            }
+            EncodeErr::MetadataTooLong(len) => {
+                write!(f, "metadata of {} encoded bytes is too long", len)
+            }
+            EncodeErr::TooDeeplyNested => {
+                write!(f, "lists are nested deeper than {} levels", MAX_LIST_DEPTH)
+            }
+        }
+    }
+}
+
+/// A decoding error and the byte offset of the input where it occurred
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub struct DecodeErr {
+    pub offset: usize,
+    pub kind: DecodeErrKind,
+}
+
+impl DecodeErr {
+    /// Return a new decoding error at `offset`
+    pub fn new(offset: usize, kind: DecodeErrKind) -> Self {
+        DecodeErr { offset, kind }
+    }
+}
+
+/// The reason decoding failed
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum DecodeErrKind {
+    /// The input ended before a type ID byte
+    MissingTypeId,
+    /// The input ended before a complete length prefix
+    MissingLength,
+    /// A compact length prefix exceeds `u32`
+    LengthOverflow,
+    /// A compact length prefix has redundant trailing bytes
+    NonMinimalLength,
+    /// A length prefix exceeds the remaining input
+    Truncated,
+    /// A list element extends past the end of the list
+    ListLengthOverflow,
+    /// The type ID is not supported
+    UnsupportedTypeId(u8),
+    /// A numeric has an unsupported width for the format
+    InvalidNumericWidth,
+    /// A numeric has redundant trailing zero bytes
+    NonMinimalNumeric,
+    /// A signed numeric has an unsupported width for the format
+    InvalidSignedWidth,
+    /// A signed numeric has redundant sign bytes
+    NonMinimalSigned,
+    /// A bool is not 1 byte of 0 or 1
+    InvalidBool,
+    /// Lists are nested deeper than `DecodeLimits::max_depth`
+    TooDeep,
+    /// A list has more elements than `DecodeLimits::max_list_len`
+    ListTooLong,
+    /// A data table has more entries than `DecodeLimits::max_data_table_entries`
+    TooManyEntries,
+    /// The input is longer than `DecodeLimits::max_bytes`
+    TooManyBytes,
+    /// The bytecode is longer than `DecodeLimits::max_bytecode_len`
+    BytecodeTooLong,
+    /// The metadata section has an unsupported layout version
+    UnsupportedMetadataVersion(u8),
+    /// A metadata field is unknown, out of order, repeated or an empty list
+    InvalidMetadataField(u8),
+    /// A metadata string is not valid UTF-8
+    InvalidUtf8,
+}
+
+/// Resource limits applied while decoding untrusted input
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub struct DecodeLimits {
+    /// The deepest nesting of lists, at most `MAX_LIST_DEPTH`
+    pub max_depth: usize,
+    /// The most entries a data table may hold
+    pub max_data_table_entries: usize,
+    /// The most elements a list may hold
+    pub max_list_len: usize,
+    /// The most bytes an encoded contract may occupy
+    pub max_bytes: usize,
+    /// The most bytes of bytecode a contract may hold
+    pub max_bytecode_len: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_depth: MAX_LIST_DEPTH,
+            max_data_table_entries: 256,
+            max_list_len: 1024,
+            max_bytes: 64 * 1024,
+            max_bytecode_len: 4 * 1024,
+        }
+    }
+}
+
+/// A version of the pact binary format
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum BinaryFormat {
+    /// Single byte lengths and fixed width numerics
+    V0,
+    /// Compact lengths and minimal width numerics
+    V1,
+}
+
+impl BinaryFormat {
+    /// The most recent binary format version
+    pub const LATEST: BinaryFormat = BinaryFormat::V1;
+
+    /// Return the format's version byte
+    pub fn version(self) -> u8 {
+        match self {
+            BinaryFormat::V0 => 0,
+            BinaryFormat::V1 => 1,
+        }
+    }
+
+    /// Return the format identified by a version byte, if supported
+    pub fn from_version(version: u8) -> Option<Self> {
+        match version {
+            0 => Some(BinaryFormat::V0),
+            1 => Some(BinaryFormat::V1),
+            _ => None,
+        }
+    }
+
+    /// Return the largest length the format can encode
+    pub fn max_length(self) -> usize {
+        match self {
+            BinaryFormat::V0 => u8::MAX as usize,
+            BinaryFormat::V1 => u32::MAX as usize,
+        }
+    }
+
+    /// Return the number of bytes used to encode a `length` prefix
+    pub(crate) fn length_len(self, length: usize) -> usize {
+        match self {
+            BinaryFormat::V0 => 1,
+            BinaryFormat::V1 => {
+                let bits = usize::BITS - length.leading_zeros();
+                (bits as usize).div_ceil(7).max(1)
+            }
+        }
+    }
+
+    /// Encode a length prefix into `buf`
+    pub(crate) fn encode_length(self, length: usize, buf: &mut Vec<u8>) {
+        match self {
+            BinaryFormat::V0 => buf.push((length as u8).swap_bits()),
+            BinaryFormat::V1 => encode_compact(length, buf),
+        }
+    }
+
+    /// Decode a length prefix from `buf`
+    /// Returns (length, bytes read) or error on failure
+    pub(crate) fn decode_length(self, buf: &[u8]) -> Result<(usize, usize), DecodeErrKind> {
+        match self {
+            BinaryFormat::V0 => buf
+                .first()
+                .map(|b| (b.swap_bits() as usize, 1))
+                .ok_or(DecodeErrKind::MissingLength),
+            BinaryFormat::V1 => decode_compact(buf),
+        }
+    }
+}
+
+/// The most bytes a compact length may occupy, enough for a `u32`
+const MAX_COMPACT_BYTES: usize = 5;
+
+/// Encode `n` as an unsigned LEB128 integer: 7 bits per byte, least significant group first,
+/// with the high bit set on every byte except the last
+fn encode_compact(mut n: usize, buf: &mut Vec<u8>) {
+    loop {
+        let b = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            buf.push(b.swap_bits());
+            return;
+        }
+        buf.push((b | 0x80).swap_bits());
+    }
+}
+
+/// Decode an unsigned LEB128 integer from `buf`
+/// Returns (value, bytes read) or error on failure
+fn decode_compact(buf: &[u8]) -> Result<(usize, usize), DecodeErrKind> {
+    let mut n: u64 = 0;
+    for (i, encoded) in buf.iter().take(MAX_COMPACT_BYTES).enumerate() {
+        let b = encoded.swap_bits();
+        n |= u64::from(b & 0x7f) << (7 * i);
+        if b & 0x80 == 0 {
+            // Reject redundant trailing zero groups so each length has one encoding
+            if b == 0 && i > 0 {
+                return Err(DecodeErrKind::NonMinimalLength);
+            }
+            let n = u32::try_from(n).map_err(|_| DecodeErrKind::LengthOverflow)?;
+            return Ok((n as usize, i + 1));
+        }
+    }
+    if buf.len() < MAX_COMPACT_BYTES {
+        Err(DecodeErrKind::MissingLength)
+    } else {
+        Err(DecodeErrKind::LengthOverflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_encodes_compact_lengths() {
+        let tests: Vec<(usize, Vec<u8>)> = vec![
+            (0, vec![0x00]),
+            (127, vec![0x7f]),
+            (128, vec![0x80, 0x01]),
+            (272, vec![0x90, 0x02]),
+            (16_384, vec![0x80, 0x80, 0x01]),
+            (u32::MAX as usize, vec![0xff, 0xff, 0xff, 0xff, 0x0f]),
+        ];
+        for (length, encoded) in tests {
+            let encoded: Vec<u8> = encoded.into_iter().map(|b| b.swap_bits()).collect();
+            let mut buf = Vec::new();
+            BinaryFormat::V1.encode_length(length, &mut buf);
+            assert_eq!(buf, encoded);
+            assert_eq!(
+                BinaryFormat::V1.decode_length(&buf),
+                Ok((length, encoded.len()))
+            );
+        }
+    }
+
+    #[test]
+    fn it_counts_length_bytes() {
+        for length in [0, 1, 127, 128, 16_383, 16_384, u32::MAX as usize] {
+            for format in [BinaryFormat::V0, BinaryFormat::V1] {
+                let mut buf = Vec::new();
+                format.encode_length(length, &mut buf);
+                assert_eq!(format.length_len(length), buf.len());
+            }
+        }
+    }
+
+    #[test]
+    fn it_fails_with_malformed_compact_lengths() {
+        let tests: Vec<(Vec<u8>, DecodeErrKind)> = vec![
+            (vec![], DecodeErrKind::MissingLength),
+            (vec![0x80], DecodeErrKind::MissingLength),
+            (vec![0x80, 0x00], DecodeErrKind::NonMinimalLength),
+            (
+                vec![0xff, 0xff, 0xff, 0xff, 0x1f],
+                DecodeErrKind::LengthOverflow,
+            ),
+            (
+                vec![0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+                DecodeErrKind::LengthOverflow,
+            ),
+        ];
+        for (encoded, err) in tests {
+            let encoded: Vec<u8> = encoded.into_iter().map(|b| b.swap_bits()).collect();
+            assert_eq!(BinaryFormat::V1.decode_length(&encoded), Err(err));
+        }
+    }
+
+    #[test]
+    fn it_round_trips_version_bytes() {
+        for format in [BinaryFormat::V0, BinaryFormat::V1] {
+            assert_eq!(BinaryFormat::from_version(format.version()), Some(format));
+        }
+        assert_eq!(BinaryFormat::from_version(2), None);
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright 2019 Centrality Investments Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Contract metadata, descriptive information which does not affect evaluation
+//!
+use crate::types::{BinaryFormat, DecodeErr, DecodeErrKind, EncodeErr};
+use alloc::{string::String, vec::Vec};
+use bit_reverse::ParallelReverse;
+use core::str;
+
+/// Set on a contract's version byte when a metadata section follows the data table
+pub(crate) const METADATA_FLAG: u8 = 0b1000_0000;
+
+/// The layout version of the metadata section
+const METADATA_VERSION: u8 = 0;
+
+// Metadata field tags, fields are encoded in ascending tag order
+const TAG_PARAMETERS: u8 = 0;
+const TAG_MODULE: u8 = 1;
+const TAG_METHOD: u8 = 2;
+const TAG_DESCRIPTION: u8 = 3;
+const TAG_CLAUSES: u8 = 4;
+
+/// Descriptive information about a contract for display e.g. in wallets
+/// It is ignored by the interpreter and may be stripped from a contract without changing its ID
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Default, PartialEq)]
+pub struct Metadata {
+    /// Input parameter names in call order, without their leading `$`
+    pub parameters: Vec<String>,
+    /// The runtime module of the call the contract permits e.g. `generic-asset`
+    pub module: Option<String>,
+    /// The method of the call the contract permits e.g. `transfer`
+    pub method: Option<String>,
+    /// A free-text description
+    pub description: Option<String>,
+    /// Labels for the contract's clauses in order
+    pub clauses: Vec<String>,
+}
+
+impl Metadata {
+    /// Encode the metadata section into `buf`
+    pub(crate) fn encode(&self, buf: &mut Vec<u8>) {
+        let mut fields = Vec::from([METADATA_VERSION.swap_bits()]);
+        let format = BinaryFormat::V1;
+        let mut field = |tag: u8, payload: &[u8]| {
+            fields.push(tag.swap_bits());
+            format.encode_length(payload.len(), &mut fields);
+            fields.extend(payload);
+        };
+        if !self.parameters.is_empty() {
+            field(TAG_PARAMETERS, &encode_strings(&self.parameters));
+        }
+        if let Some(module) = &self.module {
+            field(TAG_MODULE, module.as_bytes());
+        }
+        if let Some(method) = &self.method {
+            field(TAG_METHOD, method.as_bytes());
+        }
+        if let Some(description) = &self.description {
+            field(TAG_DESCRIPTION, description.as_bytes());
+        }
+        if !self.clauses.is_empty() {
+            field(TAG_CLAUSES, &encode_strings(&self.clauses));
+        }
+        format.encode_length(fields.len(), buf);
+        buf.append(&mut fields);
+    }
+
+    /// Return the length of the metadata section following its length prefix
+    fn encoded_len(&self) -> usize {
+        let format = BinaryFormat::V1;
+        let field = |length: usize| 1 + format.length_len(length) + length;
+        let strings = |strings: &[String]| -> usize {
+            strings
+                .iter()
+                .map(|s| format.length_len(s.len()) + s.len())
+                .sum()
+        };
+        let text = |s: &Option<String>| s.as_ref().map_or(0, |s| field(s.len()));
+        let list = |l: &[String]| if l.is_empty() { 0 } else { field(strings(l)) };
+        1 + list(&self.parameters)
+            + text(&self.module)
+            + text(&self.method)
+            + text(&self.description)
+            + list(&self.clauses)
+    }
+
+    /// Check the metadata is representable i.e. its section and strings fit a compact length
+    pub(crate) fn check_encoding(&self) -> Result<(), EncodeErr> {
+        let max = BinaryFormat::V1.max_length();
+        let strings = self
+            .parameters
+            .iter()
+            .chain(self.module.iter())
+            .chain(self.method.iter())
+            .chain(self.description.iter())
+            .chain(self.clauses.iter());
+        for s in strings {
+            if s.len() > max {
+                return Err(EncodeErr::StringTooLong(s.len()));
+            }
+        }
+        let length = self.encoded_len();
+        if length > max {
+            return Err(EncodeErr::MetadataTooLong(length));
+        }
+        Ok(())
+    }
+}
+
+/// Encode `strings` as a sequence of length prefixed strings
+fn encode_strings(strings: &[String]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for s in strings {
+        BinaryFormat::V1.encode_length(s.len(), &mut buf);
+        buf.extend(s.as_bytes());
+    }
+    buf
+}
+
+/// A borrowed, validated metadata section
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy)]
+pub struct MetadataRef<'a> {
+    /// The fields following the metadata version
+    fields: &'a [u8],
+}
+
+impl<'a> MetadataRef<'a> {
+    /// Decode a metadata section from the start of `buf`
+    /// Return the metadata and # of bytes read or error on failure
+    pub(crate) fn decode(buf: &'a [u8]) -> Result<(Self, usize), DecodeErr> {
+        let format = BinaryFormat::V1;
+        let (length, mut offset) = format
+            .decode_length(buf)
+            .map_err(|kind| DecodeErr::new(0, kind))?;
+        let end = offset + length;
+        if end > buf.len() {
+            return Err(DecodeErr::new(0, DecodeErrKind::Truncated));
+        }
+        // The version is the first byte of the section
+        match buf[..end].get(offset).map(|v| v.swap_bits()) {
+            Some(METADATA_VERSION) => {}
+            Some(version) => {
+                return Err(DecodeErr::new(
+                    offset,
+                    DecodeErrKind::UnsupportedMetadataVersion(version),
+                ))
+            }
+            None => return Err(DecodeErr::new(offset, DecodeErrKind::Truncated)),
+        }
+        offset += 1;
+        let fields_start = offset;
+
+        let mut next_tag = TAG_PARAMETERS;
+        while offset < end {
+            let tag = buf[offset].swap_bits();
+            let invalid_field = DecodeErr::new(offset, DecodeErrKind::InvalidMetadataField(tag));
+            // Tags are known, ascending and unique
+            if tag < next_tag || tag > TAG_CLAUSES {
+                return Err(invalid_field);
+            }
+            next_tag = tag + 1;
+            let (length, read) = format
+                .decode_length(&buf[offset + 1..end])
+                .map_err(|kind| DecodeErr::new(offset + 1, kind))?;
+            let start = offset + 1 + read;
+            if start + length > end {
+                return Err(DecodeErr::new(offset + 1, DecodeErrKind::Truncated));
+            }
+            let payload = &buf[start..start + length];
+            match tag {
+                TAG_PARAMETERS | TAG_CLAUSES if payload.is_empty() => return Err(invalid_field),
+                TAG_PARAMETERS | TAG_CLAUSES => validate_strings(payload)
+                    .map_err(|err| DecodeErr::new(start + err.offset, err.kind))?,
+                _ => {
+                    str::from_utf8(payload)
+                        .map_err(|_| DecodeErr::new(start, DecodeErrKind::InvalidUtf8))?;
+                }
+            }
+            offset = start + length;
+        }
+
+        Ok((
+            Self {
+                fields: &buf[fields_start..end],
+            },
+            end,
+        ))
+    }
+
+    /// Return the payload of the field with `tag`, if present
+    fn field(&self, tag: u8) -> Option<&'a [u8]> {
+        let mut buf = self.fields;
+        while let Some(encoded) = buf.first() {
+            let (length, read) = BinaryFormat::V1.decode_length(&buf[1..]).ok()?;
+            let payload = buf.get(1 + read..1 + read + length)?;
+            if encoded.swap_bits() == tag {
+                return Some(payload);
+            }
+            buf = &buf[1 + read + length..];
+        }
+        None
+    }
+
+    /// Return the text field with `tag`, if present
+    fn text(&self, tag: u8) -> Option<&'a str> {
+        self.field(tag)
+            .and_then(|payload| str::from_utf8(payload).ok())
+    }
+
+    /// Return the input parameter names in call order
+    pub fn parameters(&self) -> StrIter<'a> {
+        StrIter(self.field(TAG_PARAMETERS).unwrap_or_default())
+    }
+
+    /// Return the runtime module of the call the contract permits, if any
+    pub fn module(&self) -> Option<&'a str> {
+        self.text(TAG_MODULE)
+    }
+
+    /// Return the method of the call the contract permits, if any
+    pub fn method(&self) -> Option<&'a str> {
+        self.text(TAG_METHOD)
+    }
+
+    /// Return the contract's description, if any
+    pub fn description(&self) -> Option<&'a str> {
+        self.text(TAG_DESCRIPTION)
+    }
+
+    /// Return the labels of the contract's clauses in order
+    pub fn clauses(&self) -> StrIter<'a> {
+        StrIter(self.field(TAG_CLAUSES).unwrap_or_default())
+    }
+
+    /// Copy the borrowed metadata into an owned `Metadata`
+    pub fn into_owned(self) -> Metadata {
+        Metadata {
+            parameters: self.parameters().map(String::from).collect(),
+            module: self.module().map(String::from),
+            method: self.method().map(String::from),
+            description: self.description().map(String::from),
+            clauses: self.clauses().map(String::from).collect(),
+        }
+    }
+}
+
+/// Check `buf` is a sequence of length prefixed UTF-8 strings
+fn validate_strings(mut buf: &[u8]) -> Result<(), DecodeErr> {
+    let mut offset = 0;
+    while !buf.is_empty() {
+        let (length, read) = BinaryFormat::V1
+            .decode_length(buf)
+            .map_err(|kind| DecodeErr::new(offset, kind))?;
+        let s = buf
+            .get(read..read + length)
+            .ok_or(DecodeErr::new(offset, DecodeErrKind::Truncated))?;
+        str::from_utf8(s).map_err(|_| DecodeErr::new(offset + read, DecodeErrKind::InvalidUtf8))?;
+        buf = &buf[read + length..];
+        offset += read + length;
+    }
+    Ok(())
+}
+
+/// An iterator over a validated sequence of metadata strings
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone)]
+pub struct StrIter<'a>(&'a [u8]);
+
+impl<'a> Iterator for StrIter<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (length, read) = BinaryFormat::V1.decode_length(self.0).ok()?;
+        let s = self.0.get(read..read + length)?;
+        self.0 = &self.0[read + length..];
+        str::from_utf8(s).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            parameters: vec!["payee".into(), "amount".into()],
+            module: Some("generic-asset".into()),
+            method: Some("transfer".into()),
+            description: Some("Pay alice up to 100 ✓".into()),
+            clauses: vec!["payee".into(), "".into()],
+        }
+    }
+
+    #[test]
+    fn it_round_trips() {
+        for metadata in [metadata(), Metadata::default()] {
+            let mut buf = Vec::new();
+            metadata.encode(&mut buf);
+            let (decoded, read) = MetadataRef::decode(&buf).expect("it decodes");
+            assert_eq!(read, buf.len());
+            assert_eq!(metadata.encoded_len(), buf.len() - 1);
+            assert_eq!(decoded.into_owned(), metadata);
+        }
+    }
+
+    #[test]
+    fn it_reads_fields_without_allocating() {
+        let mut buf = Vec::new();
+        metadata().encode(&mut buf);
+        let (decoded, _) = MetadataRef::decode(&buf).unwrap();
+        assert!(decoded.parameters().eq(["payee", "amount"]));
+        assert_eq!(decoded.module(), Some("generic-asset"));
+        assert_eq!(decoded.method(), Some("transfer"));
+        assert_eq!(decoded.description(), Some("Pay alice up to 100 ✓"));
+        assert!(decoded.clauses().eq(["payee", ""]));
+    }
+
+    #[test]
+    fn it_encodes_fields_by_tag() {
+        let metadata = Metadata {
+            method: Some("hi".into()),
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        metadata.encode(&mut buf);
+        let expected: Vec<u8> = [5, METADATA_VERSION, TAG_METHOD, 2]
+            .iter()
+            .map(|b| b.swap_bits())
+            .chain(*b"hi")
+            .collect();
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn it_fails_with_malformed_metadata() {
+        let header = |bytes: &[u8]| -> Vec<u8> { bytes.iter().map(|b| b.swap_bits()).collect() };
+        let tests: Vec<(Vec<u8>, DecodeErr)> = vec![
+            (vec![], DecodeErr::new(0, DecodeErrKind::MissingLength)),
+            (header(&[2, 0]), DecodeErr::new(0, DecodeErrKind::Truncated)),
+            (header(&[0]), DecodeErr::new(1, DecodeErrKind::Truncated)),
+            (header(&[0, 0]), DecodeErr::new(1, DecodeErrKind::Truncated)),
+            (
+                header(&[1, 1]),
+                DecodeErr::new(1, DecodeErrKind::UnsupportedMetadataVersion(1)),
+            ),
+            (
+                header(&[3, 0, 5, 0]),
+                DecodeErr::new(2, DecodeErrKind::InvalidMetadataField(5)),
+            ),
+            // Out of order
+            (
+                header(&[5, 0, 2, 0, 1, 0]),
+                DecodeErr::new(4, DecodeErrKind::InvalidMetadataField(1)),
+            ),
+            // Repeated
+            (
+                header(&[5, 0, 1, 0, 1, 0]),
+                DecodeErr::new(4, DecodeErrKind::InvalidMetadataField(1)),
+            ),
+            // An empty list is omitted rather than encoded
+            (
+                header(&[3, 0, 0, 0]),
+                DecodeErr::new(2, DecodeErrKind::InvalidMetadataField(0)),
+            ),
+            (
+                header(&[3, 0, 1, 2]),
+                DecodeErr::new(3, DecodeErrKind::Truncated),
+            ),
+            (
+                [header(&[4, 0, 3, 1]), vec![0xff]].concat(),
+                DecodeErr::new(4, DecodeErrKind::InvalidUtf8),
+            ),
+            (
+                [header(&[5, 0, 0, 2, 2]), vec![b'a']].concat(),
+                DecodeErr::new(4, DecodeErrKind::Truncated),
+            ),
+        ];
+        for (buf, err) in tests {
+            assert_eq!(MetadataRef::decode(&buf).map(|_| ()), Err(err), "{:?}", buf);
+        }
+    }
+}
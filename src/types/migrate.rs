@@ -0,0 +1,299 @@
+// Copyright 2019 Centrality Investments Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Migration of contracts between binary format versions
+//!
+//! A migrated contract is checked by evaluating it and the original over inputs generated from
+//! the original's data table: each value, its neighbours and list elements, plus a zero value of
+//! each type. Every combination is evaluated when there are at most `MAX_CASES`, otherwise a fixed
+//! pseudo-random sample of `MAX_CASES` combinations is evaluated.
+//!
+use crate::interpreter::{interpret, LoadSource, OpCode, OpLoad};
+use crate::types::{
+    BinaryFormatErr, ContractRef, EncodeErr, Numeric, PactType, PactTypeRef, Signed, StringLike,
+};
+use alloc::{vec, vec::Vec};
+
+/// The most input combinations evaluated when checking a migration
+const MAX_CASES: usize = 4096;
+
+/// A contract migration error
+#[cfg_attr(feature = "std", derive(Debug, PartialEq))]
+pub enum MigrateErr {
+    /// The original or migrated contract failed to decode
+    InvalidContract(BinaryFormatErr),
+    /// The contract can not be represented in the target format
+    Unrepresentable(EncodeErr),
+    /// The migrated contract evaluates differently for this input
+    Diverged(Vec<PactType>),
+}
+
+/// Check `migrated` evaluates identically to `original` over generated inputs
+pub(crate) fn check_equivalent(
+    original: &ContractRef,
+    migrated: &ContractRef,
+) -> Result<(), MigrateErr> {
+    let entries: Vec<PactType> = original
+        .data_table
+        .iter()
+        .map(PactTypeRef::into_owned)
+        .collect();
+    let candidates = candidates(&entries);
+    let input_count = input_count(original.bytecode);
+
+    // Evaluate every combination when there are few enough, otherwise a sample
+    let exhaustive = u32::try_from(input_count)
+        .ok()
+        .and_then(|count| candidates.len().checked_pow(count))
+        .filter(|total| *total <= MAX_CASES);
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut input = Vec::with_capacity(input_count);
+    for case in 0..exhaustive.unwrap_or(MAX_CASES) {
+        input.clear();
+        let mut remaining = case;
+        for _ in 0..input_count {
+            let choice = if exhaustive.is_some() {
+                let choice = remaining % candidates.len();
+                remaining /= candidates.len();
+                choice
+            } else {
+                next_random(&mut seed) as usize % candidates.len()
+            };
+            input.push(candidates[choice].clone());
+        }
+        let expected = interpret(&input, &original.data_table, original.bytecode);
+        let actual = interpret(&input, &migrated.data_table, migrated.bytecode);
+        if expected != actual {
+            return Err(MigrateErr::Diverged(input));
+        }
+    }
+    Ok(())
+}
+
+/// Return the number of inputs `bytecode` reads, up to the first malformed OpCode
+fn input_count(bytecode: &[u8]) -> usize {
+    let mut scanner = bytecode.iter();
+    let mut count = 0;
+    while let Ok(Some(op)) = OpCode::parse(&mut scanner) {
+        let (load, indices, term) = match op {
+            OpCode::COMP(comparator) => (comparator.load, comparator.indices, None),
+            OpCode::ARITH(arithmetic) => (
+                arithmetic.load,
+                arithmetic.indices,
+                (arithmetic.term.load_source == LoadSource::Input).then_some(arithmetic.term.index),
+            ),
+            OpCode::MASK(mask) => (
+                mask.load,
+                mask.indices,
+                (mask.term.load_source == LoadSource::Input).then_some(mask.term.index),
+            ),
+            OpCode::CONJ(_) | OpCode::GROUP(_) | OpCode::REQUIRE(_) => continue,
+        };
+        let highest = match load {
+            OpLoad::INPUT_VS_USER => indices.lhs,
+            OpLoad::INPUT_VS_INPUT => indices.lhs.max(indices.rhs),
+        };
+        let highest = term.map_or(highest, |term| highest.max(term));
+        count = count.max(usize::from(highest) + 1);
+    }
+    count
+}
+
+/// Return distinct input values which exercise comparisons against `entries`
+fn candidates(entries: &[PactType]) -> Vec<PactType> {
+    let mut values = vec![
+        PactType::StringLike(StringLike(Vec::new())),
+        PactType::Numeric(Numeric(0.into())),
+        PactType::Signed(Signed(0)),
+        PactType::Bool(false),
+        PactType::Bool(true),
+    ];
+    for entry in entries {
+        push_neighbours(entry, &mut values);
+    }
+    let mut distinct = Vec::with_capacity(values.len());
+    for value in values {
+        if !distinct.contains(&value) {
+            distinct.push(value);
+        }
+    }
+    distinct
+}
+
+/// Append `value` and the values either side of it to `values`
+fn push_neighbours(value: &PactType, values: &mut Vec<PactType>) {
+    match value {
+        PactType::StringLike(StringLike(s)) => {
+            let mut longer = s.clone();
+            longer.push(0);
+            values.push(PactType::StringLike(StringLike(longer)));
+        }
+        PactType::Numeric(Numeric(n)) => {
+            let neighbours = [n.checked_sub(1.into()), n.checked_add(1.into())];
+            values.extend(
+                neighbours
+                    .into_iter()
+                    .flatten()
+                    .map(|n| PactType::Numeric(Numeric(n))),
+            );
+        }
+        PactType::Signed(Signed(n)) => {
+            let neighbours = [n.checked_sub(1), n.checked_add(1)];
+            values.extend(
+                neighbours
+                    .into_iter()
+                    .flatten()
+                    .map(|n| PactType::Signed(Signed(n))),
+            );
+        }
+        PactType::List(elements) => {
+            for element in elements {
+                push_neighbours(element, values);
+            }
+        }
+        PactType::Bool(_) => {}
+    }
+    values.push(value.clone());
+}
+
+/// Return the next value of a xorshift generator
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{Arithmetic, Comparator, Conjunction, OpArith, OpComp, OpConj};
+    use crate::types::{Contract, DataTable};
+
+    fn encode(data_table: Vec<PactType>, ops: &[OpCode]) -> Vec<u8> {
+        let mut bytecode = Vec::new();
+        for op in ops {
+            op.compile(&mut bytecode);
+        }
+        let mut buf = Vec::new();
+        Contract {
+            data_table: DataTable::new(data_table),
+            bytecode,
+        }
+        .encode(&mut buf);
+        buf
+    }
+
+    #[test]
+    fn it_generates_neighbouring_inputs() {
+        let candidates = candidates(&[
+            PactType::Numeric(Numeric(0.into())),
+            PactType::List(vec![PactType::Signed(Signed(i128::MAX))]),
+        ]);
+        for value in [
+            PactType::Numeric(Numeric(1.into())),
+            PactType::Signed(Signed(i128::MAX - 1)),
+            PactType::Signed(Signed(i128::MAX)),
+            PactType::List(vec![PactType::Signed(Signed(i128::MAX))]),
+        ] {
+            assert!(candidates.contains(&value), "{:?}", value);
+        }
+        assert_eq!(
+            candidates
+                .iter()
+                .filter(|c| **c == PactType::Numeric(Numeric(0.into())))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn it_counts_inputs() {
+        let comparator = Comparator::new(OpComp::EQ);
+        let tests: Vec<(Vec<OpCode>, usize)> = vec![
+            (vec![], 0),
+            (vec![OpCode::COMP(comparator.indices(2, 7))], 3),
+            (
+                vec![
+                    OpCode::COMP(comparator),
+                    OpCode::CONJ(Conjunction::new(OpConj::AND)),
+                    OpCode::COMP(comparator.load(OpLoad::INPUT_VS_INPUT).indices(1, 4)),
+                ],
+                5,
+            ),
+            (
+                vec![OpCode::ARITH(
+                    Arithmetic::new(OpArith::ADD, OpComp::GT).term(LoadSource::Input, 6),
+                )],
+                7,
+            ),
+            (
+                vec![OpCode::ARITH(
+                    Arithmetic::new(OpArith::ADD, OpComp::GT).term(LoadSource::DataTable, 6),
+                )],
+                1,
+            ),
+        ];
+        for (ops, count) in tests {
+            let mut bytecode = Vec::new();
+            for op in ops {
+                op.compile(&mut bytecode);
+            }
+            assert_eq!(input_count(&bytecode), count);
+        }
+    }
+
+    #[test]
+    fn it_detects_divergence() {
+        let ops = [OpCode::COMP(Comparator::new(OpComp::GT))];
+        let original = encode(vec![PactType::Numeric(Numeric(5.into()))], &ops);
+        let migrated = encode(vec![PactType::Numeric(Numeric(6.into()))], &ops);
+        let original = ContractRef::decode(&original).unwrap();
+        let migrated = ContractRef::decode(&migrated).unwrap();
+
+        assert_eq!(check_equivalent(&original, &original), Ok(()));
+        // 6 > 5 but not 6 > 6
+        assert_eq!(
+            check_equivalent(&original, &migrated),
+            Err(MigrateErr::Diverged(vec![PactType::Numeric(Numeric(
+                6.into()
+            ))]))
+        );
+    }
+
+    #[test]
+    fn it_samples_many_inputs() {
+        // 8 inputs of 8 candidates have too many combinations to evaluate exhaustively
+        let ops: Vec<OpCode> = (0..8)
+            .flat_map(|i| {
+                [
+                    OpCode::COMP(Comparator::new(OpComp::EQ).indices(i, 0)),
+                    OpCode::CONJ(Conjunction::new(OpConj::OR)),
+                ]
+            })
+            .take(15)
+            .collect();
+        let original = encode(vec![PactType::Numeric(Numeric(7.into()))], &ops);
+        let migrated = encode(vec![PactType::Numeric(Numeric(8.into()))], &ops);
+        let original = ContractRef::decode(&original).unwrap();
+        let migrated = ContractRef::decode(&migrated).unwrap();
+        assert_eq!(candidates(&[PactType::Numeric(Numeric(7.into()))]).len(), 8);
+        assert!(matches!(
+            check_equivalent(&original, &migrated),
+            Err(MigrateErr::Diverged(input)) if input.len() == 8
+        ));
+    }
+}
@@ -16,16 +16,33 @@
 //!
 //! Type definitions for the Pact interpreter and compiler
 //!
+mod armor;
 mod base;
+mod borrowed;
 mod contract;
 mod data_table;
+mod format;
+mod metadata;
+mod migrate;
 pub mod opcode;
+#[cfg(feature = "scale")]
+mod scale;
+#[cfg(feature = "serde")]
+mod serialize;
 mod type_cast;
 
 // Create nice top level exports
-pub use base::{Numeric, PactType, StringLike};
-pub use contract::{BinaryFormatErr, Contract};
+pub use armor::ArmorErr;
+pub use base::{Numeric, PactType, Signed, StringLike, MAX_LIST_DEPTH};
+pub use borrowed::{ContractRef, DataTableRef, ListIter, ListRef, PactTypeRef, Table};
+pub use contract::{
+    AnnotatedContract, BinaryFormatErr, CanonicalErr, Contract, ContractId, VersionedContract,
+};
 pub use data_table::DataTable;
+pub use format::{BinaryFormat, DecodeErr, DecodeErrKind, DecodeLimits, EncodeErr};
+pub use metadata::{Metadata, MetadataRef, StrIter};
+pub use migrate::MigrateErr;
+pub use primitive_types::U256;
 pub mod traits {
     pub use super::type_cast::IntoPact;
 }
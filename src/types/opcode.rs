@@ -19,24 +19,57 @@
 use crate::interpreter::InterpErr;
 use alloc::vec::Vec;
 
-#[cfg(feature = "compiler")]
-pub use compiler::*;
-
 // OpCode masks
+const OP_PREFIX_MASK: u8 = 0b1100_0000;
 const OP_TYPE_MASK: u8 = 0b0010_0000;
 const OP_INVERT_MASK: u8 = 0b0001_0000;
 const OP_LOAD_MASK: u8 = 0b0000_1000;
+const OP_GROUP_MASK: u8 = 0b0000_1000;
 const OP_CONJ_MASK: u8 = 0b0000_1111;
+const OP_GROUP_OP_MASK: u8 = 0b0000_0111;
 const OP_COMP_MASK: u8 = 0b0000_0111;
 
+// OpCode prefixes, the width of a base OpCode's operands or an extension
+const OP_NARROW: u8 = 0b0000_0000;
+const OP_WIDE: u8 = 0b0100_0000;
+const OP_EXTENDED: u8 = 0b1000_0000;
+const OP_REQUIRE: u8 = 0b1100_0000;
+
+// The page of an extended or REQUIRE OpCode
+const OP_PAGE_MASK: u8 = 0b0011_1111;
+
 const INDEX_LHS_MASK: u8 = 0b1111_0000;
 const INDEX_RHS_MASK: u8 = 0b0000_1111;
 
 const INDEX_LHS_SHIFT: usize = 4;
 const INDEX_RHS_SHIFT: usize = 0;
 
+// The term byte of an arithmetic or masked comparator
+const TERM_OP_MASK: u8 = 0b1100_0000;
+const TERM_INPUT_MASK: u8 = 0b0010_0000;
+const TERM_INDEX_MASK: u8 = 0b0000_1111;
+// Bits which must be unset, in the narrow and wide forms
+const TERM_NARROW_RESERVED_MASK: u8 = 0b0001_0000;
+const TERM_WIDE_RESERVED_MASK: u8 = 0b0001_1111;
+
+const TERM_OP_SHIFT: usize = 6;
+
+// The operation of the masked equality comparator within `OpPage::BITMASK`
+const MASK_EQ: u8 = 2;
+
 /// Indicates whether the source of a load is an `Input`
 /// or stored on the compiled `DataTable`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(Clone, Copy, PartialEq)]
 pub enum LoadSource {
     Input,
@@ -44,6 +77,18 @@ pub enum LoadSource {
 }
 
 /// A source for a subject for comparison
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct SubjectSource {
     pub load_source: LoadSource,
     pub index: u8,
@@ -51,14 +96,38 @@ pub struct SubjectSource {
 
 /// Data structure which breaks down the anatomy of an OpCode
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(Clone, Copy, PartialEq)]
 pub enum OpCode {
     COMP(Comparator),
     CONJ(Conjunction),
+    GROUP(Group),
+    REQUIRE(OpPage),
+    ARITH(Arithmetic),
+    MASK(MaskEq),
 }
 
 /// Comparator OpCode Structure
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(Clone, Copy, PartialEq)]
 pub struct Comparator {
     pub load: OpLoad,
@@ -67,16 +136,122 @@ pub struct Comparator {
     pub invert: bool,
 }
 
+/// Arithmetic comparator OpCode Structure, from `OpPage::ARITH`
+/// Compares `input[indices.lhs] <op> <term>` with the RHS, which is loaded as for a `Comparator`
+/// The operands are `Numeric`s and arithmetic which overflows fails the contract
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Arithmetic {
+    pub load: OpLoad,
+    pub op: OpArith,
+    pub term: SubjectSource,
+    /// One of the base comparisons `EQ`, `GT` or `GTE`
+    pub comp: OpComp,
+    pub indices: OpIndices,
+    pub invert: bool,
+}
+
+/// Masked equality comparator OpCode Structure, `MASK_EQ` from `OpPage::BITMASK`
+/// Compares `input[indices.lhs] & <term>` with the RHS for equality, the RHS is loaded as for a
+/// `Comparator`. The operands are `Numeric`s
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct MaskEq {
+    pub load: OpLoad,
+    /// The mask
+    pub term: SubjectSource,
+    pub indices: OpIndices,
+    pub invert: bool,
+}
+
 /// Conjunction OpCode Structure
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(Clone, Copy, PartialEq)]
 pub struct Conjunction {
     pub op: OpConj,
     pub invert: bool,
 }
 
+/// Group OpCode Structure
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Group {
+    pub op: OpGroup,
+    pub invert: bool,
+}
+
+/// A page of extended OpCodes, an optional interpreter feature numbered from 1 to 63
+/// Page 0 holds the base OpCodes and is always supported
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "u8", into = "u8")
+)]
+#[cfg_attr(
+    feature = "scale",
+    derive(codec::Encode, codec::MaxEncodedLen, scale_info::TypeInfo)
+)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OpPage(u8);
+
+/// A set of OpCode pages
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct Features(u64);
+
 /// Comparator OpCode Structure
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(Clone, Copy, PartialEq)]
 pub struct OpIndices {
     pub lhs: u8,
@@ -87,6 +262,16 @@ pub struct OpIndices {
 /// is comparing input to datatable or input to input
 #[allow(non_camel_case_types)]
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(Clone, Copy, PartialEq)]
 pub enum OpLoad {
     INPUT_VS_USER,
@@ -106,12 +291,41 @@ impl From<u8> for OpLoad {
 /// Enum of avaliable comparator OpCode operations
 #[allow(non_camel_case_types)]
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(Clone, Copy, PartialEq)]
 pub enum OpComp {
     EQ,
     GT,
     GTE,
     IN,
+    /// `OpPage::STRING`, whether the LHS begins with the RHS
+    PREFIX,
+    /// `OpPage::STRING`, whether the LHS ends with the RHS
+    SUFFIX,
+    /// `OpPage::STRING`, whether the RHS occurs within the LHS
+    CONTAINS,
+    /// `OpPage::GLOB`, whether the LHS matches the glob pattern on the RHS
+    /// The pattern is always a data table entry i.e. loaded with `INPUT_VS_USER`
+    GLOB,
+    /// `OpPage::LENGTH`, whether the length of the LHS equals the RHS
+    LEN_EQ,
+    /// `OpPage::LENGTH`, whether the length of the LHS is greater than the RHS
+    LEN_GT,
+    /// `OpPage::LENGTH`, whether the length of the LHS is greater than or equal to the RHS
+    LEN_GTE,
+    /// `OpPage::BITMASK`, whether the LHS has every bit of the RHS mask set
+    MASK_ALL,
+    /// `OpPage::BITMASK`, whether the LHS has any bit of the RHS mask set
+    MASK_ANY,
 }
 
 impl From<u8> for OpComp {
@@ -126,9 +340,83 @@ impl From<u8> for OpComp {
     }
 }
 
+impl OpComp {
+    /// Return the extension page defining the operation, `None` for a base operation
+    pub fn page(self) -> Option<OpPage> {
+        match self {
+            OpComp::EQ | OpComp::GT | OpComp::GTE | OpComp::IN => None,
+            OpComp::PREFIX | OpComp::SUFFIX | OpComp::CONTAINS => Some(OpPage::STRING),
+            OpComp::GLOB => Some(OpPage::GLOB),
+            OpComp::LEN_EQ | OpComp::LEN_GT | OpComp::LEN_GTE => Some(OpPage::LENGTH),
+            OpComp::MASK_ALL | OpComp::MASK_ANY => Some(OpPage::BITMASK),
+        }
+    }
+
+    /// Return the operation with `code` on `page`, or the base operations if `page` is `None`
+    fn on_page(page: Option<OpPage>, code: u8) -> Option<Self> {
+        match (page, code) {
+            (None, 0) => Some(OpComp::EQ),
+            (None, 1) => Some(OpComp::GT),
+            (None, 2) => Some(OpComp::GTE),
+            (None, 3) => Some(OpComp::IN),
+            (Some(OpPage::STRING), 0) => Some(OpComp::PREFIX),
+            (Some(OpPage::STRING), 1) => Some(OpComp::SUFFIX),
+            (Some(OpPage::STRING), 2) => Some(OpComp::CONTAINS),
+            (Some(OpPage::GLOB), 0) => Some(OpComp::GLOB),
+            (Some(OpPage::LENGTH), 0) => Some(OpComp::LEN_EQ),
+            (Some(OpPage::LENGTH), 1) => Some(OpComp::LEN_GT),
+            (Some(OpPage::LENGTH), 2) => Some(OpComp::LEN_GTE),
+            (Some(OpPage::BITMASK), 0) => Some(OpComp::MASK_ALL),
+            (Some(OpPage::BITMASK), 1) => Some(OpComp::MASK_ANY),
+            _ => None,
+        }
+    }
+}
+
+/// Enum of avaliable arithmetic comparator operations
+#[allow(non_camel_case_types)]
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum OpArith {
+    ADD,
+    SUB,
+    MUL,
+}
+
+impl From<u8> for OpArith {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::ADD,
+            1 => Self::SUB,
+            2 => Self::MUL,
+            _ => Self::ADD,
+        }
+    }
+}
+
 /// Enum of avaliable conjunction OpCode operations
 #[allow(non_camel_case_types)]
 #[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
 #[derive(Clone, Copy, PartialEq)]
 pub enum OpConj {
     AND,
@@ -147,14 +435,67 @@ impl From<u8> for OpConj {
     }
 }
 
+/// Enum of avaliable group OpCode operations
+#[allow(non_camel_case_types)]
+#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "scale",
+    derive(
+        codec::Encode,
+        codec::Decode,
+        codec::MaxEncodedLen,
+        scale_info::TypeInfo
+    )
+)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum OpGroup {
+    OPEN,
+    CLOSE,
+}
+
+impl From<u8> for OpGroup {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::OPEN,
+            1 => Self::CLOSE,
+            _ => Self::OPEN,
+        }
+    }
+}
+
 impl OpCode {
     // Compiles the OpCode object into one or more bytes
+    // Comparators use the wide form only when an index does not fit into a nibble
+    // Extended comparators carry the width in the page byte which follows the OpCode
     pub fn compile(self, stream: &mut Vec<u8>) {
-        stream.push(self.into());
         match self {
-            OpCode::COMP(comparator) => stream.push(comparator.indices.into()),
-            _ => {}
-        };
+            OpCode::COMP(comparator) => {
+                let narrow = comparator.indices.is_narrow();
+                let width = if narrow { OP_NARROW } else { OP_WIDE };
+                match comparator.op.page() {
+                    Some(page) => stream.extend([self.into(), width | page.0]),
+                    None => stream.push(u8::from(self) | width),
+                }
+                if narrow {
+                    stream.push(comparator.indices.into());
+                } else {
+                    stream.push(comparator.indices.lhs);
+                    stream.push(comparator.indices.rhs);
+                }
+            }
+            OpCode::ARITH(arithmetic) => {
+                let term = u8::from(arithmetic.op) | u8::from(arithmetic.term.load_source);
+                let operands = (arithmetic.indices, term, arithmetic.term.index);
+                compile_with_term(self.into(), OpPage::ARITH, operands, stream);
+            }
+            OpCode::MASK(mask) => {
+                let term = u8::from(mask.term.load_source);
+                let operands = (mask.indices, term, mask.term.index);
+                compile_with_term(self.into(), OpPage::BITMASK, operands, stream);
+            }
+            OpCode::CONJ(_) | OpCode::GROUP(_) | OpCode::REQUIRE(_) => stream.push(self.into()),
+        }
     }
 
     /// Return the next OpCode by parsing an input byte stream
@@ -170,6 +511,35 @@ impl OpCode {
         // Check if the invert Bit is Set
         let invert = (index & OP_INVERT_MASK) == OP_INVERT_MASK;
 
+        // Check the prefix, only comparators have a wide form
+        let (wide, page) = match (index & OP_PREFIX_MASK, index & OP_TYPE_MASK) {
+            (OP_NARROW, _) => (false, None),
+            (OP_WIDE, 0) => (true, None),
+            (OP_REQUIRE, _) => {
+                let page =
+                    OpPage::new(index & OP_PAGE_MASK).ok_or(InterpErr::InvalidOpCode(*index))?;
+                return Ok(Some(OpCode::REQUIRE(page)));
+            }
+            (OP_EXTENDED, op_type) => {
+                let page_index = stream
+                    .next()
+                    .ok_or(InterpErr::UnexpectedEOI("expected page"))?;
+                let page = OpPage::new(page_index & OP_PAGE_MASK)
+                    .ok_or(InterpErr::InvalidOpCode(*index))?;
+                // The operands of an unsupported page are unknown, so parsing can not continue
+                if !Features::SUPPORTED.contains(page) {
+                    return Err(InterpErr::UnsupportedFeature(page.id()));
+                }
+                // Only comparators are extended so far
+                match (page_index & OP_PREFIX_MASK, op_type) {
+                    (OP_NARROW, 0) => (false, Some(page)),
+                    (OP_WIDE, 0) => (true, Some(page)),
+                    _ => return Err(InterpErr::InvalidOpCode(*index)),
+                }
+            }
+            _ => return Err(InterpErr::InvalidOpCode(*index)),
+        };
+
         // Check the Type of OpCode (0 ? comparator : conjunction)
         match index & OP_TYPE_MASK {
             0 => {
@@ -179,32 +549,101 @@ impl OpCode {
                     0 => OpLoad::INPUT_VS_USER,
                     _ => OpLoad::INPUT_VS_INPUT,
                 };
-                // Determine comparator operation
-                let op = match index & OP_COMP_MASK {
-                    0 => OpComp::EQ,
-                    1 => OpComp::GT,
-                    2 => OpComp::GTE,
-                    3 => OpComp::IN,
-                    _ => return Err(InterpErr::InvalidOpCode(*index)),
+                // Determine comparator operation, arithmetic comparators use a base operation
+                let arithmetic = page == Some(OpPage::ARITH);
+                // Masked equality is the bitmask operation which takes a term
+                let masked = page == Some(OpPage::BITMASK) && index & OP_COMP_MASK == MASK_EQ;
+                let op_page = if arithmetic { None } else { page };
+                let op = match OpComp::on_page(op_page, index & OP_COMP_MASK) {
+                    // Patterns are validated when a contract is decoded, so may not be inputs
+                    Some(OpComp::GLOB) if load == OpLoad::INPUT_VS_INPUT => {
+                        return Err(InterpErr::InvalidOpCode(*index))
+                    }
+                    Some(OpComp::IN) if arithmetic => return Err(InterpErr::InvalidOpCode(*index)),
+                    Some(op) => op,
+                    None if masked => OpComp::EQ,
+                    None => return Err(InterpErr::InvalidOpCode(*index)),
                 };
                 // Load indices from the stream
-                let indices = if let Some(i) = stream.next() {
-                    Ok(*i)
+                let mut next_index = || {
+                    stream
+                        .next()
+                        .copied()
+                        .ok_or(InterpErr::UnexpectedEOI("expected index"))
+                };
+                let indices = if wide {
+                    OpIndices {
+                        lhs: next_index()?,
+                        rhs: next_index()?,
+                    }
                 } else {
-                    Err(InterpErr::UnexpectedEOI("expected index"))
-                }?;
+                    let indices = next_index()?;
+                    OpIndices {
+                        lhs: (indices & INDEX_LHS_MASK) >> INDEX_LHS_SHIFT,
+                        rhs: (indices & INDEX_RHS_MASK) >> INDEX_RHS_SHIFT,
+                    }
+                };
+
+                if arithmetic || masked {
+                    // Load the arithmetic operation and term from the stream
+                    let term = next_index()?;
+                    let (reserved, term_index) = if wide {
+                        (term & TERM_WIDE_RESERVED_MASK, next_index()?)
+                    } else {
+                        (term & TERM_NARROW_RESERVED_MASK, term & TERM_INDEX_MASK)
+                    };
+                    let load_source = match term & TERM_INPUT_MASK {
+                        0 => LoadSource::DataTable,
+                        _ => LoadSource::Input,
+                    };
+                    let term_source = SubjectSource {
+                        load_source,
+                        index: term_index,
+                    };
+                    // The operation bits of a masked comparator's term are reserved
+                    let arith_op = match ((term & TERM_OP_MASK) >> TERM_OP_SHIFT, masked) {
+                        (_, _) if reserved != 0 => return Err(InterpErr::InvalidOpCode(*index)),
+                        (0, true) => {
+                            return Ok(Some(OpCode::MASK(MaskEq {
+                                load,
+                                term: term_source,
+                                indices,
+                                invert,
+                            })))
+                        }
+                        (0, false) => OpArith::ADD,
+                        (1, false) => OpArith::SUB,
+                        (2, false) => OpArith::MUL,
+                        _ => return Err(InterpErr::InvalidOpCode(*index)),
+                    };
+                    return Ok(Some(OpCode::ARITH(Arithmetic {
+                        load,
+                        op: arith_op,
+                        term: term_source,
+                        comp: op,
+                        indices,
+                        invert,
+                    })));
+                }
 
                 // form and return the comparator OpCode
                 Ok(Some(OpCode::COMP(Comparator {
-                    load: load,
-                    op: op,
-                    indices: OpIndices {
-                        lhs: (indices & INDEX_LHS_MASK) >> INDEX_LHS_SHIFT,
-                        rhs: (indices & INDEX_RHS_MASK) >> INDEX_RHS_SHIFT,
-                    },
-                    invert: invert,
+                    load,
+                    op,
+                    indices,
+                    invert,
                 })))
             }
+            _ if index & OP_GROUP_MASK == OP_GROUP_MASK => {
+                // Group, only an opening group may be inverted
+                let op = match (index & OP_GROUP_OP_MASK, invert) {
+                    (0, _) => OpGroup::OPEN,
+                    (1, false) => OpGroup::CLOSE,
+                    _ => return Err(InterpErr::InvalidOpCode(*index)),
+                };
+                // form and return the group OpCode
+                Ok(Some(OpCode::GROUP(Group { op, invert })))
+            }
             _ => {
                 // Conjunction
                 let op = match index & OP_CONJ_MASK {
@@ -214,10 +653,7 @@ impl OpCode {
                     _ => return Err(InterpErr::InvalidOpCode(*index)),
                 };
                 // form and return the conjunction OpCode
-                Ok(Some(OpCode::CONJ(Conjunction {
-                    op: op,
-                    invert: invert,
-                })))
+                Ok(Some(OpCode::CONJ(Conjunction { op, invert })))
             }
         }
     }
@@ -228,7 +664,7 @@ impl Comparator {
     pub fn new(op: OpComp) -> Self {
         Comparator {
             load: OpLoad::INPUT_VS_USER,
-            op: op,
+            op,
             indices: OpIndices { lhs: 0, rhs: 0 },
             invert: false,
         }
@@ -256,7 +692,8 @@ impl Comparator {
     // Update the `load` field based on a subject set
     // If lhs = `DataTable` and rhs = `Input`, we need to change sides so that
     // lhs = `Input` and rhs = `DataTable` as per the `OpCode` encoding spec
-    pub fn loads_from_subjects(mut self, lhs: SubjectSource, rhs: SubjectSource) -> Self {
+    // Returns `None` when the sides must change but the operation has no flipped equivalent
+    pub fn loads_from_subjects(mut self, lhs: SubjectSource, rhs: SubjectSource) -> Option<Self> {
         // Determine the Load Order
         let (load, flip) = match (lhs.load_source, rhs.load_source) {
             (LoadSource::Input, LoadSource::Input) => (OpLoad::INPUT_VS_INPUT, false),
@@ -272,36 +709,219 @@ impl Comparator {
         if flip {
             self.flip_indices()
         } else {
-            self
+            Some(self)
         }
     }
 
     // Flips the lhs and rhs indices and applies any necessary changes to the `op` and
     // `invert` parameters to keep the expressions consistent
-    pub fn flip_indices(mut self) -> Self {
-        self.indices = OpIndices {
-            lhs: self.indices.rhs,
-            rhs: self.indices.lhs,
-        };
+    // Returns `None` for operations with no flipped equivalent
+    pub fn flip_indices(mut self) -> Option<Self> {
         let (op, invert) = match self.op {
             OpComp::EQ => (self.op, self.invert),
-            OpComp::IN => (self.op, self.invert),
             OpComp::GT => (OpComp::GTE, !self.invert),
             OpComp::GTE => (OpComp::GT, !self.invert),
+            // `l & r != 0` is symmetric, `l & r == r` is not
+            OpComp::MASK_ANY => (self.op, self.invert),
+            OpComp::MASK_ALL => return None,
+            // Membership and string operations distinguish their operands
+            OpComp::IN | OpComp::PREFIX | OpComp::SUFFIX | OpComp::CONTAINS | OpComp::GLOB => {
+                return None
+            }
+            // Only the length of the LHS is taken
+            OpComp::LEN_EQ | OpComp::LEN_GT | OpComp::LEN_GTE => return None,
+        };
+        self.indices = OpIndices {
+            lhs: self.indices.rhs,
+            rhs: self.indices.lhs,
         };
         self.op = op;
         self.invert = invert;
+        Some(self)
+    }
+}
+
+impl Arithmetic {
+    // Constructor for `Arithmetic`, the term is the second input
+    pub fn new(op: OpArith, comp: OpComp) -> Self {
+        Arithmetic {
+            load: OpLoad::INPUT_VS_USER,
+            op,
+            term: SubjectSource {
+                load_source: LoadSource::Input,
+                index: 1,
+            },
+            comp,
+            indices: OpIndices { lhs: 0, rhs: 0 },
+            invert: false,
+        }
+    }
+
+    // Update the `load` field
+    pub fn load(mut self, load: OpLoad) -> Self {
+        self.load = load;
+        self
+    }
+
+    // Update the `indices` field
+    pub fn indices(mut self, lhs: u8, rhs: u8) -> Self {
+        self.indices.lhs = lhs;
+        self.indices.rhs = rhs;
+        self
+    }
+
+    // Update the `term` field
+    pub fn term(mut self, load_source: LoadSource, index: u8) -> Self {
+        self.term = SubjectSource { load_source, index };
+        self
+    }
+
+    // Update the `invert` field
+    pub fn invert(mut self) -> Self {
+        self.invert = true;
+        self
+    }
+}
+
+impl MaskEq {
+    // Constructor for `MaskEq`, masking the first input by the `term`
+    pub fn new(load_source: LoadSource, index: u8) -> Self {
+        MaskEq {
+            load: OpLoad::INPUT_VS_USER,
+            term: SubjectSource { load_source, index },
+            indices: OpIndices { lhs: 0, rhs: 0 },
+            invert: false,
+        }
+    }
+
+    // Update the `load` field
+    pub fn load(mut self, load: OpLoad) -> Self {
+        self.load = load;
+        self
+    }
+
+    // Update the `indices` field
+    pub fn indices(mut self, lhs: u8, rhs: u8) -> Self {
+        self.indices.lhs = lhs;
+        self.indices.rhs = rhs;
+        self
+    }
+
+    // Update the `invert` field
+    pub fn invert(mut self) -> Self {
+        self.invert = true;
         self
     }
 }
 
+impl OpIndices {
+    // Whether both indices fit into a nibble of the narrow index byte
+    pub fn is_narrow(&self) -> bool {
+        self.lhs <= INDEX_RHS_MASK && self.rhs <= INDEX_RHS_MASK
+    }
+}
+
+impl OpPage {
+    /// String comparators: `PREFIX`, `SUFFIX` and `CONTAINS`
+    pub const STRING: OpPage = OpPage(1);
+    /// Glob pattern matching: `GLOB`
+    pub const GLOB: OpPage = OpPage(2);
+    /// Length comparators: `LEN_EQ`, `LEN_GT` and `LEN_GTE`
+    pub const LENGTH: OpPage = OpPage(3);
+    /// Arithmetic comparators: `OpCode::ARITH`
+    pub const ARITH: OpPage = OpPage(4);
+    /// Bitmask comparators: `MASK_ALL`, `MASK_ANY` and `OpCode::MASK`
+    pub const BITMASK: OpPage = OpPage(5);
+
+    /// Return the page numbered `id`, if it is an extension page i.e. from 1 to 63
+    pub fn new(id: u8) -> Option<Self> {
+        (1..=OP_PAGE_MASK).contains(&id).then_some(OpPage(id))
+    }
+
+    /// Return the page number
+    pub fn id(self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<u8> for OpPage {
+    type Error = &'static str;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        OpPage::new(id).ok_or("OpCode pages are numbered from 1 to 63")
+    }
+}
+
+impl From<OpPage> for u8 {
+    fn from(page: OpPage) -> u8 {
+        page.0
+    }
+}
+
+impl Features {
+    /// The pages supported by this interpreter
+    pub const SUPPORTED: Features = Features(
+        1 << OpPage::STRING.0
+            | 1 << OpPage::GLOB.0
+            | 1 << OpPage::LENGTH.0
+            | 1 << OpPage::ARITH.0
+            | 1 << OpPage::BITMASK.0,
+    );
+
+    /// Return the set with `page` added
+    pub fn with(self, page: OpPage) -> Self {
+        Features(self.0 | 1 << page.0)
+    }
+
+    /// Return whether the set holds `page`
+    pub fn contains(self, page: OpPage) -> bool {
+        self.0 & 1 << page.0 != 0
+    }
+
+    /// Return whether the set holds no pages
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Return an iterator over the pages in ascending order
+    pub fn iter(self) -> impl Iterator<Item = OpPage> {
+        (1..=OP_PAGE_MASK)
+            .map(OpPage)
+            .filter(move |page| self.contains(*page))
+    }
+
+    /// Return the pages declared by the REQUIRE OpCodes which begin `bytecode`
+    /// The OpCodes which follow are not parsed, so this succeeds for contracts using
+    /// unsupported pages
+    pub fn required_by(bytecode: &[u8]) -> Result<Self, InterpErr> {
+        bytecode
+            .iter()
+            .take_while(|op| *op & OP_PREFIX_MASK == OP_REQUIRE)
+            .try_fold(Features::default(), |features, op| {
+                OpPage::new(op & OP_PAGE_MASK)
+                    .map(|page| features.with(page))
+                    .ok_or(InterpErr::InvalidOpCode(*op))
+            })
+    }
+}
+
 impl Conjunction {
     // Constructor for `Conjunction`
     pub fn new(op: OpConj) -> Self {
-        Conjunction {
-            op: op,
-            invert: false,
-        }
+        Conjunction { op, invert: false }
+    }
+
+    // Update the `invert` field
+    pub fn invert(mut self) -> Self {
+        self.invert = true;
+        self
+    }
+}
+
+impl Group {
+    // Constructor for `Group`
+    pub fn new(op: OpGroup) -> Self {
+        Group { op, invert: false }
     }
 
     // Update the `invert` field
@@ -343,40 +963,82 @@ mod compiler {
         }
     }
 
+    impl From<&ast::Group> for Group {
+        // Creates an opening `Group` from an `ast::Group` type
+        fn from(group: &ast::Group) -> Self {
+            if group.negated {
+                Group::new(OpGroup::OPEN).invert()
+            } else {
+                Group::new(OpGroup::OPEN)
+            }
+        }
+    }
+
     impl From<&ast::Conjunctive> for Conjunction {
         // Creates a `Conjunction` from an `ast::Conjunctive` type
         fn from(conjunctive: &ast::Conjunctive) -> Self {
             match conjunctive {
                 ast::Conjunctive::And => Conjunction::new(OpConj::AND),
                 ast::Conjunctive::Or => Conjunction::new(OpConj::OR),
+                ast::Conjunctive::Xor => Conjunction::new(OpConj::XOR),
             }
         }
     }
 }
 
-impl Into<u8> for OpLoad {
-    fn into(self) -> u8 {
-        match self {
+impl From<OpLoad> for u8 {
+    fn from(load: OpLoad) -> u8 {
+        match load {
             OpLoad::INPUT_VS_USER => 0,
             OpLoad::INPUT_VS_INPUT => OP_LOAD_MASK,
         }
     }
 }
 
-impl Into<u8> for OpComp {
-    fn into(self) -> u8 {
-        match self {
+impl From<OpComp> for u8 {
+    fn from(comp: OpComp) -> u8 {
+        match comp {
             OpComp::EQ => 0,
             OpComp::GT => 1,
             OpComp::GTE => 2,
             OpComp::IN => 3,
+            // Extended operations are numbered within their page
+            OpComp::PREFIX => 0,
+            OpComp::SUFFIX => 1,
+            OpComp::CONTAINS => 2,
+            OpComp::GLOB => 0,
+            OpComp::LEN_EQ => 0,
+            OpComp::LEN_GT => 1,
+            OpComp::LEN_GTE => 2,
+            OpComp::MASK_ALL => 0,
+            OpComp::MASK_ANY => 1,
         }
     }
 }
 
-impl Into<u8> for OpConj {
-    fn into(self) -> u8 {
-        match self {
+impl From<OpArith> for u8 {
+    fn from(arith: OpArith) -> u8 {
+        let op: u8 = match arith {
+            OpArith::ADD => 0,
+            OpArith::SUB => 1,
+            OpArith::MUL => 2,
+        };
+        op << TERM_OP_SHIFT
+    }
+}
+
+impl From<LoadSource> for u8 {
+    fn from(source: LoadSource) -> u8 {
+        match source {
+            LoadSource::DataTable => 0,
+            LoadSource::Input => TERM_INPUT_MASK,
+        }
+    }
+}
+
+impl From<OpConj> for u8 {
+    fn from(conj: OpConj) -> u8 {
+        match conj {
             OpConj::AND => 0,
             OpConj::OR => 1,
             OpConj::XOR => 2,
@@ -384,32 +1046,83 @@ impl Into<u8> for OpConj {
     }
 }
 
-impl Into<u8> for OpIndices {
-    fn into(self) -> u8 {
-        (self.lhs << INDEX_LHS_SHIFT) & INDEX_LHS_MASK
-            | (self.rhs << INDEX_RHS_SHIFT) & INDEX_RHS_MASK
+impl From<OpGroup> for u8 {
+    fn from(group: OpGroup) -> u8 {
+        match group {
+            OpGroup::OPEN => 0,
+            OpGroup::CLOSE => 1,
+        }
+    }
+}
+
+impl From<OpIndices> for u8 {
+    fn from(indices: OpIndices) -> u8 {
+        (indices.lhs << INDEX_LHS_SHIFT) & INDEX_LHS_MASK
+            | (indices.rhs << INDEX_RHS_SHIFT) & INDEX_RHS_MASK
     }
 }
 
 /// Convert an OpCode into its u8 bytecode
-impl Into<u8> for OpCode {
-    fn into(self) -> u8 {
-        match self {
+impl From<OpCode> for u8 {
+    fn from(opcode: OpCode) -> u8 {
+        match opcode {
             OpCode::COMP(comp) => {
                 let invert_u8: u8 = if comp.invert { OP_INVERT_MASK } else { 0 };
                 let load_u8: u8 = comp.load.into();
                 let comp_u8: u8 = comp.op.into();
-                invert_u8 | load_u8 | comp_u8
+                let prefix_u8: u8 = if comp.op.page().is_some() {
+                    OP_EXTENDED
+                } else {
+                    OP_NARROW
+                };
+                prefix_u8 | invert_u8 | load_u8 | comp_u8
             }
             OpCode::CONJ(conj) => {
                 let invert_u8: u8 = if conj.invert { OP_INVERT_MASK } else { 0 };
                 let conj_u8: u8 = conj.op.into();
                 OP_TYPE_MASK | invert_u8 | conj_u8
             }
+            OpCode::GROUP(group) => {
+                let invert_u8: u8 = if group.invert { OP_INVERT_MASK } else { 0 };
+                let group_u8: u8 = group.op.into();
+                OP_TYPE_MASK | OP_GROUP_MASK | invert_u8 | group_u8
+            }
+            OpCode::REQUIRE(page) => OP_REQUIRE | page.0,
+            OpCode::ARITH(arithmetic) => {
+                let invert_u8: u8 = if arithmetic.invert { OP_INVERT_MASK } else { 0 };
+                let load_u8: u8 = arithmetic.load.into();
+                let comp_u8: u8 = arithmetic.comp.into();
+                OP_EXTENDED | invert_u8 | load_u8 | comp_u8
+            }
+            OpCode::MASK(mask) => {
+                let invert_u8: u8 = if mask.invert { OP_INVERT_MASK } else { 0 };
+                let load_u8: u8 = mask.load.into();
+                OP_EXTENDED | invert_u8 | load_u8 | MASK_EQ
+            }
         }
     }
 }
 
+/// Compile an extended comparator on `page` which is followed by the indices and a term byte
+/// The term shares the narrow form with the indices when its index fits into a nibble
+fn compile_with_term(
+    op: u8,
+    page: OpPage,
+    (indices, term, term_index): (OpIndices, u8, u8),
+    stream: &mut Vec<u8>,
+) {
+    let narrow = indices.is_narrow() && term_index <= TERM_INDEX_MASK;
+    let width = if narrow { OP_NARROW } else { OP_WIDE };
+    stream.extend([op, width | page.0]);
+    if narrow {
+        stream.push(indices.into());
+        stream.push(term | term_index);
+    } else {
+        stream.extend([indices.lhs, indices.rhs]);
+        stream.extend([term, term_index]);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -446,6 +1159,21 @@ mod test {
         assert_eq!(bytes, vec![0x18, 0xb3]);
     }
 
+    #[test]
+    fn compile_comparator_wide() {
+        let mut bytes = Vec::<u8>::default();
+        OpCode::COMP(Comparator::new(OpComp::GT).indices(15, 15)).compile(&mut bytes);
+        OpCode::COMP(
+            Comparator::new(OpComp::GTE)
+                .load(OpLoad::INPUT_VS_INPUT)
+                .invert()
+                .indices(16, 2),
+        )
+        .compile(&mut bytes);
+        OpCode::COMP(Comparator::new(OpComp::EQ).indices(0, 255)).compile(&mut bytes);
+        assert_eq!(bytes, vec![0x01, 0xff, 0x5a, 0x10, 0x02, 0x40, 0x00, 0xff]);
+    }
+
     #[test]
     fn compile_conjunction_advanced() {
         let mut bytes = Vec::<u8>::default();
@@ -453,6 +1181,15 @@ mod test {
         assert_eq!(bytes, vec![0x31]);
     }
 
+    #[test]
+    fn compile_groups() {
+        let mut bytes = Vec::<u8>::default();
+        OpCode::GROUP(Group::new(OpGroup::OPEN)).compile(&mut bytes);
+        OpCode::GROUP(Group::new(OpGroup::OPEN).invert()).compile(&mut bytes);
+        OpCode::GROUP(Group::new(OpGroup::CLOSE)).compile(&mut bytes);
+        assert_eq!(bytes, vec![0x28, 0x38, 0x29]);
+    }
+
     #[test]
     fn parse_comparator_basic() {
         let mut stream = [0x00_u8, 0x00_u8].iter();
@@ -492,6 +1229,388 @@ mod test {
         );
     }
 
+    #[test]
+    fn parse_comparator_wide() {
+        let mut stream = [0x5a_u8, 0x10_u8, 0x02_u8].iter();
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::COMP(
+                Comparator::new(OpComp::GTE)
+                    .load(OpLoad::INPUT_VS_INPUT)
+                    .invert()
+                    .indices(16, 2)
+            ))
+        );
+        // The wide form is accepted for indices which would fit a nibble
+        let mut stream = [0x40_u8, 0x05_u8, 0x0c_u8].iter();
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::COMP(Comparator::new(OpComp::EQ).indices(5, 12)))
+        );
+    }
+
+    #[test]
+    fn parse_comparator_wide_missing_indices() {
+        let mut stream = [0x40_u8, 0x05_u8].iter();
+        assert_eq!(
+            OpCode::parse(&mut stream),
+            Err(InterpErr::UnexpectedEOI("expected index"))
+        );
+    }
+
+    #[test]
+    fn parse_reserved_width_invalid() {
+        for invalid in [0x80_u8, 0xc0_u8, 0x60_u8] {
+            let bytecode = [invalid, 0x00_u8, 0x00_u8];
+            assert_eq!(
+                OpCode::parse(&mut bytecode.iter()),
+                Err(InterpErr::InvalidOpCode(invalid))
+            );
+        }
+    }
+
+    #[test]
+    fn parse_require() {
+        let mut stream = [0xc1_u8, 0xff_u8].iter();
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::REQUIRE(OpPage::new(1).unwrap()))
+        );
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::REQUIRE(OpPage::new(63).unwrap()))
+        );
+
+        let mut bytes = Vec::new();
+        OpCode::REQUIRE(OpPage::new(5).unwrap()).compile(&mut bytes);
+        assert_eq!(bytes, vec![0xc5]);
+    }
+
+    #[test]
+    fn parse_extended_unsupported() {
+        // The page's width bits do not change the page
+        for page in [0x06_u8, 0x46_u8] {
+            let bytecode = [0x80_u8, page, 0x00_u8];
+            assert_eq!(
+                OpCode::parse(&mut bytecode.iter()),
+                Err(InterpErr::UnsupportedFeature(6))
+            );
+        }
+        assert_eq!(
+            OpCode::parse(&mut [0x80_u8].iter()),
+            Err(InterpErr::UnexpectedEOI("expected page"))
+        );
+    }
+
+    #[test]
+    fn compile_comparator_extended() {
+        let mut bytes = Vec::<u8>::default();
+        OpCode::COMP(Comparator::new(OpComp::PREFIX).indices(1, 2)).compile(&mut bytes);
+        OpCode::COMP(Comparator::new(OpComp::SUFFIX).invert()).compile(&mut bytes);
+        OpCode::COMP(
+            Comparator::new(OpComp::CONTAINS)
+                .load(OpLoad::INPUT_VS_INPUT)
+                .indices(16, 2),
+        )
+        .compile(&mut bytes);
+        assert_eq!(
+            bytes,
+            vec![0x80, 0x01, 0x12, 0x91, 0x01, 0x00, 0x8a, 0x41, 0x10, 0x02]
+        );
+    }
+
+    #[test]
+    fn parse_comparator_extended() {
+        let mut stream = [
+            0x80_u8, 0x01, 0x12, 0x91, 0x01, 0x00, 0x8a, 0x41, 0x10, 0x02,
+        ]
+        .iter();
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::COMP(Comparator::new(OpComp::PREFIX).indices(1, 2)))
+        );
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::COMP(Comparator::new(OpComp::SUFFIX).invert()))
+        );
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::COMP(
+                Comparator::new(OpComp::CONTAINS)
+                    .load(OpLoad::INPUT_VS_INPUT)
+                    .indices(16, 2)
+            ))
+        );
+        assert_eq!(OpComp::PREFIX.page(), Some(OpPage::STRING));
+        assert_eq!(OpComp::IN.page(), None);
+    }
+
+    #[test]
+    fn parse_comparator_glob() {
+        let glob = Comparator::new(OpComp::GLOB).invert().indices(2, 3);
+        let mut bytes = Vec::new();
+        OpCode::COMP(glob).compile(&mut bytes);
+        assert_eq!(bytes, vec![0x90, 0x02, 0x23]);
+        assert_eq!(
+            OpCode::parse(&mut bytes.iter()).unwrap(),
+            Some(OpCode::COMP(glob))
+        );
+        // Patterns may not be inputs
+        assert_eq!(
+            OpCode::parse(&mut [0x88_u8, 0x02, 0x23].iter()),
+            Err(InterpErr::InvalidOpCode(0x88))
+        );
+    }
+
+    #[test]
+    fn parse_comparator_length() {
+        let mut bytes = Vec::new();
+        OpCode::COMP(Comparator::new(OpComp::LEN_EQ)).compile(&mut bytes);
+        OpCode::COMP(Comparator::new(OpComp::LEN_GT).invert().indices(1, 2)).compile(&mut bytes);
+        OpCode::COMP(Comparator::new(OpComp::LEN_GTE).load(OpLoad::INPUT_VS_INPUT))
+            .compile(&mut bytes);
+        assert_eq!(
+            bytes,
+            vec![0x80, 0x03, 0x00, 0x91, 0x03, 0x12, 0x8a, 0x03, 0x00]
+        );
+
+        let mut stream = bytes.iter();
+        for op in [OpComp::LEN_EQ, OpComp::LEN_GT, OpComp::LEN_GTE] {
+            assert!(matches!(
+                OpCode::parse(&mut stream),
+                Ok(Some(OpCode::COMP(comparator))) if comparator.op == op
+            ));
+        }
+        assert_eq!(
+            OpCode::parse(&mut [0x83_u8, 0x03, 0x00].iter()),
+            Err(InterpErr::InvalidOpCode(0x83))
+        );
+    }
+
+    #[test]
+    fn parse_comparator_bitmask() {
+        let mut bytes = Vec::new();
+        OpCode::COMP(Comparator::new(OpComp::MASK_ALL).indices(0, 1)).compile(&mut bytes);
+        OpCode::COMP(Comparator::new(OpComp::MASK_ANY).invert().indices(2, 0)).compile(&mut bytes);
+        assert_eq!(bytes, vec![0x80, 0x05, 0x01, 0x91, 0x05, 0x20]);
+
+        let mut stream = bytes.iter();
+        for op in [OpComp::MASK_ALL, OpComp::MASK_ANY] {
+            assert!(matches!(
+                OpCode::parse(&mut stream),
+                Ok(Some(OpCode::COMP(comparator))) if comparator.op == op
+            ));
+        }
+        assert_eq!(
+            OpCode::parse(&mut [0x83_u8, 0x05, 0x00].iter()),
+            Err(InterpErr::InvalidOpCode(0x83))
+        );
+    }
+
+    #[test]
+    fn parse_mask_eq() {
+        let tests = vec![
+            (
+                MaskEq::new(LoadSource::DataTable, 1).indices(0, 2),
+                vec![0x82, 0x05, 0x02, 0x01],
+            ),
+            (
+                MaskEq::new(LoadSource::Input, 1)
+                    .load(OpLoad::INPUT_VS_INPUT)
+                    .invert(),
+                vec![0x9a, 0x05, 0x00, 0x21],
+            ),
+            // The wide form when any index does not fit into a nibble
+            (
+                MaskEq::new(LoadSource::DataTable, 16),
+                vec![0x82, 0x45, 0x00, 0x00, 0x00, 0x10],
+            ),
+        ];
+        for (mask, bytecode) in tests {
+            let mut bytes = Vec::new();
+            OpCode::MASK(mask).compile(&mut bytes);
+            assert_eq!(bytes, bytecode);
+            assert_eq!(
+                OpCode::parse(&mut bytecode.iter()),
+                Ok(Some(OpCode::MASK(mask)))
+            );
+        }
+
+        // The term operation bits and reserved bits
+        for bytecode in [
+            vec![0x82_u8, 0x05, 0x00, 0x41],
+            vec![0x82, 0x05, 0x00, 0x11],
+            vec![0x82, 0x45, 0x00, 0x00, 0x01, 0x01],
+        ] {
+            assert_eq!(
+                OpCode::parse(&mut bytecode.iter()),
+                Err(InterpErr::InvalidOpCode(0x82)),
+                "{:?}",
+                bytecode
+            );
+        }
+        assert_eq!(
+            OpCode::parse(&mut [0x82_u8, 0x05, 0x00].iter()),
+            Err(InterpErr::UnexpectedEOI("expected index"))
+        );
+    }
+
+    #[test]
+    fn parse_arithmetic() {
+        let tests = vec![
+            (
+                Arithmetic::new(OpArith::ADD, OpComp::GT).invert(),
+                vec![0x91, 0x04, 0x00, 0x21],
+            ),
+            (
+                Arithmetic::new(OpArith::MUL, OpComp::GTE)
+                    .load(OpLoad::INPUT_VS_INPUT)
+                    .indices(1, 2)
+                    .term(LoadSource::DataTable, 15),
+                vec![0x8a, 0x04, 0x12, 0x8f],
+            ),
+            // The wide form when any index does not fit into a nibble
+            (
+                Arithmetic::new(OpArith::SUB, OpComp::EQ).term(LoadSource::Input, 16),
+                vec![0x80, 0x44, 0x00, 0x00, 0x60, 0x10],
+            ),
+            (
+                Arithmetic::new(OpArith::SUB, OpComp::EQ).indices(0, 16),
+                vec![0x80, 0x44, 0x00, 0x10, 0x60, 0x01],
+            ),
+        ];
+        for (arithmetic, bytecode) in tests {
+            let mut bytes = Vec::new();
+            OpCode::ARITH(arithmetic).compile(&mut bytes);
+            assert_eq!(bytes, bytecode);
+            assert_eq!(
+                OpCode::parse(&mut bytecode.iter()),
+                Ok(Some(OpCode::ARITH(arithmetic)))
+            );
+        }
+    }
+
+    #[test]
+    fn parse_arithmetic_invalid() {
+        let invalid: Vec<Vec<u8>> = vec![
+            // IN is not an arithmetic comparison
+            vec![0x83, 0x04, 0x00, 0x21],
+            // An undefined arithmetic operation
+            vec![0x80, 0x04, 0x00, 0xe1],
+            // Reserved term bits
+            vec![0x80, 0x04, 0x00, 0x31],
+            vec![0x80, 0x44, 0x00, 0x00, 0x61, 0x01],
+        ];
+        for bytecode in invalid {
+            assert_eq!(
+                OpCode::parse(&mut bytecode.iter()),
+                Err(InterpErr::InvalidOpCode(bytecode[0])),
+                "{:?}",
+                bytecode
+            );
+        }
+        assert_eq!(
+            OpCode::parse(&mut [0x80_u8, 0x44, 0x00, 0x00, 0x60].iter()),
+            Err(InterpErr::UnexpectedEOI("expected index"))
+        );
+    }
+
+    #[test]
+    fn parse_comparator_extended_invalid() {
+        // An undefined operation, a reserved width and an extended conjunction
+        for bytecode in [
+            [0x83_u8, 0x01, 0x00],
+            [0x80, 0x81, 0x00],
+            [0xa0, 0x01, 0x00],
+        ] {
+            assert_eq!(
+                OpCode::parse(&mut bytecode.iter()),
+                Err(InterpErr::InvalidOpCode(bytecode[0]))
+            );
+        }
+        assert_eq!(
+            OpCode::parse(&mut [0x80_u8, 0x01].iter()),
+            Err(InterpErr::UnexpectedEOI("expected index"))
+        );
+    }
+
+    #[test]
+    fn flip_comparator_indices() {
+        assert_eq!(
+            Comparator::new(OpComp::GT).indices(1, 2).flip_indices(),
+            Some(Comparator::new(OpComp::GTE).invert().indices(2, 1))
+        );
+        assert_eq!(
+            Comparator::new(OpComp::EQ)
+                .invert()
+                .indices(1, 2)
+                .flip_indices(),
+            Some(Comparator::new(OpComp::EQ).invert().indices(2, 1))
+        );
+        for op in [
+            OpComp::IN,
+            OpComp::PREFIX,
+            OpComp::SUFFIX,
+            OpComp::CONTAINS,
+            OpComp::GLOB,
+            OpComp::LEN_EQ,
+            OpComp::LEN_GT,
+            OpComp::LEN_GTE,
+            OpComp::MASK_ALL,
+        ] {
+            assert_eq!(Comparator::new(op).indices(1, 2).flip_indices(), None);
+        }
+        assert_eq!(
+            Comparator::new(OpComp::MASK_ANY)
+                .indices(1, 2)
+                .flip_indices(),
+            Some(Comparator::new(OpComp::MASK_ANY).indices(2, 1))
+        );
+
+        // Only a data table LHS is flipped
+        let input = SubjectSource {
+            load_source: LoadSource::Input,
+            index: 1,
+        };
+        let data_table = SubjectSource {
+            load_source: LoadSource::DataTable,
+            index: 0,
+        };
+        let prefix = Comparator::new(OpComp::PREFIX);
+        assert_eq!(
+            prefix.loads_from_subjects(input, data_table),
+            Some(prefix.indices(1, 0))
+        );
+        assert_eq!(prefix.loads_from_subjects(data_table, input), None);
+    }
+
+    #[test]
+    fn features() {
+        let page = |id| OpPage::new(id).unwrap();
+        assert_eq!(OpPage::new(0), None);
+        assert_eq!(OpPage::new(64), None);
+
+        let features = Features::default().with(page(63)).with(page(1));
+        assert!(features.contains(page(1)) && features.contains(page(63)));
+        assert!(!features.contains(page(2)));
+        assert_eq!(features.iter().collect::<Vec<_>>(), vec![page(1), page(63)]);
+        assert!(Features::default().is_empty());
+
+        // Declarations are read without parsing the OpCodes which follow
+        assert_eq!(
+            Features::required_by(&[0xc1, 0xff, 0x80, 0x7f]),
+            Ok(features)
+        );
+        assert_eq!(
+            Features::required_by(&[0x00, 0xc1]),
+            Ok(Features::default())
+        );
+        assert_eq!(
+            Features::required_by(&[0xc1, 0xc0]),
+            Err(InterpErr::InvalidOpCode(0xc0))
+        );
+    }
+
     #[test]
     fn parse_comparator_invalid() {
         let mut stream = [0x07_u8, 0x00_u8].iter();
@@ -533,6 +1652,34 @@ mod test {
         );
     }
 
+    #[test]
+    fn parse_groups() {
+        let mut stream = [0x28_u8, 0x38_u8, 0x29_u8].iter();
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::GROUP(Group::new(OpGroup::OPEN)))
+        );
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::GROUP(Group::new(OpGroup::OPEN).invert()))
+        );
+        assert_eq!(
+            OpCode::parse(&mut stream).unwrap(),
+            Some(OpCode::GROUP(Group::new(OpGroup::CLOSE)))
+        );
+    }
+
+    #[test]
+    fn parse_group_invalid() {
+        for invalid in [0x2a_u8, 0x39_u8, 0x68_u8] {
+            let bytecode = [invalid];
+            assert_eq!(
+                OpCode::parse(&mut bytecode.iter()),
+                Err(InterpErr::InvalidOpCode(invalid))
+            );
+        }
+    }
+
     #[test]
     fn parse_conjunction_invalid() {
         let mut stream = [0x2f_u8].iter();
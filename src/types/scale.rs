@@ -0,0 +1,330 @@
+// Copyright 2019 Centrality Investments Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! SCALE codec support for pact types
+//!
+//! Most types derive their SCALE encoding. The exceptions are:
+//! - `PactType` decoding rejects lists nested deeper than `MAX_LIST_DEPTH`
+//! - `Contract` is encoded as its pact binary format bytes, so it is bounded by the
+//!   default `DecodeLimits` and has a `MaxEncodedLen` for use in runtime storage
+//! - `InterpErr` messages are recovered from the interpreter's known messages
+//! - `OpPage` decoding rejects page numbers outside of 1 to 63
+//!
+use crate::interpreter::{InterpErr, OpPage};
+use crate::types::{
+    BinaryFormat, Contract, DecodeLimits, Numeric, PactType, Signed, StringLike, MAX_LIST_DEPTH,
+};
+use alloc::{vec, vec::Vec};
+use codec::{Compact, CompactLen, Decode, Encode, Error, Input, MaxEncodedLen, Output};
+use scale_info::{build::Fields, Path, Type, TypeInfo};
+
+impl Decode for PactType {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        decode_pact_type(input, 0)
+    }
+}
+
+/// Decode a `PactType` enclosed by `depth` lists
+/// Variant indices match the derived encoding
+fn decode_pact_type<I: Input>(input: &mut I, depth: usize) -> Result<PactType, Error> {
+    match input.read_byte()? {
+        0 => StringLike::decode(input).map(PactType::StringLike),
+        1 => Numeric::decode(input).map(PactType::Numeric),
+        2 if depth == MAX_LIST_DEPTH => Err("PactType lists are nested too deeply".into()),
+        2 => {
+            let len = Compact::<u32>::decode(input)?.0;
+            // Grow as elements are read rather than trusting the length
+            let mut list = Vec::new();
+            for _ in 0..len {
+                list.push(decode_pact_type(input, depth + 1)?);
+            }
+            Ok(PactType::List(list))
+        }
+        3 => Signed::decode(input).map(PactType::Signed),
+        4 => bool::decode(input).map(PactType::Bool),
+        _ => Err("invalid PactType variant".into()),
+    }
+}
+
+impl Decode for OpPage {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        OpPage::new(input.read_byte()?).ok_or_else(|| "invalid OpPage".into())
+    }
+}
+
+impl Encode for Contract {
+    fn size_hint(&self) -> usize {
+        // The bytecode and data table are usually small, this only needs to be a hint
+        Compact::<u32>::max_encoded_len() + 2 + self.bytecode.len()
+    }
+
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        let mut buf = Vec::new();
+        self.encode_with(BinaryFormat::LATEST, &mut buf);
+        buf.encode_to(dest);
+    }
+}
+
+impl Decode for Contract {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let limits = DecodeLimits::default();
+        let len = Compact::<u32>::decode(input)?.0 as usize;
+        if len > limits.max_bytes {
+            return Err("pact contract is too long".into());
+        }
+        let mut buf = vec![0_u8; len];
+        input.read(&mut buf)?;
+        Contract::decode_with_limits(&buf, &limits).map_err(|_| "invalid pact contract".into())
+    }
+}
+
+impl MaxEncodedLen for Contract {
+    fn max_encoded_len() -> usize {
+        let max_bytes = DecodeLimits::default().max_bytes;
+        Compact::<u32>::compact_len(&(max_bytes as u32)) + max_bytes
+    }
+}
+
+impl TypeInfo for Contract {
+    type Identity = Self;
+
+    fn type_info() -> Type {
+        Type::builder()
+            .path(Path::new("Contract", "trn_pact::types::contract"))
+            .docs(&["A pact contract in the pact binary format"])
+            .composite(Fields::unnamed().field(|f| f.ty::<Vec<u8>>().type_name("Vec<u8>")))
+    }
+}
+
+/// Every message an `InterpErr` is raised with
+/// An error with a message missing from this list encodes but does not decode
+const INTERP_ERR_MESSAGES: [&str; 6] = [
+    "expected index",
+    "expected page",
+    "unclosed group",
+    "incomplete operation",
+    "invalid escape",
+    "pattern too long",
+];
+
+/// Decode an `InterpErr` message, returning its static equivalent
+fn decode_message<I: Input>(input: &mut I) -> Result<&'static str, Error> {
+    let message = Vec::<u8>::decode(input)?;
+    INTERP_ERR_MESSAGES
+        .iter()
+        .find(|m| m.as_bytes() == message.as_slice())
+        .copied()
+        .ok_or_else(|| "unknown InterpErr message".into())
+}
+
+impl Decode for InterpErr {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        // Variant indices match the derived encoding
+        match input.read_byte()? {
+            0 => Ok(InterpErr::TypeMismatch),
+            1 => Ok(InterpErr::BadTypeOperation),
+            2 => decode_message(input).map(InterpErr::UnexpectedEOI),
+            3 => u8::decode(input).map(InterpErr::UnexpectedOpCode),
+            4 => decode_message(input).map(InterpErr::UnsupportedOpCode),
+            5 => u8::decode(input).map(InterpErr::InvalidOpCode),
+            6 => u8::decode(input).map(InterpErr::MissingIndex),
+            7 => Ok(InterpErr::StackOverflow),
+            8 => Ok(InterpErr::Refused),
+            9 => u8::decode(input).map(InterpErr::UnsupportedFeature),
+            10 => u8::decode(input).map(InterpErr::UndeclaredFeature),
+            11 => decode_message(input).map(InterpErr::InvalidPattern),
+            _ => Err("invalid InterpErr variant".into()),
+        }
+    }
+}
+
+impl MaxEncodedLen for InterpErr {
+    fn max_encoded_len() -> usize {
+        let longest = INTERP_ERR_MESSAGES
+            .iter()
+            .map(|m| m.len())
+            .max()
+            .unwrap_or(0);
+        1 + Compact::<u32>::compact_len(&(longest as u32)) + longest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{interpret, Comparator, OpCode, OpComp, OpConj, OpLoad};
+    use crate::types::DataTable;
+
+    fn nested_list(depth: usize) -> PactType {
+        (0..depth).fold(PactType::Bool(true), |t, _| PactType::List(vec![t]))
+    }
+
+    #[test]
+    fn it_round_trips_pact_types() {
+        let tests = vec![
+            PactType::StringLike(StringLike(b"hello".to_vec())),
+            PactType::Numeric(Numeric(u64::MAX.into())),
+            PactType::Signed(Signed(-1)),
+            PactType::Bool(false),
+            PactType::List(vec![
+                PactType::Numeric(Numeric(1.into())),
+                PactType::List(vec![PactType::Signed(Signed(i128::MIN))]),
+            ]),
+            nested_list(MAX_LIST_DEPTH),
+        ];
+        for t in tests {
+            let encoded = Encode::encode(&t);
+            assert_eq!(<PactType as Decode>::decode(&mut &encoded[..]), Ok(t));
+        }
+    }
+
+    #[test]
+    fn it_uses_pact_type_ids_as_variant_indices() {
+        assert_eq!(
+            Encode::encode(&PactType::Signed(Signed(0)))[0],
+            3,
+            "derived encoding and manual decoding disagree"
+        );
+        assert_eq!(Encode::encode(&PactType::Bool(true)), vec![4, 1]);
+    }
+
+    #[test]
+    fn it_rejects_lists_nested_too_deeply() {
+        let encoded = Encode::encode(&nested_list(MAX_LIST_DEPTH + 1));
+        assert!(<PactType as Decode>::decode(&mut &encoded[..]).is_err());
+    }
+
+    #[test]
+    fn it_rejects_truncated_lists() {
+        // A list claiming u32::MAX elements followed by one
+        let mut encoded = vec![2];
+        Compact(u32::MAX).encode_to(&mut encoded);
+        encoded.extend([4, 1]);
+        assert!(<PactType as Decode>::decode(&mut &encoded[..]).is_err());
+    }
+
+    #[test]
+    fn it_round_trips_a_contract() {
+        let contract = Contract {
+            data_table: DataTable::new(vec![PactType::StringLike(StringLike(b"test".to_vec()))]),
+            bytecode: vec![
+                OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+                0x00,
+                OpCode::CONJ(crate::interpreter::Conjunction::new(OpConj::AND)).into(),
+                OpCode::COMP(Comparator::new(OpComp::EQ).load(OpLoad::INPUT_VS_INPUT)).into(),
+                0x01,
+            ],
+        };
+        let mut binary = Vec::new();
+        contract.encode_with(BinaryFormat::LATEST, &mut binary);
+
+        let encoded = Encode::encode(&contract);
+        assert_eq!(encoded, Encode::encode(&binary));
+        assert!(encoded.len() <= Contract::max_encoded_len());
+        assert_eq!(
+            <Contract as Decode>::decode(&mut &encoded[..]),
+            Ok(contract)
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_contracts() {
+        // Unsupported version
+        let encoded = Encode::encode(&vec![0xff_u8, 0x00]);
+        assert!(<Contract as Decode>::decode(&mut &encoded[..]).is_err());
+
+        // Longer than the decode limits, without reading the body
+        let mut encoded = Vec::new();
+        Compact(DecodeLimits::default().max_bytes as u32 + 1).encode_to(&mut encoded);
+        assert!(<Contract as Decode>::decode(&mut &encoded[..]).is_err());
+    }
+
+    #[test]
+    fn it_round_trips_evaluation_results() {
+        let input = [PactType::Numeric(Numeric(5.into()))];
+        let user = [PactType::Numeric(Numeric(5.into()))];
+        // An invalid glob pattern in a data table which was not decoded
+        let pattern = [PactType::StringLike(StringLike(b"a\\".to_vec()))];
+        let mut glob = Vec::new();
+        OpCode::REQUIRE(OpPage::GLOB).compile(&mut glob);
+        OpCode::COMP(Comparator::new(OpComp::GLOB)).compile(&mut glob);
+        let tests: Vec<Result<bool, InterpErr>> = vec![
+            interpret(
+                &input,
+                &user,
+                &[OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00],
+            ),
+            interpret(
+                &input,
+                &user,
+                &[OpCode::COMP(Comparator::new(OpComp::EQ)).into()],
+            ),
+            interpret(&input, &user, &[0x28]),
+            interpret(&input, &user, &[0x80]),
+            interpret(&input, &user, &[0xc1]),
+            interpret(&input, &user, &[0x80, 0x01, 0x00]),
+            Err(InterpErr::MissingIndex(3)),
+            interpret(&pattern, &pattern, &glob),
+        ];
+        assert_eq!(tests[0], Ok(true));
+        for result in tests {
+            let encoded = result.encode();
+            assert!(encoded.len() <= <Result<bool, InterpErr>>::max_encoded_len());
+            assert_eq!(Decode::decode(&mut &encoded[..]), Ok(result));
+        }
+    }
+
+    #[test]
+    fn it_round_trips_opcodes() {
+        for op in [
+            OpCode::COMP(Comparator::new(OpComp::IN).load(OpLoad::INPUT_VS_INPUT)),
+            OpCode::CONJ(crate::interpreter::Conjunction::new(OpConj::XOR)),
+            OpCode::REQUIRE(OpPage::new(63).unwrap()),
+        ] {
+            let encoded = op.encode();
+            assert!(encoded.len() <= OpCode::max_encoded_len());
+            assert_eq!(OpCode::decode(&mut &encoded[..]), Ok(op));
+        }
+        // Page 0 is not an extension page
+        assert!(OpPage::decode(&mut &[0_u8][..]).is_err());
+    }
+
+    #[test]
+    fn it_describes_types_for_metadata() {
+        let mut registry = scale_info::Registry::new();
+        registry.register_type(&scale_info::meta_type::<Contract>());
+        registry.register_type(&scale_info::meta_type::<DataTable>());
+        registry.register_type(&scale_info::meta_type::<Result<bool, InterpErr>>());
+        let registry: scale_info::PortableRegistry = registry.into();
+        let paths: Vec<_> = registry
+            .types
+            .iter()
+            .map(|t| t.ty.path.segments.join("::"))
+            .collect();
+        for path in [
+            "trn_pact::types::contract::Contract",
+            "trn_pact::types::data_table::DataTable",
+            "trn_pact::types::base::PactType",
+            "trn_pact::interpreter::InterpErr",
+        ] {
+            assert!(
+                paths.iter().any(|p| p == path),
+                "{} is not registered",
+                path
+            );
+        }
+    }
+}
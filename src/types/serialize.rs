@@ -0,0 +1,471 @@
+// Copyright 2019 Centrality Investments Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//!
+//! Serde support for pact types
+//!
+//! Human-readable formats (e.g. JSON) use:
+//! - UTF-8 strings as-is, other strings as `0x` prefixed lowercase hex
+//! - numerics as numbers within the JS safe integer range and decimal strings beyond it
+//!
+//! Binary formats use raw bytes and fixed width integers.
+//!
+use crate::types::{Numeric, Signed, StringLike, U256};
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// The largest integer a JS number represents exactly, `2^53 - 1`
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Write `bytes` as `0x` prefixed lowercase hex
+fn write_hex(bytes: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("0x")?;
+    for b in bytes {
+        write!(f, "{:02x}", b)?;
+    }
+    Ok(())
+}
+
+/// Parse `0x` prefixed hex into bytes
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    let digits = s.strip_prefix("0x")?.as_bytes();
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let hi = (pair[0] as char).to_digit(16)?;
+            let lo = (pair[1] as char).to_digit(16)?;
+            Some((hi * 16 + lo) as u8)
+        })
+        .collect()
+}
+
+/// Displays bytes as `0x` prefixed hex
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hex(self.0, f)
+    }
+}
+
+/// Serde `with` module for byte vectors, hex in human-readable formats
+pub(crate) mod hex_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&Hex(bytes))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor { text: false })
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor { text: false })
+        }
+    }
+}
+
+/// Visits bytes, or a hex string, or a UTF-8 string when `text` is set
+struct BytesVisitor {
+    text: bool,
+}
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.text {
+            f.write_str("a string or 0x prefixed hex")
+        } else {
+            f.write_str("0x prefixed hex")
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        if self.text && !s.starts_with("0x") {
+            return Ok(s.as_bytes().to_vec());
+        }
+        parse_hex(s).ok_or_else(|| E::invalid_value(de::Unexpected::Str(s), &self))
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+        Ok(bytes.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(bytes)
+    }
+}
+
+impl Serialize for StringLike {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return serializer.serialize_bytes(&self.0);
+        }
+        // Text which could be mistaken for hex is written as hex
+        match core::str::from_utf8(&self.0) {
+            Ok(s) if !s.starts_with("0x") => serializer.serialize_str(s),
+            _ => serializer.collect_str(&Hex(&self.0)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for StringLike {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor { text: true })
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor { text: false })
+        }
+        .map(StringLike)
+    }
+}
+
+impl Serialize for Numeric {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            let mut bytes = [0_u8; 32];
+            self.0.to_little_endian(&mut bytes);
+            return serializer.serialize_bytes(&bytes);
+        }
+        if self.0 <= U256::from(MAX_SAFE_INTEGER) {
+            serializer.serialize_u64(self.0.low_u64())
+        } else {
+            serializer.collect_str(&self.0)
+        }
+    }
+}
+
+/// Visits an unsigned integer, a decimal string or 32 little endian bytes
+struct NumericVisitor;
+
+impl<'de> Visitor<'de> for NumericVisitor {
+    type Value = Numeric;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an unsigned integer or decimal string of up to 256-bits")
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+        Ok(Numeric(n.into()))
+    }
+
+    fn visit_u128<E: de::Error>(self, n: u128) -> Result<Self::Value, E> {
+        Ok(Numeric(n.into()))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+        u64::try_from(n)
+            .map(|n| Numeric(n.into()))
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(n), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        // `from_dec_str` accepts an empty string as zero
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(E::invalid_value(de::Unexpected::Str(s), &self));
+        }
+        U256::from_dec_str(s)
+            .map(Numeric)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(s), &self))
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+        if bytes.len() != 32 {
+            return Err(E::invalid_length(bytes.len(), &self));
+        }
+        Ok(Numeric(U256::from_little_endian(bytes)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Numeric {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(NumericVisitor)
+        } else {
+            deserializer.deserialize_bytes(NumericVisitor)
+        }
+    }
+}
+
+impl Serialize for Signed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return serializer.serialize_i128(self.0);
+        }
+        if self.0.unsigned_abs() <= u128::from(MAX_SAFE_INTEGER) {
+            serializer.serialize_i64(self.0 as i64)
+        } else {
+            serializer.collect_str(&self.0)
+        }
+    }
+}
+
+/// Visits a signed integer or a decimal string
+struct SignedVisitor;
+
+impl<'de> Visitor<'de> for SignedVisitor {
+    type Value = Signed;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a signed integer or decimal string of up to 128-bits")
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Self::Value, E> {
+        Ok(Signed(n.into()))
+    }
+
+    fn visit_i128<E: de::Error>(self, n: i128) -> Result<Self::Value, E> {
+        Ok(Signed(n))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Self::Value, E> {
+        Ok(Signed(n.into()))
+    }
+
+    fn visit_u128<E: de::Error>(self, n: u128) -> Result<Self::Value, E> {
+        i128::try_from(n)
+            .map(Signed)
+            .map_err(|_| E::invalid_value(de::Unexpected::Other("integer out of range"), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        s.parse::<i128>()
+            .map(Signed)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(s), &self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Signed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(SignedVisitor)
+        } else {
+            deserializer.deserialize_i128(SignedVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{Comparator, OpCode, OpComp, OpConj, OpGroup, OpLoad, OpPage};
+    use crate::types::{
+        AnnotatedContract, Contract, DataTable, Metadata, Numeric, PactType, Signed, StringLike,
+        U256,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn it_serializes_strings_as_text_or_hex() {
+        let tests = vec![
+            (b"hello".to_vec(), json!("hello")),
+            (vec![0xde, 0xad, 0xbe, 0xef], json!("0xdeadbeef")),
+            (b"0x12".to_vec(), json!("0x30783132")),
+            (vec![], json!("")),
+        ];
+        for (bytes, expected) in tests {
+            let s = StringLike(bytes);
+            assert_eq!(serde_json::to_value(&s).unwrap(), expected);
+            assert_eq!(serde_json::from_value::<StringLike>(expected).unwrap(), s);
+        }
+        assert!(serde_json::from_value::<StringLike>(json!("0xabc")).is_err());
+        assert!(serde_json::from_value::<StringLike>(json!("0xzz")).is_err());
+    }
+
+    #[test]
+    fn it_serializes_numerics_beyond_the_safe_range_as_strings() {
+        let safe = (1_u64 << 53) - 1;
+        let tests = vec![
+            (Numeric(0.into()), json!(0)),
+            (Numeric(safe.into()), json!(safe)),
+            (Numeric((safe + 1).into()), json!("9007199254740992")),
+            (
+                Numeric(U256::MAX),
+                json!(
+                    "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+                ),
+            ),
+        ];
+        for (n, expected) in tests {
+            assert_eq!(serde_json::to_value(&n).unwrap(), expected);
+            assert_eq!(serde_json::from_value::<Numeric>(expected).unwrap(), n);
+        }
+        // Large numbers are accepted either way
+        assert_eq!(
+            serde_json::from_str::<Numeric>("9007199254740992").unwrap(),
+            Numeric((safe + 1).into())
+        );
+        for invalid in [json!(-1), json!(1.5), json!(""), json!("0x10"), json!("-1")] {
+            assert!(serde_json::from_value::<Numeric>(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn it_serializes_signed_numerics_beyond_the_safe_range_as_strings() {
+        let safe = (1_i128 << 53) - 1;
+        let tests = vec![
+            (Signed(-safe), json!(-9007199254740991_i64)),
+            (Signed(-safe - 1), json!("-9007199254740992")),
+            (Signed(safe + 1), json!("9007199254740992")),
+            (
+                Signed(i128::MIN),
+                json!("-170141183460469231731687303715884105728"),
+            ),
+        ];
+        for (n, expected) in tests {
+            assert_eq!(serde_json::to_value(&n).unwrap(), expected);
+            assert_eq!(serde_json::from_value::<Signed>(expected).unwrap(), n);
+        }
+        assert!(serde_json::from_value::<Signed>(json!("1e3")).is_err());
+    }
+
+    #[test]
+    fn it_serializes_a_contract() {
+        let contract = Contract {
+            data_table: DataTable::new(vec![
+                PactType::StringLike(StringLike(b"alice".to_vec())),
+                PactType::List(vec![
+                    PactType::Numeric(Numeric(1.into())),
+                    PactType::Signed(Signed(-1)),
+                    PactType::Bool(true),
+                ]),
+            ]),
+            bytecode: vec![0x00, 0x13],
+        };
+        let expected = json!({
+            "data_table": [
+                { "StringLike": "alice" },
+                { "List": [{ "Numeric": 1 }, { "Signed": -1 }, { "Bool": true }] },
+            ],
+            "bytecode": "0x0013",
+        });
+        assert_eq!(serde_json::to_value(&contract).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<Contract>(expected).unwrap(),
+            contract
+        );
+    }
+
+    #[test]
+    fn it_serializes_an_annotated_contract() {
+        let contract = || Contract {
+            data_table: DataTable::new(vec![PactType::Numeric(Numeric(1.into()))]),
+            bytecode: vec![0x00, 0x00],
+        };
+        let annotated = contract().with_metadata(Metadata {
+            parameters: vec!["amount".into()],
+            ..Default::default()
+        });
+        let expected = json!({
+            "data_table": [{ "Numeric": 1 }],
+            "bytecode": "0x0000",
+            "metadata": {
+                "parameters": ["amount"],
+                "module": null,
+                "method": null,
+                "description": null,
+                "clauses": [],
+            },
+        });
+        assert_eq!(serde_json::to_value(&annotated).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<AnnotatedContract>(expected).unwrap(),
+            annotated
+        );
+
+        // Metadata is optional
+        let expected = json!({ "data_table": [{ "Numeric": 1 }], "bytecode": "0x0000" });
+        assert_eq!(
+            serde_json::from_value::<AnnotatedContract>(expected).unwrap(),
+            AnnotatedContract {
+                contract: contract(),
+                metadata: None,
+            }
+        );
+    }
+
+    #[test]
+    fn it_serializes_opcodes() {
+        let tests = vec![
+            (
+                OpCode::COMP(Comparator::new(OpComp::GTE).load(OpLoad::INPUT_VS_INPUT)),
+                json!({ "COMP": {
+                    "load": "INPUT_VS_INPUT",
+                    "op": "GTE",
+                    "indices": { "lhs": 0, "rhs": 0 },
+                    "invert": false,
+                }}),
+            ),
+            (
+                OpCode::COMP(Comparator::new(OpComp::CONTAINS).invert()),
+                json!({ "COMP": {
+                    "load": "INPUT_VS_USER",
+                    "op": "CONTAINS",
+                    "indices": { "lhs": 0, "rhs": 0 },
+                    "invert": true,
+                }}),
+            ),
+            (
+                OpCode::CONJ(crate::interpreter::Conjunction {
+                    op: OpConj::XOR,
+                    invert: true,
+                }),
+                json!({ "CONJ": { "op": "XOR", "invert": true } }),
+            ),
+            (
+                OpCode::GROUP(crate::interpreter::Group {
+                    op: OpGroup::OPEN,
+                    invert: false,
+                }),
+                json!({ "GROUP": { "op": "OPEN", "invert": false } }),
+            ),
+            (
+                OpCode::REQUIRE(OpPage::new(5).unwrap()),
+                json!({ "REQUIRE": 5 }),
+            ),
+            (
+                OpCode::ARITH(crate::interpreter::Arithmetic::new(
+                    crate::interpreter::OpArith::MUL,
+                    OpComp::GTE,
+                )),
+                json!({ "ARITH": {
+                    "load": "INPUT_VS_USER",
+                    "op": "MUL",
+                    "term": { "load_source": "Input", "index": 1 },
+                    "comp": "GTE",
+                    "indices": { "lhs": 0, "rhs": 0 },
+                    "invert": false,
+                }}),
+            ),
+        ];
+        for (op, expected) in tests {
+            assert_eq!(serde_json::to_value(op).unwrap(), expected);
+            assert_eq!(serde_json::from_value::<OpCode>(expected).unwrap(), op);
+        }
+        // Page 0 is not an extension page
+        assert!(serde_json::from_value::<OpCode>(json!({ "REQUIRE": 0 })).is_err());
+    }
+}
@@ -16,20 +16,57 @@
 //!
 //! Type conversion traits and impls for `PactType`s
 //!
-use crate::types::{Numeric, PactType, StringLike};
-use core::convert::TryInto;
+use crate::types::{Numeric, PactType, Signed, StringLike};
+use core::convert::TryFrom;
+use primitive_types::U256;
 
 /// A blanket trait for conversion into PactType
+#[allow(clippy::result_unit_err)]
 pub trait IntoPact<I> {
     fn into_pact(self) -> Result<PactType, ()>;
 }
 
-/// Impl for all types that implement fallible conversion into u64
-// FIXME: impl Into<u128> after this is implemented https://github.com/cennznet/pact/issues/1
-impl<T: TryInto<u64> + Copy> IntoPact<&T> for T {
+/// Impl for unsigned integers, these always convert
+macro_rules! impl_into_pact_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl IntoPact<$t> for $t {
+                fn into_pact(self) -> Result<PactType, ()> {
+                    Ok(PactType::Numeric(Numeric(U256::from(self))))
+                }
+            }
+        )*
+    };
+}
+
+/// Impl for signed integers, these always convert
+macro_rules! impl_into_pact_signed {
+    ($($t:ty),*) => {
+        $(
+            impl IntoPact<$t> for $t {
+                fn into_pact(self) -> Result<PactType, ()> {
+                    Ok(PactType::Signed(Signed(i128::from(self))))
+                }
+            }
+        )*
+    };
+}
+
+impl_into_pact_unsigned!(u8, u16, u32, u64, u128, usize, U256);
+impl_into_pact_signed!(i8, i16, i32, i64, i128);
+
+/// `isize` is at most 64-bits on supported targets
+impl IntoPact<isize> for isize {
+    fn into_pact(self) -> Result<PactType, ()> {
+        let result = i128::try_from(self).map_err(|_| ())?;
+        Ok(PactType::Signed(Signed(result)))
+    }
+}
+
+/// Impl for bool
+impl IntoPact<bool> for bool {
     fn into_pact(self) -> Result<PactType, ()> {
-        let result: u64 = self.try_into().map_err(|_| ())?;
-        Ok(PactType::Numeric(Numeric(result)))
+        Ok(PactType::Bool(self))
     }
 }
 
@@ -48,17 +85,56 @@ mod tests {
     #[test]
     fn it_converts_numeric() {
         let tests = vec![
-            (0_u8.into_pact(), Ok(PactType::Numeric(Numeric(0)))),
-            (1_u16.into_pact(), Ok(PactType::Numeric(Numeric(1)))),
-            (2_u32.into_pact(), Ok(PactType::Numeric(Numeric(2)))),
-            (3_u64.into_pact(), Ok(PactType::Numeric(Numeric(3)))),
-            (4_u128.into_pact(), Ok(PactType::Numeric(Numeric(4)))),
+            (0_u8.into_pact(), Ok(PactType::Numeric(Numeric(0.into())))),
+            (1_u16.into_pact(), Ok(PactType::Numeric(Numeric(1.into())))),
+            (2_u32.into_pact(), Ok(PactType::Numeric(Numeric(2.into())))),
+            (3_u64.into_pact(), Ok(PactType::Numeric(Numeric(3.into())))),
+            (4_u128.into_pact(), Ok(PactType::Numeric(Numeric(4.into())))),
         ];
         for (lhs, rhs) in tests {
             assert_eq!(lhs, rhs);
         }
     }
 
+    #[test]
+    fn it_converts_signed() {
+        let tests = vec![
+            ((-1_i8).into_pact(), Ok(PactType::Signed(Signed(-1)))),
+            (2_i16.into_pact(), Ok(PactType::Signed(Signed(2)))),
+            ((-3_i32).into_pact(), Ok(PactType::Signed(Signed(-3)))),
+            (
+                i64::MIN.into_pact(),
+                Ok(PactType::Signed(Signed(i64::MIN.into()))),
+            ),
+            (
+                i128::MAX.into_pact(),
+                Ok(PactType::Signed(Signed(i128::MAX))),
+            ),
+            ((-6_isize).into_pact(), Ok(PactType::Signed(Signed(-6)))),
+        ];
+        for (lhs, rhs) in tests {
+            assert_eq!(lhs, rhs);
+        }
+    }
+
+    #[test]
+    fn it_converts_wide_numeric() {
+        assert_eq!(
+            u128::MAX.into_pact(),
+            Ok(PactType::Numeric(Numeric(U256::from(u128::MAX))))
+        );
+        assert_eq!(
+            U256::MAX.into_pact(),
+            Ok(PactType::Numeric(Numeric(U256::MAX)))
+        );
+    }
+
+    #[test]
+    fn it_converts_bool() {
+        assert_eq!(true.into_pact(), Ok(PactType::Bool(true)));
+        assert_eq!(false.into_pact(), Ok(PactType::Bool(false)));
+    }
+
     #[test]
     fn it_converts_string_like() {
         assert_eq!(
@@ -139,8 +215,8 @@ mod tests {
         let n64: <Bar as Foo>::Number64 = 20u64;
 
         let tests = vec![
-            (n32.into_pact(), Ok(PactType::Numeric(Numeric(10)))),
-            (n64.into_pact(), Ok(PactType::Numeric(Numeric(20)))),
+            (n32.into_pact(), Ok(PactType::Numeric(Numeric(10.into())))),
+            (n64.into_pact(), Ok(PactType::Numeric(Numeric(20.into())))),
         ];
         for (lhs, rhs) in tests {
             assert_eq!(lhs, rhs);
@@ -16,15 +16,22 @@
 //! Codec integration tests
 
 #![cfg(test)]
-use trn_pact::interpreter::{Comparator, OpCode, OpComp, OpIndices, OpLoad};
-use trn_pact::types::{BinaryFormatErr, Contract, DataTable, Numeric, PactType, StringLike};
+use bit_reverse::ParallelReverse;
+use trn_pact::interpreter::{
+    Comparator, Conjunction, Group, OpCode, OpComp, OpConj, OpGroup, OpIndices, OpLoad,
+};
+use trn_pact::types::{
+    AnnotatedContract, ArmorErr, BinaryFormat, BinaryFormatErr, Contract, DataTable, DecodeErr,
+    DecodeErrKind, DecodeLimits, Metadata, Numeric, PactType, Signed, StringLike,
+    VersionedContract,
+};
 
 #[test]
 fn contract_binary_format_codec() {
     let expected = Contract {
         data_table: DataTable::new(vec![
-            PactType::Numeric(Numeric(111)),
-            PactType::Numeric(Numeric(333)),
+            PactType::Numeric(Numeric::from(111)),
+            PactType::Numeric(Numeric::from(333)),
             PactType::StringLike(StringLike(b"testing".to_vec())),
         ]),
         bytecode: [
@@ -63,20 +70,262 @@ fn contract_binary_format_malformed_data_table() {
     let malformed_short: Vec<u8> = vec![0, 1];
     assert_eq!(
         Contract::decode(&malformed_short),
-        Err(BinaryFormatErr::MalformedDataTable("missing type ID byte"))
+        Err(BinaryFormatErr::MalformedDataTable(DecodeErr::new(
+            2,
+            DecodeErrKind::MissingTypeId
+        )))
     );
 
     let bad_type_id = vec![0, 0b1000_0000, 0b0000_0001, 0b0000_0000];
     assert_eq!(
         Contract::decode(&bad_type_id),
-        Err(BinaryFormatErr::MalformedDataTable("unsupported type ID"))
+        Err(BinaryFormatErr::MalformedDataTable(DecodeErr::new(
+            2,
+            DecodeErrKind::UnsupportedTypeId(128)
+        )))
     );
 
     let numeric_too_small = vec![0, 0b1000_0000, 0b1000_0000, 0b0100_0000, 0, 0];
     assert_eq!(
         Contract::decode(&numeric_too_small),
-        Err(BinaryFormatErr::MalformedDataTable(
-            "implementation only supports 64-bit numerics"
-        ))
+        Err(BinaryFormatErr::MalformedDataTable(DecodeErr::new(
+            3,
+            DecodeErrKind::InvalidNumericWidth
+        )))
     );
 }
+
+#[test]
+fn contract_binary_format_v1_long_allowlist() {
+    // Eight 32-byte account IDs exceed the 255 byte length of the v0 format
+    let allowlist: Vec<PactType> = (0..8_u8)
+        .map(|n| PactType::StringLike(StringLike([n; 32].to_vec())))
+        .collect();
+    let expected = Contract {
+        data_table: DataTable::new(vec![PactType::List(allowlist)]),
+        bytecode: [OpCode::COMP(Comparator::new(OpComp::IN)).into(), 0x00].to_vec(),
+    };
+
+    let mut buf: Vec<u8> = Vec::new();
+    expected.encode_with(BinaryFormat::V1, &mut buf);
+    assert_eq!(Contract::decode(&buf), Ok(expected));
+}
+
+#[test]
+fn contract_binary_format_v1_malformed_data_table() {
+    let version = BinaryFormat::V1.version().swap_bits();
+
+    // 1 entry, a string of length 128 with a truncated compact length
+    let truncated_length = vec![version, 0b1000_0000, 0, 0b0000_0001];
+    assert_eq!(
+        Contract::decode(&truncated_length),
+        Err(BinaryFormatErr::MalformedDataTable(DecodeErr::new(
+            3,
+            DecodeErrKind::MissingLength
+        )))
+    );
+
+    // 1 entry, a numeric with a trailing zero byte
+    let non_minimal = vec![version, 0b1000_0000, 0b1000_0000, 0b0100_0000, 1, 0];
+    assert_eq!(
+        Contract::decode(&non_minimal),
+        Err(BinaryFormatErr::MalformedDataTable(DecodeErr::new(
+            4,
+            DecodeErrKind::NonMinimalNumeric
+        )))
+    );
+}
+
+#[test]
+fn contract_binary_format_never_panics_on_truncation() {
+    let contract = Contract {
+        data_table: DataTable::new(vec![
+            PactType::StringLike(StringLike(b"testing".to_vec())),
+            PactType::List(vec![
+                PactType::Numeric(Numeric::from(300)),
+                PactType::List(vec![PactType::Bool(true)]),
+            ]),
+        ]),
+        bytecode: [OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00].to_vec(),
+    }
+    .with_metadata(Metadata {
+        parameters: vec!["a".into()],
+        description: Some("truncated".into()),
+        ..Default::default()
+    });
+    for format in [BinaryFormat::V0, BinaryFormat::V1] {
+        let mut buf: Vec<u8> = Vec::new();
+        contract.encode_with(format, &mut buf);
+        // Every prefix fails cleanly, or decodes with a shorter bytecode
+        for end in 0..buf.len() {
+            let _ = Contract::decode(&buf[..end]);
+        }
+    }
+    // Arbitrary bytes after each version byte, with and without metadata
+    for version in [0_u8, 0x80, 0x81] {
+        for b in 0..=u8::MAX {
+            let _ = Contract::decode(&[version, b, b, b, 0xff, 0xff, 0xff]);
+        }
+    }
+}
+
+#[test]
+fn contract_binary_format_decode_limits() {
+    let contract = Contract {
+        data_table: DataTable::new(vec![
+            PactType::Numeric(Numeric::from(1)),
+            PactType::List(vec![
+                PactType::Numeric(Numeric::from(1)),
+                PactType::Numeric(Numeric::from(2)),
+                PactType::List(vec![PactType::Numeric(Numeric::from(3))]),
+            ]),
+        ]),
+        bytecode: [OpCode::COMP(Comparator::new(OpComp::IN)).into(), 0x01].to_vec(),
+    };
+    let mut buf: Vec<u8> = Vec::new();
+    contract.encode_with(BinaryFormat::V1, &mut buf);
+    // version | table length | Numeric(1) | List header | Numeric(1) | Numeric(2) | List
+    let inner_list_offset = 1 + 1 + 3 + 2 + 3 + 3;
+    let limits = DecodeLimits::default();
+    assert_eq!(Contract::decode_with_limits(&buf, &limits), Ok(contract));
+
+    let tests = vec![
+        (
+            DecodeLimits {
+                max_bytes: 8,
+                ..limits
+            },
+            BinaryFormatErr::LimitExceeded(DecodeErr::new(8, DecodeErrKind::TooManyBytes)),
+        ),
+        (
+            DecodeLimits {
+                max_bytecode_len: 1,
+                ..limits
+            },
+            BinaryFormatErr::LimitExceeded(DecodeErr::new(
+                buf.len() - 1,
+                DecodeErrKind::BytecodeTooLong,
+            )),
+        ),
+        (
+            DecodeLimits {
+                max_data_table_entries: 1,
+                ..limits
+            },
+            BinaryFormatErr::MalformedDataTable(DecodeErr::new(1, DecodeErrKind::TooManyEntries)),
+        ),
+        (
+            DecodeLimits {
+                max_depth: 1,
+                ..limits
+            },
+            BinaryFormatErr::MalformedDataTable(DecodeErr::new(
+                inner_list_offset,
+                DecodeErrKind::TooDeep,
+            )),
+        ),
+        (
+            DecodeLimits {
+                max_list_len: 2,
+                ..limits
+            },
+            BinaryFormatErr::MalformedDataTable(DecodeErr::new(
+                inner_list_offset,
+                DecodeErrKind::ListTooLong,
+            )),
+        ),
+    ];
+    for (limits, err) in tests {
+        assert_eq!(Contract::decode_with_limits(&buf, &limits), Err(err));
+    }
+}
+
+#[test]
+fn contract_armor() {
+    let contract = || Contract {
+        data_table: DataTable::new(vec![PactType::Numeric(Numeric::from(5))]),
+        bytecode: [OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00].to_vec(),
+    };
+    // The armor format is stable
+    let armor = contract().to_armored().unwrap();
+    assert_eq!(armor, "pact1qszqgpq9qqqqqzessep");
+    assert_eq!(Contract::from_armored(&armor), Ok(contract()));
+    assert_eq!(
+        Contract::from_armored(&armor.to_uppercase()),
+        Ok(contract())
+    );
+
+    let annotated = || {
+        contract().with_metadata(Metadata {
+            parameters: vec!["amount".into()],
+            ..Default::default()
+        })
+    };
+    let armor = annotated().to_armored().unwrap();
+    assert_eq!(AnnotatedContract::from_armored(&armor), Ok(annotated()));
+    // Decoding a plain contract drops the metadata
+    assert_eq!(Contract::from_armored(&armor), Ok(contract()));
+
+    // A typo is detected rather than decoded as another contract
+    let typo = armor.replacen("pact1q", "pact1p", 1);
+    assert_eq!(
+        Contract::from_armored(&typo),
+        Err(ArmorErr::InvalidChecksum)
+    );
+}
+
+#[test]
+fn contract_migrate_v0_to_v1() {
+    let contract = || Contract {
+        data_table: DataTable::new(vec![
+            PactType::StringLike(StringLike(b"alice".to_vec())),
+            PactType::List(vec![
+                PactType::Numeric(Numeric::from(u64::MAX)),
+                PactType::Numeric(Numeric::from(0)),
+            ]),
+            PactType::Signed(Signed(-1)),
+        ]),
+        bytecode: [
+            OpCode::GROUP(Group::new(OpGroup::OPEN)),
+            OpCode::COMP(Comparator::new(OpComp::EQ)),
+            OpCode::CONJ(Conjunction::new(OpConj::OR)),
+            OpCode::COMP(Comparator::new(OpComp::IN).indices(1, 1)),
+            OpCode::GROUP(Group::new(OpGroup::CLOSE)),
+            OpCode::CONJ(Conjunction::new(OpConj::AND)),
+            OpCode::COMP(Comparator::new(OpComp::GT).invert().indices(2, 2)),
+        ]
+        .iter()
+        .flat_map(|op| {
+            let mut buf = Vec::new();
+            op.compile(&mut buf);
+            buf
+        })
+        .collect(),
+    };
+    let mut v0 = Vec::new();
+    contract().encode_with(BinaryFormat::V0, &mut v0);
+    let v1 = Contract::migrate(&v0, BinaryFormat::V1).expect("it migrates");
+
+    let mut expected = Vec::new();
+    contract().encode_with(BinaryFormat::V1, &mut expected);
+    assert_eq!(v1, expected);
+    assert_eq!(
+        Contract::decode_versioned(&v1),
+        Ok(VersionedContract {
+            format: BinaryFormat::V1,
+            contract: contract(),
+        })
+    );
+
+    // Metadata is dropped when migrating to v0
+    let mut labelled = Vec::new();
+    contract()
+        .with_metadata(Metadata {
+            description: Some("allowlist".into()),
+            ..Default::default()
+        })
+        .encode_with(BinaryFormat::V1, &mut labelled);
+    assert_eq!(Contract::migrate(&labelled, BinaryFormat::V0), Ok(v0));
+    // and kept otherwise
+    assert_eq!(Contract::migrate(&labelled, BinaryFormat::V1), Ok(labelled));
+}
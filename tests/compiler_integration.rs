@@ -0,0 +1,150 @@
+// Copyright 2023-2024 Futureverse Corporation Limited
+// This file is part of Pact.
+//
+// Licensed under the Apache License v2.0;
+// you may not use this file except in compliance with the License.
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// You should have received a copy of the Apache License v2.0
+// along with Pact. If not, see:
+//   <https://futureverse.com/licenses/apachev2.txt>
+
+//! Compiler integration tests
+
+#![cfg(all(test, feature = "compiler"))]
+use trn_pact::compiler::{compile, compile_annotated, decompile, decompile_annotated};
+use trn_pact::interpreter::{Comparator, OpCode, OpComp};
+use trn_pact::types::{Contract, DataTable, Numeric, PactType};
+
+/// Compile `source`, decompile it and check the result compiles to the same bytes
+fn assert_round_trip(source: &str) {
+    let contract = compile(source).expect("it compiles");
+    assert_eq!(
+        contract.check_canonical(),
+        Ok(()),
+        "compiler output is canonical"
+    );
+    let decompiled = decompile(&contract).expect("it decompiles");
+    let recompiled = compile(&decompiled).expect("decompiled source compiles");
+
+    let (mut expected, mut actual) = (Vec::new(), Vec::new());
+    contract.encode(&mut expected);
+    recompiled.encode(&mut actual);
+    assert_eq!(expected, actual, "round trip of:\n{}", decompiled);
+}
+
+#[test]
+fn it_round_trips_compiler_output() {
+    assert_round_trip(
+        r#"
+        given parameters $payee, $amount, $asset_id
+        define $limit as 1_000
+        $payee must be one of ["alice", "bob"] or $amount must be less than $limit
+        $amount must not be greater than or equal to $limit
+        $asset_id must be 0xdeadbeef xor $asset_id must not be equal to "tab\tquote\""
+        "#,
+    );
+    assert_round_trip(
+        r#"
+        given parameters $a, $b
+        $a must be less than or equal to $b and $b must not be one of [1, 2, 3]
+        $a must be greater than 5
+        "#,
+    );
+    assert_round_trip(
+        r#"
+        given parameters $a, $b
+        ($a must be 1 or not ($b must be 2 xor $b must be 3)) and $a must not be 4
+        not ($a must be one of [5, 6])
+        "#,
+    );
+
+    // Wide numerics
+    assert_round_trip(
+        r#"
+        given parameters $amount
+        $amount must be less than 340282366920938463463374607431768211456
+        $amount must not be equal to 115792089237316195423570985008687907853269984665640564039457584007913129639935
+        "#,
+    );
+
+    // Signed numerics
+    assert_round_trip(
+        r#"
+        given parameters $delta
+        $delta must be greater than -170141183460469231731687303715884105728
+        $delta must be one of [-1, +0, +1] or $delta must be less than or equal to +100
+        "#,
+    );
+
+    // Bools
+    assert_round_trip(
+        r#"
+        given parameters $keep_alive, $allow_burn
+        $keep_alive must be equal to true xor $allow_burn must not be one of [false]
+        "#,
+    );
+
+    // Nested lists
+    assert_round_trip(
+        r#"
+        given parameters $pair
+        $pair must be one of [[1, 2], [3, [4]], []]
+        "#,
+    );
+
+    // Wide indices
+    let parameters: Vec<String> = (0..20).map(|i| format!("$p{}", i)).collect();
+    let clauses: Vec<String> = (0..20)
+        .map(|i| format!("$p{} must be {}", 19 - i, i))
+        .collect();
+    assert_round_trip(&format!(
+        "given {}\n{}",
+        parameters.join(", "),
+        clauses.join("\n")
+    ));
+}
+
+#[test]
+fn it_decompiles_to_an_equivalent_contract() {
+    // The data table is out of order and contains an unused value
+    let mut bytecode = Vec::new();
+    OpCode::COMP(Comparator::new(OpComp::GT).indices(0, 1)).compile(&mut bytecode);
+    let contract = Contract {
+        data_table: DataTable::new(vec![
+            PactType::Numeric(Numeric::from(9)),
+            PactType::Numeric(Numeric::from(5)),
+        ]),
+        bytecode,
+    };
+
+    let recompiled = compile(&decompile(&contract).unwrap()).unwrap();
+    assert_eq!(
+        recompiled.data_table,
+        DataTable::new(vec![PactType::Numeric(Numeric::from(5))])
+    );
+    let mut expected = Vec::new();
+    OpCode::COMP(Comparator::new(OpComp::GT).indices(0, 0)).compile(&mut expected);
+    assert_eq!(recompiled.bytecode, expected);
+}
+
+#[test]
+fn it_records_parameter_names_as_metadata() {
+    let source = "given parameters $payee, $amount\n\n$payee must be equal to \"alice\" and $amount must be less than 5\n";
+    let annotated = compile_annotated(source).expect("it compiles");
+    assert_eq!(
+        annotated.metadata.as_ref().map(|m| m.parameters.clone()),
+        Some(vec!["payee".into(), "amount".into()])
+    );
+    assert_eq!(annotated.contract, compile(source).unwrap());
+
+    // Parameter names round trip through the metadata, otherwise they are named by index
+    assert_eq!(decompile_annotated(&annotated).unwrap(), source);
+    assert!(decompile(&annotated.contract)
+        .unwrap()
+        .starts_with("given parameters $input0, $input1\n"));
+}
@@ -18,19 +18,25 @@
 #![cfg(test)]
 use trn_pact::{
     interpreter::{self, InterpErr},
-    interpreter::{Comparator, Conjunction, OpCode, OpComp, OpConj, OpLoad},
-    types::{Numeric, PactType, StringLike},
+    interpreter::{
+        Arithmetic, Comparator, Conjunction, Features, Group, LoadSource, MaskEq, OpArith, OpCode,
+        OpComp, OpConj, OpGroup, OpLoad, OpPage,
+    },
+    types::{
+        BinaryFormat, Contract, ContractRef, DataTable, Metadata, Numeric, PactType, Signed,
+        StringLike, U256,
+    },
 };
 
 #[test]
 fn it_does_an_eq_comparison() {
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
@@ -48,10 +54,10 @@ fn it_does_an_eq_comparison() {
 #[test]
 fn it_does_a_not_eq_comparison() {
     let input = [
-        PactType::Numeric(Numeric(123)),
-        PactType::Numeric(Numeric(234)),
+        PactType::Numeric(Numeric::from(123)),
+        PactType::Numeric(Numeric::from(234)),
     ];
-    let user = [PactType::Numeric(Numeric(123))];
+    let user = [PactType::Numeric(Numeric::from(123))];
     let neq = OpCode::COMP(Comparator::new(OpComp::EQ).invert());
 
     let result = interpreter::interpret(&input, &user, &[neq.into(), 0x00]);
@@ -64,8 +70,8 @@ fn it_does_a_not_eq_comparison() {
 #[test]
 fn it_does_a_lt_comparison_ok() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(99))],
-        &[PactType::Numeric(Numeric(100))],
+        &[PactType::Numeric(Numeric::from(99))],
+        &[PactType::Numeric(Numeric::from(100))],
         &[
             // INPUT(1) < USER(1)
             OpCode::COMP(Comparator::new(OpComp::GTE).invert()).into(),
@@ -79,8 +85,8 @@ fn it_does_a_lt_comparison_ok() {
 #[test]
 fn it_does_an_lte_comparison_ok() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(100))],
-        &[PactType::Numeric(Numeric(100))],
+        &[PactType::Numeric(Numeric::from(100))],
+        &[PactType::Numeric(Numeric::from(100))],
         &[
             // INPUT(1) <= USER(1)
             OpCode::COMP(Comparator::new(OpComp::GT).invert()).into(),
@@ -94,8 +100,8 @@ fn it_does_an_lte_comparison_ok() {
 #[test]
 fn it_does_a_gt_comparison_ok() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(101))],
-        &[PactType::Numeric(Numeric(100))],
+        &[PactType::Numeric(Numeric::from(101))],
+        &[PactType::Numeric(Numeric::from(100))],
         &[
             // INPUT(1) > USER(1)
             OpCode::COMP(Comparator::new(OpComp::GT)).into(),
@@ -109,8 +115,8 @@ fn it_does_a_gt_comparison_ok() {
 #[test]
 fn it_does_a_gte_comparison_ok() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(100))],
-        &[PactType::Numeric(Numeric(100))],
+        &[PactType::Numeric(Numeric::from(100))],
+        &[PactType::Numeric(Numeric::from(100))],
         &[
             // INPUT(1) < USER(1)
             OpCode::COMP(Comparator::new(OpComp::GTE)).into(),
@@ -126,8 +132,8 @@ fn input_to_input_works() {
     let eq = OpCode::COMP(Comparator::new(OpComp::EQ).load(OpLoad::INPUT_VS_INPUT));
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
+            PactType::Numeric(Numeric::from(123)),
         ],
         &[],
         &[eq.into(), 0x01],
@@ -191,8 +197,8 @@ fn load_fails_with_missing_index() {
     assert_eq!(result, Err(InterpErr::MissingIndex(0)));
 
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(101))],
-        &[PactType::Numeric(Numeric(101))],
+        &[PactType::Numeric(Numeric::from(101))],
+        &[PactType::Numeric(Numeric::from(101))],
         &[OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x05],
     );
     assert_eq!(result, Err(InterpErr::MissingIndex(5)));
@@ -201,10 +207,10 @@ fn load_fails_with_missing_index() {
 #[test]
 fn load_input_to_input_fails_with_missing_index_2() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(123))],
+        &[PactType::Numeric(Numeric::from(123))],
         &[
-            PactType::Numeric(Numeric(123)),
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
+            PactType::Numeric(Numeric::from(123)),
         ],
         &[
             OpCode::COMP(Comparator::new(OpComp::EQ).load(OpLoad::INPUT_VS_INPUT)).into(),
@@ -233,11 +239,11 @@ fn it_fails_when_first_op_code_is_not_a_comparator() {
 fn it_does_an_and_conjunction_ok() {
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
@@ -255,11 +261,11 @@ fn it_does_an_and_conjunction_ok() {
 fn it_does_an_or_conjunction_ok() {
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(321)),
+            PactType::Numeric(Numeric::from(321)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
@@ -274,11 +280,11 @@ fn it_does_an_or_conjunction_ok() {
 
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
@@ -296,11 +302,11 @@ fn it_does_an_or_conjunction_ok() {
 fn it_does_a_xor_conjunction_ok() {
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(321)),
+            PactType::Numeric(Numeric::from(321)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
@@ -318,11 +324,11 @@ fn it_does_a_xor_conjunction_ok() {
 fn it_does_an_and_conjunction_evaluates_false() {
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(321)),
+            PactType::Numeric(Numeric::from(321)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
@@ -340,11 +346,11 @@ fn it_does_an_and_conjunction_evaluates_false() {
 fn it_does_an_or_conjunction_evaluates_false() {
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(321)),
+            PactType::Numeric(Numeric::from(321)),
             PactType::StringLike(StringLike(b"world hello".to_vec())),
         ],
         &[
@@ -362,11 +368,11 @@ fn it_does_an_or_conjunction_evaluates_false() {
 fn it_does_a_xor_conjunction_evaluates_false() {
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
@@ -383,8 +389,8 @@ fn it_does_a_xor_conjunction_evaluates_false() {
 #[test]
 fn it_fails_with_unexpected_end_of_input_no_rhs_of_conjunction() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(123))],
-        &[PactType::Numeric(Numeric(123))],
+        &[PactType::Numeric(Numeric::from(123))],
+        &[PactType::Numeric(Numeric::from(123))],
         &[
             OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
             0x00,
@@ -401,11 +407,11 @@ fn it_fails_with_unexpected_end_of_input_no_rhs_of_conjunction() {
 fn it_does_an_eq_comparison_evaluates_false() {
     let result = interpreter::interpret(
         &[
-            PactType::Numeric(Numeric(123)),
+            PactType::Numeric(Numeric::from(123)),
             PactType::StringLike(StringLike(b"hello world".to_vec())),
         ],
         &[
-            PactType::Numeric(Numeric(321)),
+            PactType::Numeric(Numeric::from(321)),
             PactType::StringLike(StringLike(b"world hello".to_vec())),
         ],
         &[
@@ -424,8 +430,8 @@ fn it_does_an_eq_comparison_evaluates_false() {
 #[test]
 fn it_does_an_lt_comparison_evaluates_false() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(100))],
-        &[PactType::Numeric(Numeric(99))],
+        &[PactType::Numeric(Numeric::from(100))],
+        &[PactType::Numeric(Numeric::from(99))],
         &[
             OpCode::COMP(Comparator::new(OpComp::GTE).invert()).into(),
             0x00,
@@ -438,8 +444,8 @@ fn it_does_an_lt_comparison_evaluates_false() {
 #[test]
 fn it_does_an_lte_comparison_evaluates_false() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(101))],
-        &[PactType::Numeric(Numeric(100))],
+        &[PactType::Numeric(Numeric::from(101))],
+        &[PactType::Numeric(Numeric::from(100))],
         &[
             OpCode::COMP(Comparator::new(OpComp::GT).invert()).into(),
             0x00,
@@ -452,8 +458,8 @@ fn it_does_an_lte_comparison_evaluates_false() {
 #[test]
 fn it_does_a_gt_comparison_evaluates_false() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(100))],
-        &[PactType::Numeric(Numeric(101))],
+        &[PactType::Numeric(Numeric::from(100))],
+        &[PactType::Numeric(Numeric::from(101))],
         &[OpCode::COMP(Comparator::new(OpComp::GT)).into(), 0x00],
     );
 
@@ -463,8 +469,8 @@ fn it_does_a_gt_comparison_evaluates_false() {
 #[test]
 fn it_does_a_gte_comparison_evaluates_false() {
     let result = interpreter::interpret(
-        &[PactType::Numeric(Numeric(100))],
-        &[PactType::Numeric(Numeric(101))],
+        &[PactType::Numeric(Numeric::from(100))],
+        &[PactType::Numeric(Numeric::from(101))],
         &[OpCode::COMP(Comparator::new(OpComp::GTE)).into(), 0x00],
     );
 
@@ -473,10 +479,13 @@ fn it_does_a_gte_comparison_evaluates_false() {
 
 #[test]
 fn it_does_a_numeric_in_comparison() {
-    let input_data = [PactType::Numeric(Numeric(2)), PactType::Numeric(Numeric(5))];
+    let input_data = [
+        PactType::Numeric(Numeric::from(2)),
+        PactType::Numeric(Numeric::from(5)),
+    ];
     let user_data = [PactType::List(vec![
-        PactType::Numeric(Numeric(1)),
-        PactType::Numeric(Numeric(2)),
+        PactType::Numeric(Numeric::from(1)),
+        PactType::Numeric(Numeric::from(2)),
     ])];
 
     let result = interpreter::interpret(
@@ -523,10 +532,13 @@ fn it_does_a_string_in_comparison() {
 #[test]
 fn it_fails_with_lhs_list_for_in_comparison() {
     let input_data = [PactType::List(vec![
-        PactType::Numeric(Numeric(1)),
-        PactType::Numeric(Numeric(2)),
+        PactType::Numeric(Numeric::from(1)),
+        PactType::Numeric(Numeric::from(2)),
     ])];
-    let user_data = [PactType::Numeric(Numeric(2)), PactType::Numeric(Numeric(5))];
+    let user_data = [
+        PactType::Numeric(Numeric::from(2)),
+        PactType::Numeric(Numeric::from(5)),
+    ];
 
     // List in Numeric
     let result = interpreter::interpret(
@@ -536,21 +548,60 @@ fn it_fails_with_lhs_list_for_in_comparison() {
     );
     assert_eq!(result, Err(InterpErr::BadTypeOperation));
 
-    // List in List
+    // List in List, the LHS is not an element of the RHS
     let result = interpreter::interpret(
         &input_data,
         &input_data,
         &[OpCode::COMP(Comparator::new(OpComp::IN)).into(), 0x00],
     );
+    assert_eq!(result, Ok(false));
+}
+
+#[test]
+fn it_does_an_in_comparison_with_nested_lists() {
+    let input_data = [
+        PactType::List(vec![
+            PactType::Numeric(Numeric::from(1)),
+            PactType::Numeric(Numeric::from(2)),
+        ]),
+        PactType::List(vec![PactType::Numeric(Numeric::from(1))]),
+        PactType::Numeric(Numeric::from(1)),
+    ];
+    let user_data = [PactType::List(vec![
+        PactType::List(vec![
+            PactType::Numeric(Numeric::from(1)),
+            PactType::Numeric(Numeric::from(2)),
+        ]),
+        PactType::List(vec![PactType::Numeric(Numeric::from(3))]),
+    ])];
+
+    let results: Vec<_> = [0x00, 0x10, 0x20]
+        .iter()
+        .map(|indices| {
+            interpreter::interpret(
+                &input_data,
+                &user_data,
+                &[OpCode::COMP(Comparator::new(OpComp::IN)).into(), *indices],
+            )
+        })
+        .collect();
+    assert_eq!(results, vec![Ok(true), Ok(false), Ok(false)]);
+
+    // Lists are only comparable by membership
+    let result = interpreter::interpret(
+        &input_data,
+        &input_data,
+        &[OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x01],
+    );
     assert_eq!(result, Err(InterpErr::BadTypeOperation));
 }
 
 #[test]
 fn it_does_an_in_comparison_with_a_mixed_list() {
-    let input_data = [PactType::Numeric(Numeric(1931))];
+    let input_data = [PactType::Numeric(Numeric::from(1931))];
     let user_data = [PactType::List(vec![
         PactType::StringLike(StringLike(b"It's alive! It's alive!".to_vec())),
-        PactType::Numeric(Numeric(1931)),
+        PactType::Numeric(Numeric::from(1931)),
     ])];
 
     let result = interpreter::interpret(
@@ -563,10 +614,13 @@ fn it_does_an_in_comparison_with_a_mixed_list() {
 
 #[test]
 fn it_does_a_not_in_comparison() {
-    let input_data = [PactType::Numeric(Numeric(2)), PactType::Numeric(Numeric(5))];
+    let input_data = [
+        PactType::Numeric(Numeric::from(2)),
+        PactType::Numeric(Numeric::from(5)),
+    ];
     let user_data = [PactType::List(vec![
-        PactType::Numeric(Numeric(1)),
-        PactType::Numeric(Numeric(2)),
+        PactType::Numeric(Numeric::from(1)),
+        PactType::Numeric(Numeric::from(2)),
     ])];
 
     let result = interpreter::interpret(
@@ -591,11 +645,12 @@ fn it_does_a_not_in_comparison() {
 }
 
 #[test]
+#[allow(clippy::clone_on_copy)]
 fn it_fails_for_invalid_list_operators() {
-    let input_data = [PactType::Numeric(Numeric(2))];
+    let input_data = [PactType::Numeric(Numeric::from(2))];
     let user_data = [PactType::List(vec![
-        PactType::Numeric(Numeric(1)),
-        PactType::Numeric(Numeric(2)),
+        PactType::Numeric(Numeric::from(1)),
+        PactType::Numeric(Numeric::from(2)),
     ])];
 
     let invalid_code_set = [
@@ -615,3 +670,1044 @@ fn it_fails_for_invalid_list_operators() {
         assert_eq!(result, Err(InterpErr::BadTypeOperation));
     }
 }
+
+#[test]
+fn it_does_a_wide_comparison() {
+    let mut input: Vec<PactType> = (0..20)
+        .map(|n| PactType::Numeric(Numeric::from(n)))
+        .collect();
+    input.push(PactType::Numeric(Numeric::from(3)));
+    let user: Vec<PactType> = (0..20)
+        .map(|n| PactType::Numeric(Numeric::from(n * 10)))
+        .collect();
+
+    let mut bytecode = Vec::new();
+    // INPUT(20) == INPUT(3)
+    OpCode::COMP(
+        Comparator::new(OpComp::EQ)
+            .load(OpLoad::INPUT_VS_INPUT)
+            .indices(20, 3),
+    )
+    .compile(&mut bytecode);
+    OpCode::CONJ(Conjunction::new(OpConj::AND)).compile(&mut bytecode);
+    // INPUT(19) < USER(19)
+    OpCode::COMP(Comparator::new(OpComp::GTE).invert().indices(19, 19)).compile(&mut bytecode);
+    assert_eq!(bytecode, vec![0x48, 20, 3, 0x20, 0x52, 19, 19]);
+
+    assert_eq!(interpreter::interpret(&input, &user, &bytecode), Ok(true));
+    assert_eq!(
+        interpreter::interpret(&input, &user[..10], &bytecode),
+        Err(InterpErr::MissingIndex(19))
+    );
+}
+
+/// Compile `ops` into bytecode
+fn bytecode(ops: &[OpCode]) -> Vec<u8> {
+    let mut bytecode = Vec::new();
+    for op in ops {
+        op.compile(&mut bytecode);
+    }
+    bytecode
+}
+
+#[test]
+fn it_evaluates_groups() {
+    let open = OpCode::GROUP(Group::new(OpGroup::OPEN));
+    let close = OpCode::GROUP(Group::new(OpGroup::CLOSE));
+    let and = OpCode::CONJ(Conjunction::new(OpConj::AND));
+    let or = OpCode::CONJ(Conjunction::new(OpConj::OR));
+    // INPUT(i) == USER(i)
+    let eq = |i| OpCode::COMP(Comparator::new(OpComp::EQ).indices(i, i));
+
+    let user = [
+        PactType::Numeric(Numeric::from(0)),
+        PactType::Numeric(Numeric::from(1)),
+        PactType::Numeric(Numeric::from(2)),
+        PactType::Numeric(Numeric::from(3)),
+    ];
+    // (a or b) and (c or d)
+    let grouped = bytecode(&[
+        open,
+        eq(0),
+        or,
+        eq(1),
+        close,
+        and,
+        open,
+        eq(2),
+        or,
+        eq(3),
+        close,
+    ]);
+    // a or b and c or d, folded left to right
+    let folded = bytecode(&[eq(0), or, eq(1), and, eq(2), or, eq(3)]);
+
+    // a, b, c, d = true, false, false, false
+    let input = [
+        PactType::Numeric(Numeric::from(0)),
+        PactType::Numeric(Numeric::from(0)),
+        PactType::Numeric(Numeric::from(0)),
+        PactType::Numeric(Numeric::from(0)),
+    ];
+    assert_eq!(interpreter::interpret(&input, &user, &grouped), Ok(false));
+    // ((a or b) and c) or d
+    assert_eq!(interpreter::interpret(&input, &user, &folded), Ok(false));
+
+    // a, b, c, d = false, false, false, true
+    let input = [
+        PactType::Numeric(Numeric::from(9)),
+        PactType::Numeric(Numeric::from(9)),
+        PactType::Numeric(Numeric::from(9)),
+        PactType::Numeric(Numeric::from(3)),
+    ];
+    assert_eq!(interpreter::interpret(&input, &user, &grouped), Ok(false));
+    assert_eq!(interpreter::interpret(&input, &user, &folded), Ok(true));
+
+    // a, b, c, d = false, true, true, false
+    let input = [
+        PactType::Numeric(Numeric::from(9)),
+        PactType::Numeric(Numeric::from(1)),
+        PactType::Numeric(Numeric::from(2)),
+        PactType::Numeric(Numeric::from(9)),
+    ];
+    assert_eq!(interpreter::interpret(&input, &user, &grouped), Ok(true));
+}
+
+#[test]
+fn it_evaluates_negated_and_nested_groups() {
+    let input = [PactType::Numeric(Numeric::from(5))];
+    let user = [PactType::Numeric(Numeric::from(5))];
+    let eq = OpCode::COMP(Comparator::new(OpComp::EQ));
+    let neq = OpCode::COMP(Comparator::new(OpComp::EQ).invert());
+    let open = OpCode::GROUP(Group::new(OpGroup::OPEN));
+    let not = OpCode::GROUP(Group::new(OpGroup::OPEN).invert());
+    let close = OpCode::GROUP(Group::new(OpGroup::CLOSE));
+    let or = OpCode::CONJ(Conjunction::new(OpConj::OR));
+
+    // not (5 != 5)
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[not, neq, close])),
+        Ok(true)
+    );
+    // 5 != 5 or not ((5 == 5) or 5 != 5)
+    assert_eq!(
+        interpreter::interpret(
+            &input,
+            &user,
+            &bytecode(&[neq, or, not, open, eq, close, or, neq, close])
+        ),
+        Ok(false)
+    );
+    // A failed group fails the contract as an independent clause
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[eq, open, neq, close, eq])),
+        Ok(false)
+    );
+}
+
+#[test]
+fn it_fails_on_malformed_groups() {
+    let input = [PactType::Numeric(Numeric::from(5))];
+    let user = [PactType::Numeric(Numeric::from(5))];
+    let eq = OpCode::COMP(Comparator::new(OpComp::EQ));
+    let open = OpCode::GROUP(Group::new(OpGroup::OPEN));
+    let close = OpCode::GROUP(Group::new(OpGroup::CLOSE));
+
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[open, eq])),
+        Err(InterpErr::UnexpectedEOI("unclosed group"))
+    );
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[eq, close])),
+        Err(InterpErr::UnexpectedOpCode(0x29))
+    );
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[open, close])),
+        Err(InterpErr::UnexpectedOpCode(0x29))
+    );
+    // Assertions within a group must be joined by a conjunction
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[open, eq, eq, close])),
+        Err(InterpErr::UnexpectedOpCode(0x00))
+    );
+
+    let mut nested = vec![open; interpreter::MAX_GROUP_DEPTH];
+    nested.push(eq);
+    nested.extend(vec![close; interpreter::MAX_GROUP_DEPTH]);
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&nested)),
+        Ok(true)
+    );
+    nested.insert(0, open);
+    nested.push(close);
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&nested)),
+        Err(InterpErr::StackOverflow)
+    );
+}
+
+#[test]
+fn it_compares_wide_numerics() {
+    let cap = U256::from(u128::MAX) + 1;
+    let user = [PactType::Numeric(Numeric::new(cap))];
+    // INPUT(0) <= USER(0)
+    let lte = [
+        OpCode::COMP(Comparator::new(OpComp::GT).invert()).into(),
+        0x00,
+    ];
+
+    for (amount, expected) in [
+        (U256::from(u128::MAX), true),
+        (cap, true),
+        (cap + 1, false),
+        (U256::MAX, false),
+    ] {
+        let input = [PactType::Numeric(Numeric::new(amount))];
+        assert_eq!(interpreter::interpret(&input, &user, &lte), Ok(expected));
+    }
+}
+
+#[test]
+fn it_compares_signed_numerics() {
+    let user = [PactType::Signed(Signed(-10))];
+    // INPUT(0) > USER(0)
+    let gt = [OpCode::COMP(Comparator::new(OpComp::GT)).into(), 0x00];
+
+    for (delta, expected) in [
+        (i128::MIN, false),
+        (-11, false),
+        (-10, false),
+        (-9, true),
+        (0, true),
+        (i128::MAX, true),
+    ] {
+        let input = [PactType::Signed(Signed(delta))];
+        assert_eq!(interpreter::interpret(&input, &user, &gt), Ok(expected));
+    }
+}
+
+#[test]
+fn it_fails_to_compare_signed_and_unsigned_numerics() {
+    let eq = [OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00];
+    assert_eq!(
+        interpreter::interpret(
+            &[PactType::Signed(Signed(5))],
+            &[PactType::Numeric(Numeric::from(5))],
+            &eq
+        ),
+        Err(InterpErr::TypeMismatch)
+    );
+    assert_eq!(
+        interpreter::interpret(
+            &[PactType::Numeric(Numeric::from(5))],
+            &[PactType::Signed(Signed(5))],
+            &eq
+        ),
+        Err(InterpErr::TypeMismatch)
+    );
+    // Unless explicitly converted
+    let converted = Signed::try_from(Numeric::from(5)).unwrap();
+    assert_eq!(
+        interpreter::interpret(
+            &[PactType::Signed(converted)],
+            &[PactType::Signed(Signed(5))],
+            &eq
+        ),
+        Ok(true)
+    );
+}
+
+#[test]
+fn it_compares_bools() {
+    let user = [
+        PactType::Bool(true),
+        PactType::List(vec![PactType::Bool(false)]),
+    ];
+    let eq = [OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00];
+    let one_of = [OpCode::COMP(Comparator::new(OpComp::IN)).into(), 0x01];
+
+    assert_eq!(
+        interpreter::interpret(&[PactType::Bool(true)], &user, &eq),
+        Ok(true)
+    );
+    assert_eq!(
+        interpreter::interpret(&[PactType::Bool(false)], &user, &eq),
+        Ok(false)
+    );
+    assert_eq!(
+        interpreter::interpret(&[PactType::Bool(false)], &user, &one_of),
+        Ok(true)
+    );
+
+    // Bools are unordered and distinct from numerics
+    let gt = [OpCode::COMP(Comparator::new(OpComp::GT)).into(), 0x00];
+    assert_eq!(
+        interpreter::interpret(&[PactType::Bool(true)], &user, &gt),
+        Err(InterpErr::BadTypeOperation)
+    );
+    assert_eq!(
+        interpreter::interpret(&[PactType::Numeric(Numeric::from(1))], &user, &eq),
+        Err(InterpErr::TypeMismatch)
+    );
+}
+
+#[test]
+fn it_interprets_a_borrowed_contract() {
+    let contract = Contract {
+        data_table: DataTable::new(vec![
+            PactType::StringLike(StringLike(b"alice".to_vec())),
+            PactType::List(vec![
+                PactType::List(vec![
+                    PactType::Numeric(Numeric::from(1)),
+                    PactType::Numeric(Numeric::from(2)),
+                ]),
+                PactType::StringLike(StringLike(b"bob".to_vec())),
+            ]),
+        ]),
+        bytecode: [
+            // INPUT(0) == USER(0) AND INPUT(1) IN USER(1)
+            OpCode::COMP(Comparator::new(OpComp::EQ)).into(),
+            0x00,
+            OpCode::CONJ(Conjunction::new(OpConj::AND)).into(),
+            OpCode::COMP(Comparator::new(OpComp::IN)).into(),
+            0x11,
+        ]
+        .to_vec(),
+    };
+
+    for format in [BinaryFormat::V0, BinaryFormat::V1] {
+        let mut buf = Vec::new();
+        contract.encode_with(format, &mut buf);
+        let borrowed = ContractRef::decode(&buf).expect("it decodes");
+
+        let pair = PactType::List(vec![
+            PactType::Numeric(Numeric::from(1)),
+            PactType::Numeric(Numeric::from(2)),
+        ]);
+        let input = [PactType::StringLike(StringLike(b"alice".to_vec())), pair];
+        assert_eq!(
+            interpreter::interpret(&input, &borrowed.data_table, borrowed.bytecode),
+            Ok(true)
+        );
+
+        let input = [
+            PactType::StringLike(StringLike(b"alice".to_vec())),
+            PactType::StringLike(StringLike(b"carol".to_vec())),
+        ];
+        assert_eq!(
+            interpreter::interpret(&input, &borrowed.data_table, borrowed.bytecode),
+            Ok(false)
+        );
+    }
+}
+
+#[test]
+fn it_ignores_contract_metadata() {
+    let contract = Contract {
+        data_table: DataTable::new(vec![PactType::StringLike(StringLike(b"alice".to_vec()))]),
+        bytecode: [OpCode::COMP(Comparator::new(OpComp::EQ)).into(), 0x00].to_vec(),
+    }
+    .with_metadata(Metadata {
+        parameters: vec!["payee".into()],
+        description: Some("Pay alice".into()),
+        ..Default::default()
+    });
+    let mut buf = Vec::new();
+    contract.encode_with(BinaryFormat::V1, &mut buf);
+    let borrowed = ContractRef::decode(&buf).expect("it decodes");
+    assert!(borrowed.metadata.is_some());
+
+    let input = [PactType::StringLike(StringLike(b"alice".to_vec()))];
+    assert_eq!(
+        interpreter::interpret(&input, &borrowed.data_table, borrowed.bytecode),
+        Ok(true)
+    );
+    let input = [PactType::StringLike(StringLike(b"carol".to_vec()))];
+    assert_eq!(
+        interpreter::interpret(&input, &borrowed.data_table, borrowed.bytecode),
+        Ok(false)
+    );
+}
+
+#[test]
+fn it_validates_bytecode_without_evaluating() {
+    let comp: u8 = OpCode::COMP(Comparator::new(OpComp::EQ)).into();
+    let and: u8 = OpCode::CONJ(Conjunction::new(OpConj::AND)).into();
+    let open: u8 = OpCode::GROUP(Group::new(OpGroup::OPEN)).into();
+    let close: u8 = OpCode::GROUP(Group::new(OpGroup::CLOSE)).into();
+    let require: u8 = OpCode::REQUIRE(OpPage::new(1).unwrap()).into();
+    let require_2: u8 = OpCode::REQUIRE(OpPage::new(2).unwrap()).into();
+
+    let valid: Vec<Vec<u8>> = vec![
+        vec![comp, 0x00],
+        // Declared pages are checked when interpreting, not validating
+        vec![require, require_2, comp, 0x00],
+        vec![comp, 0x00, comp, 0x11],
+        vec![comp, 0x00, and, open, comp, 0x00, and, comp, 0x11, close],
+        vec![open, comp, 0x00, close, open, comp, 0x11, close],
+    ];
+    for source in valid {
+        assert_eq!(interpreter::validate(&source), Ok(()), "{:?}", source);
+    }
+
+    let invalid: Vec<(Vec<u8>, InterpErr)> = vec![
+        (vec![], InterpErr::UnexpectedEOI("incomplete operation")),
+        (vec![comp], InterpErr::UnexpectedEOI("expected index")),
+        (
+            vec![comp, 0x00, and],
+            InterpErr::UnexpectedEOI("incomplete operation"),
+        ),
+        (
+            vec![open, comp, 0x00],
+            InterpErr::UnexpectedEOI("unclosed group"),
+        ),
+        (vec![and], InterpErr::UnexpectedOpCode(and)),
+        (vec![open, close], InterpErr::UnexpectedOpCode(close)),
+        (
+            vec![open, comp, 0x00, comp, 0x00, close],
+            InterpErr::UnexpectedOpCode(comp),
+        ),
+        (vec![comp, 0x00, 0x2f], InterpErr::InvalidOpCode(0x2f)),
+        (vec![open; 17], InterpErr::StackOverflow),
+        (
+            vec![comp, 0x00, require],
+            InterpErr::UnexpectedOpCode(require),
+        ),
+        (
+            vec![require, require, comp, 0x00],
+            InterpErr::UnexpectedOpCode(require),
+        ),
+        (
+            vec![require_2, require, comp, 0x00],
+            InterpErr::UnexpectedOpCode(require),
+        ),
+    ];
+    for (source, err) in invalid {
+        assert_eq!(interpreter::validate(&source), Err(err), "{:?}", source);
+    }
+
+    // Trailing garbage after a failed contract is not parsed, but fails validation
+    let source = [comp, 0x00, comp, 0x00, comp, 0x00, 0x2f];
+    let input = [PactType::Bool(true)];
+    let user = [PactType::Bool(false)];
+    assert_eq!(interpreter::interpret(&input, &user, &source), Ok(false));
+    assert_eq!(
+        interpreter::validate(&source),
+        Err(InterpErr::InvalidOpCode(0x2f))
+    );
+}
+
+#[test]
+fn it_rejects_contracts_requiring_unsupported_features() {
+    let comp: u8 = OpCode::COMP(Comparator::new(OpComp::EQ)).into();
+    let page = OpPage::new(9).unwrap();
+    let require: u8 = OpCode::REQUIRE(page).into();
+    let input = [PactType::Bool(true)];
+    let user = [PactType::Bool(false)];
+    assert!(!Features::SUPPORTED.contains(page));
+
+    // The contract is rejected before its first clause is evaluated
+    let source = [require, comp, 0x00];
+    assert_eq!(
+        interpreter::interpret(&input, &user, &source),
+        Err(InterpErr::UnsupportedFeature(9))
+    );
+    // An extended OpCode from an unsupported page can not be parsed
+    let source = [comp, 0x00, 0b1000_0000, page.id(), 0x00];
+    assert_eq!(
+        interpreter::interpret(&input, &user, &source),
+        Err(InterpErr::UnsupportedFeature(9))
+    );
+    // Required pages may be read ahead of interpreting
+    assert_eq!(
+        Features::required_by(&[require, 0b1000_0000, page.id(), 0x00]),
+        Ok(Features::default().with(page))
+    );
+    // A REQUIRE must precede all other OpCodes, unless the contract has already failed
+    assert_eq!(
+        interpreter::interpret(&input, &input, &[comp, 0x00, require]),
+        Err(InterpErr::UnexpectedOpCode(require))
+    );
+    assert_eq!(
+        interpreter::interpret(&input, &user, &[comp, 0x00, comp, 0x00, require]),
+        Ok(false)
+    );
+}
+
+#[test]
+fn it_does_string_comparisons() {
+    let require = OpCode::REQUIRE(OpPage::STRING);
+    let input = [
+        PactType::StringLike(StringLike(b"order:1234".to_vec())),
+        PactType::StringLike(StringLike(b"1234".to_vec())),
+    ];
+    let user = [
+        PactType::StringLike(StringLike(b"order:".to_vec())),
+        PactType::StringLike(StringLike(b"1234".to_vec())),
+        PactType::StringLike(StringLike(b"".to_vec())),
+        PactType::StringLike(StringLike(b"der:12".to_vec())),
+    ];
+    let input_vs_input = |op| Comparator::new(op).load(OpLoad::INPUT_VS_INPUT);
+    let tests = [
+        (Comparator::new(OpComp::PREFIX).indices(0, 0), true),
+        (Comparator::new(OpComp::PREFIX).indices(0, 1), false),
+        (Comparator::new(OpComp::PREFIX).indices(0, 2), true),
+        (Comparator::new(OpComp::SUFFIX).indices(0, 1), true),
+        (Comparator::new(OpComp::SUFFIX).indices(0, 0), false),
+        (Comparator::new(OpComp::SUFFIX).indices(1, 1), true),
+        (Comparator::new(OpComp::CONTAINS).indices(0, 3), true),
+        (Comparator::new(OpComp::CONTAINS).indices(1, 3), false),
+        (Comparator::new(OpComp::CONTAINS).indices(1, 2), true),
+        (input_vs_input(OpComp::SUFFIX).indices(0, 1), true),
+        (input_vs_input(OpComp::PREFIX).indices(0, 1), false),
+        // The RHS is longer than the LHS
+        (input_vs_input(OpComp::CONTAINS).indices(1, 0), false),
+        (input_vs_input(OpComp::CONTAINS).indices(0, 0), true),
+    ];
+    for (comparator, expected) in tests {
+        let source = bytecode(&[require, OpCode::COMP(comparator)]);
+        assert_eq!(
+            interpreter::interpret(&input, &user, &source),
+            Ok(expected),
+            "{:?}",
+            comparator
+        );
+        let source = bytecode(&[require, OpCode::COMP(comparator.invert())]);
+        assert_eq!(
+            interpreter::interpret(&input, &user, &source),
+            Ok(!expected),
+            "{:?}",
+            comparator
+        );
+    }
+}
+
+#[test]
+fn it_fails_with_bad_type_operation_on_string_comparators() {
+    let require = OpCode::REQUIRE(OpPage::STRING);
+    let tests = [
+        (
+            PactType::Numeric(Numeric::from(12)),
+            PactType::Numeric(Numeric::from(1)),
+            InterpErr::BadTypeOperation,
+        ),
+        (
+            PactType::Signed(Signed(-12)),
+            PactType::Signed(Signed(-1)),
+            InterpErr::BadTypeOperation,
+        ),
+        (
+            PactType::Bool(true),
+            PactType::Bool(true),
+            InterpErr::BadTypeOperation,
+        ),
+        (
+            PactType::StringLike(StringLike(b"a".to_vec())),
+            PactType::List(vec![PactType::StringLike(StringLike(b"a".to_vec()))]),
+            InterpErr::BadTypeOperation,
+        ),
+        (
+            PactType::StringLike(StringLike(b"12".to_vec())),
+            PactType::Numeric(Numeric::from(1)),
+            InterpErr::TypeMismatch,
+        ),
+    ];
+    for (lhs, rhs, err) in &tests {
+        for op in [OpComp::PREFIX, OpComp::SUFFIX, OpComp::CONTAINS] {
+            let source = bytecode(&[require, OpCode::COMP(Comparator::new(op))]);
+            let result = interpreter::interpret(
+                std::slice::from_ref(lhs),
+                std::slice::from_ref(rhs),
+                &source,
+            );
+            assert_eq!(result.as_ref().err(), Some(err), "{:?}", op);
+        }
+    }
+}
+
+#[test]
+fn it_requires_string_comparators_are_declared() {
+    let require: u8 = OpCode::REQUIRE(OpPage::STRING).into();
+    let comp = bytecode(&[OpCode::COMP(Comparator::new(OpComp::PREFIX))]);
+    let input = [PactType::StringLike(StringLike(b"abc".to_vec()))];
+    let user = [PactType::StringLike(StringLike(b"a".to_vec()))];
+    assert!(Features::SUPPORTED.contains(OpPage::STRING));
+
+    let declared = [&[require][..], &comp].concat();
+    assert_eq!(interpreter::interpret(&input, &user, &declared), Ok(true));
+    assert_eq!(interpreter::validate(&declared), Ok(()));
+
+    for source in [comp.clone(), [&comp[..], &[require]].concat()] {
+        assert_eq!(
+            interpreter::validate(&source),
+            Err(InterpErr::UndeclaredFeature(1))
+        );
+    }
+    assert_eq!(
+        interpreter::interpret(&input, &user, &comp),
+        Err(InterpErr::UndeclaredFeature(1))
+    );
+}
+
+#[test]
+fn it_does_glob_comparisons() {
+    let require = OpCode::REQUIRE(OpPage::GLOB);
+    let glob = Comparator::new(OpComp::GLOB);
+    let contract = Contract {
+        data_table: DataTable::new(vec![
+            PactType::StringLike(StringLike(b"collection-*-gold".to_vec())),
+            PactType::StringLike(StringLike(b"?x*".to_vec())),
+        ]),
+        bytecode: bytecode(&[
+            require,
+            OpCode::COMP(glob.indices(0, 0)),
+            OpCode::CONJ(Conjunction::new(OpConj::AND)),
+            OpCode::COMP(glob.invert().indices(1, 1)),
+        ]),
+    };
+    let mut encoded = Vec::new();
+    contract.encode(&mut encoded);
+    let contract = ContractRef::decode(&encoded).unwrap();
+
+    let tests: [(&[u8], &[u8], bool); 5] = [
+        (b"collection-7-gold", b"key", true),
+        (b"collection--gold", b"", true),
+        (b"collection-7-gold", b"axe", false),
+        (b"collection-7-silver", b"key", false),
+        (b"Collection-7-gold", b"key", false),
+    ];
+    for (name, key, expected) in tests {
+        let input = [
+            PactType::StringLike(StringLike(name.to_vec())),
+            PactType::StringLike(StringLike(key.to_vec())),
+        ];
+        assert_eq!(
+            interpreter::interpret(&input, &contract.data_table, contract.bytecode),
+            Ok(expected)
+        );
+    }
+}
+
+#[test]
+fn it_fails_glob_comparisons_with_invalid_patterns() {
+    let require = OpCode::REQUIRE(OpPage::GLOB);
+    let glob = OpCode::COMP(Comparator::new(OpComp::GLOB));
+    let input = [PactType::StringLike(StringLike(b"a".to_vec()))];
+    // Contracts with invalid patterns fail to decode, the interpreter does not assume this
+    let user = [PactType::StringLike(StringLike(b"a\\".to_vec()))];
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[require, glob])),
+        Err(InterpErr::InvalidPattern("invalid escape"))
+    );
+    let contract = Contract {
+        data_table: DataTable::new(vec![PactType::StringLike(StringLike(b"?".repeat(128)))]),
+        bytecode: bytecode(&[require, glob]),
+    };
+    assert_eq!(
+        interpreter::interpret(&input, &contract.data_table, &contract.bytecode),
+        Err(InterpErr::InvalidPattern("pattern too long"))
+    );
+    let user = [PactType::Numeric(Numeric::from(1))];
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[require, glob])),
+        Err(InterpErr::TypeMismatch)
+    );
+    let user = [PactType::StringLike(StringLike(b"a".to_vec()))];
+    assert_eq!(
+        interpreter::interpret(&input, &user, &bytecode(&[glob])),
+        Err(InterpErr::UndeclaredFeature(2))
+    );
+}
+
+#[test]
+fn it_does_length_comparisons() {
+    let require = OpCode::REQUIRE(OpPage::LENGTH);
+    let recipients = |n: u64| {
+        PactType::List(
+            (0..n)
+                .map(|i| PactType::StringLike(StringLike(i.to_be_bytes().to_vec())))
+                .collect(),
+        )
+    };
+    let input = [
+        PactType::StringLike(StringLike(b"a".repeat(64))),
+        recipients(10),
+        PactType::Numeric(Numeric::from(10)),
+        PactType::StringLike(StringLike(vec![])),
+    ];
+    let user = [
+        PactType::Numeric(Numeric::from(64)),
+        PactType::Numeric(Numeric::from(10)),
+        PactType::Numeric(Numeric::from(0)),
+    ];
+    let tests = [
+        // The remark must be at most 64 bytes
+        (Comparator::new(OpComp::LEN_GT).invert().indices(0, 0), true),
+        (Comparator::new(OpComp::LEN_EQ).indices(0, 0), true),
+        (Comparator::new(OpComp::LEN_GTE).indices(0, 0), true),
+        (Comparator::new(OpComp::LEN_GT).indices(0, 1), true),
+        // The batch must have at most 10 recipients
+        (Comparator::new(OpComp::LEN_GT).invert().indices(1, 1), true),
+        (Comparator::new(OpComp::LEN_GTE).indices(1, 0), false),
+        (Comparator::new(OpComp::LEN_EQ).indices(3, 2), true),
+        (Comparator::new(OpComp::LEN_GT).indices(3, 2), false),
+        // The batch has as many recipients as an input
+        (
+            Comparator::new(OpComp::LEN_EQ)
+                .load(OpLoad::INPUT_VS_INPUT)
+                .indices(1, 2),
+            true,
+        ),
+        (
+            Comparator::new(OpComp::LEN_GT)
+                .load(OpLoad::INPUT_VS_INPUT)
+                .indices(0, 2),
+            true,
+        ),
+    ];
+    for (comparator, expected) in tests {
+        let inverted = Comparator {
+            invert: !comparator.invert,
+            ..comparator
+        };
+        for (comparator, expected) in [(comparator, expected), (inverted, !expected)] {
+            let source = bytecode(&[require, OpCode::COMP(comparator)]);
+            assert_eq!(
+                interpreter::interpret(&input, &user, &source),
+                Ok(expected),
+                "{:?}",
+                comparator
+            );
+        }
+    }
+    // A nested list is one element
+    let input = [PactType::List(vec![recipients(3)])];
+    let source = bytecode(&[require, OpCode::COMP(Comparator::new(OpComp::LEN_EQ))]);
+    assert_eq!(
+        interpreter::interpret(&input, &[PactType::Numeric(Numeric::from(1))], &source),
+        Ok(true)
+    );
+}
+
+#[test]
+fn it_fails_length_comparisons_with_bad_types() {
+    let require = OpCode::REQUIRE(OpPage::LENGTH);
+    let source = bytecode(&[require, OpCode::COMP(Comparator::new(OpComp::LEN_EQ))]);
+    let string = PactType::StringLike(StringLike(b"abc".to_vec()));
+    let numeric = PactType::Numeric(Numeric::from(3));
+    let tests = [
+        (
+            numeric.clone(),
+            numeric.clone(),
+            InterpErr::BadTypeOperation,
+        ),
+        (
+            PactType::Signed(Signed(3)),
+            numeric.clone(),
+            InterpErr::BadTypeOperation,
+        ),
+        (
+            PactType::Bool(true),
+            numeric.clone(),
+            InterpErr::BadTypeOperation,
+        ),
+        (string.clone(), string.clone(), InterpErr::TypeMismatch),
+        (
+            string.clone(),
+            PactType::Signed(Signed(3)),
+            InterpErr::TypeMismatch,
+        ),
+        (
+            string,
+            PactType::List(vec![numeric]),
+            InterpErr::BadTypeOperation,
+        ),
+    ];
+    for (lhs, rhs, err) in tests {
+        assert_eq!(interpreter::interpret(&[lhs], &[rhs], &source), Err(err));
+    }
+    assert_eq!(
+        interpreter::interpret(
+            &[PactType::StringLike(StringLike(vec![]))],
+            &[PactType::Numeric(Numeric::from(0))],
+            &bytecode(&[OpCode::COMP(Comparator::new(OpComp::LEN_EQ))])
+        ),
+        Err(InterpErr::UndeclaredFeature(3))
+    );
+}
+
+#[test]
+fn it_evaluates_arithmetic_constraints() {
+    let require = OpCode::REQUIRE(OpPage::ARITH);
+    let numeric = |n: u64| PactType::Numeric(Numeric::from(n));
+    // amount, fee, price, quantity, budget
+    let input = [
+        numeric(900),
+        numeric(100),
+        numeric(25),
+        numeric(40),
+        numeric(1000),
+    ];
+    let user = [numeric(1000), numeric(5)];
+    let tests = [
+        // amount + fee <= 1000
+        (Arithmetic::new(OpArith::ADD, OpComp::GT).invert(), true),
+        // amount + fee > 1000
+        (Arithmetic::new(OpArith::ADD, OpComp::GT), false),
+        // amount + 5 >= 1000
+        (
+            Arithmetic::new(OpArith::ADD, OpComp::GTE).term(LoadSource::DataTable, 1),
+            false,
+        ),
+        // amount - fee == 800
+        (
+            Arithmetic::new(OpArith::SUB, OpComp::EQ)
+                .load(OpLoad::INPUT_VS_INPUT)
+                .indices(0, 4),
+            false,
+        ),
+        // price * quantity <= budget
+        (
+            Arithmetic::new(OpArith::MUL, OpComp::GT)
+                .load(OpLoad::INPUT_VS_INPUT)
+                .indices(2, 4)
+                .term(LoadSource::Input, 3)
+                .invert(),
+            true,
+        ),
+        // price * quantity == 1000
+        (
+            Arithmetic::new(OpArith::MUL, OpComp::EQ)
+                .indices(2, 0)
+                .term(LoadSource::Input, 3),
+            true,
+        ),
+    ];
+    for (arithmetic, expected) in tests {
+        let source = bytecode(&[require, OpCode::ARITH(arithmetic)]);
+        assert_eq!(
+            interpreter::interpret(&input, &user, &source),
+            Ok(expected),
+            "{:?}",
+            arithmetic
+        );
+    }
+}
+
+#[test]
+fn it_denies_arithmetic_which_overflows() {
+    let require = OpCode::REQUIRE(OpPage::ARITH);
+    let or = OpCode::CONJ(Conjunction::new(OpConj::OR));
+    let open = OpCode::GROUP(Group::new(OpGroup::OPEN).invert());
+    let close = OpCode::GROUP(Group::new(OpGroup::CLOSE));
+    let always = OpCode::COMP(Comparator::new(OpComp::EQ).load(OpLoad::INPUT_VS_INPUT));
+    let input = [
+        PactType::Numeric(Numeric::new(U256::MAX)),
+        PactType::Numeric(Numeric::from(2)),
+    ];
+    let user = [PactType::Numeric(Numeric::from(0))];
+
+    for op in [OpArith::ADD, OpArith::MUL] {
+        for arithmetic in [
+            Arithmetic::new(op, OpComp::GT),
+            Arithmetic::new(op, OpComp::GT).invert(),
+        ] {
+            let arithmetic = OpCode::ARITH(arithmetic);
+            // Neither inversion nor a disjunction permits the contract
+            for ops in [
+                vec![require, arithmetic],
+                vec![require, always, or, arithmetic],
+                vec![require, open, arithmetic, or, always, close],
+                vec![require, arithmetic, always],
+            ] {
+                assert_eq!(
+                    interpreter::interpret(&input, &user, &bytecode(&ops)),
+                    Ok(false),
+                    "{:?}",
+                    ops
+                );
+            }
+        }
+    }
+    // Numerics are unsigned, so subtraction may overflow too
+    let source = bytecode(&[
+        require,
+        OpCode::ARITH(
+            Arithmetic::new(OpArith::SUB, OpComp::GT)
+                .indices(1, 0)
+                .term(LoadSource::Input, 0)
+                .invert(),
+        ),
+    ]);
+    assert_eq!(interpreter::interpret(&input, &user, &source), Ok(false));
+}
+
+#[test]
+fn it_fails_arithmetic_with_bad_operands() {
+    let require = OpCode::REQUIRE(OpPage::ARITH);
+    let arithmetic = OpCode::ARITH(Arithmetic::new(OpArith::ADD, OpComp::EQ));
+    let source = bytecode(&[require, arithmetic]);
+    let numeric = PactType::Numeric(Numeric::from(1));
+    let signed = PactType::Signed(Signed(1));
+    let tests = [
+        (
+            [numeric.clone(), signed.clone()],
+            numeric.clone(),
+            InterpErr::TypeMismatch,
+        ),
+        (
+            [numeric.clone(), numeric.clone()],
+            signed.clone(),
+            InterpErr::TypeMismatch,
+        ),
+        (
+            [signed.clone(), signed.clone()],
+            signed,
+            InterpErr::BadTypeOperation,
+        ),
+    ];
+    for (input, user, err) in tests {
+        assert_eq!(interpreter::interpret(&input, &[user], &source), Err(err));
+    }
+
+    let input = [numeric.clone()];
+    assert_eq!(
+        interpreter::interpret(&input, std::slice::from_ref(&numeric), &source),
+        Err(InterpErr::MissingIndex(1))
+    );
+    assert_eq!(
+        interpreter::interpret(&input, &[numeric], &bytecode(&[arithmetic])),
+        Err(InterpErr::UndeclaredFeature(4))
+    );
+}
+
+#[test]
+fn it_does_bitmask_comparisons() {
+    const BURN: u64 = 0b0100;
+    let require = OpCode::REQUIRE(OpPage::BITMASK);
+    let numeric = |n: u64| PactType::Numeric(Numeric::from(n));
+    // BURN, the bits above 0-3, MINT | TRANSFER
+    let user = [numeric(BURN), numeric(!0b1111), numeric(0b0011)];
+    let tests = [
+        // flags must not contain BURN
+        (Comparator::new(OpComp::MASK_ANY).invert(), 0b0011, true),
+        (Comparator::new(OpComp::MASK_ANY).invert(), 0b0111, false),
+        // only bits 0-3 may be set
+        (
+            Comparator::new(OpComp::MASK_ANY).indices(0, 1).invert(),
+            0b1111,
+            true,
+        ),
+        (
+            Comparator::new(OpComp::MASK_ANY).indices(0, 1).invert(),
+            0b1_0000,
+            false,
+        ),
+        // flags must contain both MINT and TRANSFER
+        (
+            Comparator::new(OpComp::MASK_ALL).indices(0, 2),
+            0b1011,
+            true,
+        ),
+        (
+            Comparator::new(OpComp::MASK_ALL).indices(0, 2),
+            0b1001,
+            false,
+        ),
+        // no flags are set
+        (Comparator::new(OpComp::MASK_ANY), 0, false),
+        (Comparator::new(OpComp::MASK_ALL), 0, false),
+    ];
+    for (comparator, flags, expected) in tests {
+        let source = bytecode(&[require, OpCode::COMP(comparator)]);
+        assert_eq!(
+            interpreter::interpret(&[numeric(flags)], &user, &source),
+            Ok(expected),
+            "{:?} {:#b}",
+            comparator,
+            flags
+        );
+    }
+    // Any flags have every bit of an empty mask and none of its bits
+    let empty = [numeric(0)];
+    for op in [OpComp::MASK_ALL, OpComp::MASK_ANY] {
+        let source = bytecode(&[require, OpCode::COMP(Comparator::new(op))]);
+        assert_eq!(
+            interpreter::interpret(&[numeric(0b1010)], &empty, &source),
+            Ok(op == OpComp::MASK_ALL)
+        );
+    }
+
+    // flags masked by the bits 0-3 must equal MINT | TRANSFER
+    let mask = MaskEq::new(LoadSource::DataTable, 0).indices(0, 1);
+    let user = [numeric(0b1111), numeric(0b0011)];
+    for (flags, expected) in [(0b1_0011, true), (0b0011, true), (0b0111, false)] {
+        for mask in [mask, mask.invert()] {
+            let source = bytecode(&[require, OpCode::MASK(mask)]);
+            assert_eq!(
+                interpreter::interpret(&[numeric(flags)], &user, &source),
+                Ok(expected ^ mask.invert),
+                "{:?} {:#b}",
+                mask,
+                flags
+            );
+        }
+    }
+    assert_eq!(
+        interpreter::interpret(&[numeric(0)], &user, &bytecode(&[OpCode::MASK(mask)])),
+        Err(InterpErr::UndeclaredFeature(5))
+    );
+    let strings = [
+        PactType::StringLike(StringLike(b"\x0f".to_vec())),
+        numeric(0),
+    ];
+    assert_eq!(
+        interpreter::interpret(
+            &[numeric(0)],
+            &strings,
+            &bytecode(&[require, OpCode::MASK(mask)])
+        ),
+        Err(InterpErr::TypeMismatch)
+    );
+}
+
+#[test]
+fn it_fails_bitmask_comparisons_with_bad_types() {
+    let require = OpCode::REQUIRE(OpPage::BITMASK);
+    let mask = OpCode::COMP(Comparator::new(OpComp::MASK_ANY));
+    let source = bytecode(&[require, mask]);
+    let numeric = PactType::Numeric(Numeric::from(1));
+    let tests = [
+        (
+            PactType::Signed(Signed(1)),
+            PactType::Signed(Signed(1)),
+            InterpErr::BadTypeOperation,
+        ),
+        (
+            PactType::Bool(true),
+            PactType::Bool(true),
+            InterpErr::BadTypeOperation,
+        ),
+        (
+            numeric.clone(),
+            PactType::StringLike(StringLike(b"\x01".to_vec())),
+            InterpErr::TypeMismatch,
+        ),
+        (
+            numeric.clone(),
+            PactType::List(vec![numeric.clone()]),
+            InterpErr::BadTypeOperation,
+        ),
+    ];
+    for (input, user, err) in tests {
+        assert_eq!(interpreter::interpret(&[input], &[user], &source), Err(err));
+    }
+    assert_eq!(
+        interpreter::interpret(
+            std::slice::from_ref(&numeric),
+            std::slice::from_ref(&numeric),
+            &bytecode(&[mask])
+        ),
+        Err(InterpErr::UndeclaredFeature(5))
+    );
+}